@@ -1,7 +1,10 @@
 use derive_new::new;
 use rmf_host::{
     Content, InputSource, Timestamp,
-    audio::{self, Audio, AudioDataContext, AudioInputService},
+    audio::{
+        self, Audio, AudioCursorOptions, AudioDataContext, AudioFormat, AudioInputService,
+        ChannelLayout, SampleLayout,
+    },
     service::{ContentCursorTrait, ContentStreamServiceTrait},
 };
 use std::{
@@ -28,9 +31,8 @@ async fn play_audio_loop(mut receiver: mpsc::Receiver<Message>) {
             /*
             match message {
                 Message::End => break,
-                Message::Received(audio) => match audio.data() {
-                    AudioDataContext::None => {},
-                    AudioDataContext::U8(data) => data.get_channel_line(index)
+                Message::Received(audio) => if let AudioDataContext::F32(data) = audio.data() {
+                    let interleaved = data.plane(0);
                 },
             }
             */
@@ -67,7 +69,13 @@ impl InnerContent {
 
 async fn inner_decode_loop(path: PathBuf, sender: mpsc::Sender<Message>) -> anyhow::Result<()> {
     let input_source = InputSource::new_path(path.clone());
-    let input_service = AudioInputService::try_new(input_source)?;
+    let input_service = AudioInputService::try_new(input_source)?.with_cursor_options(
+        AudioCursorOptions::default()
+            .with_format(AudioFormat::F32)
+            .with_sample_rate(48_000)
+            .with_channel_layout(ChannelLayout::stereo())
+            .with_layout(SampleLayout::Packed),
+    );
     let mut cursor = input_service.cursor()?;
     const MAX_QUEUE_SIZE: usize = 10;
     let mut content_queue = VecDeque::<InnerContent>::with_capacity(MAX_QUEUE_SIZE);
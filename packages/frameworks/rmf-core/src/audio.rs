@@ -1,6 +1,8 @@
 use dyn_clone::DynClone;
 
-use crate::{Content, InnerContent, Result, Timestamp};
+use crate::{
+    Content, InnerContent, Rational, RationalTimestamp, Result, SeekMode, StreamInfo, Timestamp,
+};
 
 pub trait Audio: InnerContent + Clone {
     type U8Data: AudioData<Item = u8>;
@@ -23,6 +25,23 @@ pub trait Audio: InnerContent + Clone {
         }
     }
 
+    fn sample_rate(&self) -> u32;
+    fn channel_layout(&self) -> &ChannelLayout;
+
+    /// Samples in each channel.
+    #[inline]
+    fn samples_len(&self) -> usize {
+        match self.data() {
+            AudioDataContext::None => 0,
+            AudioDataContext::U8(d) => d.samples_len(),
+            AudioDataContext::I16(d) => d.samples_len(),
+            AudioDataContext::I32(d) => d.samples_len(),
+            AudioDataContext::I64(d) => d.samples_len(),
+            AudioDataContext::F32(d) => d.samples_len(),
+            AudioDataContext::F64(d) => d.samples_len(),
+        }
+    }
+
     #[allow(clippy::type_complexity)]
     fn data(
         &self,
@@ -55,13 +74,25 @@ pub trait AudioConstructor {
             Self::F32Data,
             Self::F64Data,
         >,
+        sample_rate: u32,
+        channel_layout: ChannelLayout,
     ) -> Result<Self::Audio>;
 }
 pub trait AudioContentCursor {
     type Item: Audio;
     fn offset(&self) -> Timestamp;
+    /// Time base of the stream's own timestamps, which offsets are rounded from.
+    fn time_base(&self) -> Rational {
+        Rational::MICROSECOND
+    }
+    /// [`AudioContentCursor::offset`] in [`AudioContentCursor::time_base`], without rounding.
+    fn native_offset(&self) -> RationalTimestamp {
+        self.offset().into()
+    }
+    /// Returns `Ok(None)` once every decodable content has been returned, and keeps doing so until the next seek.
     fn read(&mut self) -> Result<Option<Content<Self::Item>>>;
     fn seek(&mut self, timestamp: Timestamp) -> Result<()>;
+    fn seek_with_mode(&mut self, timestamp: Timestamp, mode: SeekMode) -> Result<()>;
 }
 
 pub trait AudioInput: DynClone {
@@ -69,11 +100,50 @@ pub trait AudioInput: DynClone {
     type ContentCursor: AudioContentCursor;
     fn duration(&self) -> Timestamp;
     fn sample_rate(&self) -> u32;
+    /// Every audio stream of the input.
+    fn streams(&self) -> &[StreamInfo];
+    /// The stream read by cursors of this input.
+    fn stream_index(&self) -> usize;
     fn cursor(&self) -> Result<Self::ContentCursor>;
+    fn cursor_with_options(&self, options: AudioCursorOptions) -> Result<Self::ContentCursor>;
 }
 dyn_clone::clone_trait_object!(<I,C> AudioInput<Item = I,ContentCursor=C> where I:Audio ,C:AudioContentCursor);
 
+/// How cursors deliver audio. Every `None` keeps the value of the decoded stream.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AudioCursorOptions {
+    pub format: Option<AudioFormat>,
+    pub sample_rate: Option<u32>,
+    pub channel_layout: Option<ChannelLayout>,
+    pub layout: Option<SampleLayout>,
+}
+
+impl AudioCursorOptions {
+    pub fn with_format(mut self, format: AudioFormat) -> Self {
+        self.format = Some(format);
+        self
+    }
+    pub fn with_sample_rate(mut self, sample_rate: u32) -> Self {
+        self.sample_rate = Some(sample_rate);
+        self
+    }
+    pub fn with_channel_layout(mut self, channel_layout: ChannelLayout) -> Self {
+        self.channel_layout = Some(channel_layout);
+        self
+    }
+    pub fn with_layout(mut self, layout: SampleLayout) -> Self {
+        self.layout = Some(layout);
+        self
+    }
+    /// Whether cursors deliver the decoded stream unchanged.
+    #[inline]
+    pub fn is_native(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
 #[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum AudioFormat {
     None = 0,
     U8 = 1,
@@ -95,9 +165,239 @@ pub enum AudioDataContext<U8B, I16B, I32B, I64B, F32B, F64B> {
     F64(F64B),
 }
 
+/// Where a channel is meant to be played.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ChannelPosition {
+    FrontLeft,
+    FrontRight,
+    FrontCenter,
+    LowFrequency,
+    BackLeft,
+    BackRight,
+    FrontLeftOfCenter,
+    FrontRightOfCenter,
+    BackCenter,
+    SideLeft,
+    SideRight,
+    TopCenter,
+    TopFrontLeft,
+    TopFrontCenter,
+    TopFrontRight,
+    TopBackLeft,
+    TopBackCenter,
+    TopBackRight,
+    StereoLeft,
+    StereoRight,
+    WideLeft,
+    WideRight,
+    SurroundDirectLeft,
+    SurroundDirectRight,
+    LowFrequency2,
+    TopSideLeft,
+    TopSideRight,
+    BottomFrontCenter,
+    BottomFrontLeft,
+    BottomFrontRight,
+    Unknown,
+}
+
+/// The position of every channel, in the order the channels are stored.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ChannelLayout {
+    positions: Vec<ChannelPosition>,
+}
+
+impl ChannelLayout {
+    #[inline]
+    pub fn new(positions: Vec<ChannelPosition>) -> Self {
+        Self { positions }
+    }
+
+    /// `channels` channels without known positions.
+    #[inline]
+    pub fn unspecified(channels: usize) -> Self {
+        Self::new(vec![ChannelPosition::Unknown; channels])
+    }
+
+    #[inline]
+    pub fn mono() -> Self {
+        Self::new(vec![ChannelPosition::FrontCenter])
+    }
+
+    #[inline]
+    pub fn stereo() -> Self {
+        Self::new(vec![
+            ChannelPosition::FrontLeft,
+            ChannelPosition::FrontRight,
+        ])
+    }
+
+    pub fn surround_5_1() -> Self {
+        use ChannelPosition::*;
+        Self::new(vec![
+            FrontLeft,
+            FrontRight,
+            FrontCenter,
+            LowFrequency,
+            SideLeft,
+            SideRight,
+        ])
+    }
+
+    pub fn surround_7_1() -> Self {
+        use ChannelPosition::*;
+        Self::new(vec![
+            FrontLeft,
+            FrontRight,
+            FrontCenter,
+            LowFrequency,
+            BackLeft,
+            BackRight,
+            SideLeft,
+            SideRight,
+        ])
+    }
+
+    /// The usual layout for `channels` channels, unspecified when there is none.
+    pub fn default_for(channels: usize) -> Self {
+        match channels {
+            1 => Self::mono(),
+            2 => Self::stereo(),
+            6 => Self::surround_5_1(),
+            8 => Self::surround_7_1(),
+            _ => Self::unspecified(channels),
+        }
+    }
+
+    #[inline]
+    pub fn channels(&self) -> usize {
+        self.positions.len()
+    }
+
+    #[inline]
+    pub fn positions(&self) -> &[ChannelPosition] {
+        &self.positions
+    }
+
+    #[inline]
+    pub fn position(&self, index: usize) -> Option<ChannelPosition> {
+        self.positions.get(index).copied()
+    }
+
+    /// The index of the first channel at `position`.
+    pub fn index_of(&self, position: ChannelPosition) -> Option<usize> {
+        self.positions.iter().position(|p| *p == position)
+    }
+}
+
+/// How the samples of several channels are laid out in memory.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SampleLayout {
+    /// One plane per channel.
+    #[default]
+    Planar,
+    /// A single plane with the channels interleaved.
+    Packed,
+}
+
 pub trait AudioData: Clone {
     type Item;
-    fn get_channel_line(&self, index: usize) -> Option<&[Self::Item]>;
+    fn layout(&self) -> SampleLayout;
     fn channels_len(&self) -> usize;
+    /// Samples in each channel.
+    fn samples_len(&self) -> usize;
+    /// The samples of one channel, which only planar data stores contiguously.
+    fn get_channel_line(&self, index: usize) -> Option<&[Self::Item]>;
+    fn plane(&self, index: usize) -> Option<&[Self::Item]>;
+    /// Iterates over the planes, see [`SampleLayout`].
     fn iter(&self) -> impl Iterator<Item = &'_ [Self::Item]>;
+
+    /// The samples of one channel regardless of the layout.
+    fn channel_samples(&self, channel: usize) -> impl Iterator<Item = &'_ Self::Item> {
+        let (plane, start, step) = match self.layout() {
+            SampleLayout::Planar => (self.plane(channel), 0, 1),
+            SampleLayout::Packed => (self.plane(0), channel, self.channels_len()),
+        };
+        plane
+            .filter(|_| channel < self.channels_len())
+            .unwrap_or_default()
+            .iter()
+            .skip(start)
+            .step_by(step.max(1))
+            .take(self.samples_len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    #[derive(Clone)]
+    struct TestData {
+        layout: SampleLayout,
+        planes: Vec<Vec<i32>>,
+    }
+
+    impl AudioData for TestData {
+        type Item = i32;
+        fn layout(&self) -> SampleLayout {
+            self.layout
+        }
+        fn channels_len(&self) -> usize {
+            2
+        }
+        fn samples_len(&self) -> usize {
+            3
+        }
+        fn get_channel_line(&self, index: usize) -> Option<&[i32]> {
+            (self.layout == SampleLayout::Planar)
+                .then(|| self.plane(index))
+                .flatten()
+        }
+        fn plane(&self, index: usize) -> Option<&[i32]> {
+            self.planes.get(index).map(Vec::as_slice)
+        }
+        fn iter(&self) -> impl Iterator<Item = &'_ [i32]> {
+            self.planes.iter().map(Vec::as_slice)
+        }
+    }
+
+    #[rstest]
+    #[case(1, ChannelLayout::mono())]
+    #[case(2, ChannelLayout::stereo())]
+    #[case(6, ChannelLayout::surround_5_1())]
+    #[case(3, ChannelLayout::unspecified(3))]
+    fn default_for_works(#[case] channels: usize, #[case] expected: ChannelLayout) {
+        let layout = ChannelLayout::default_for(channels);
+        assert_eq!(layout.channels(), channels);
+        assert_eq!(layout, expected);
+    }
+
+    #[rstest]
+    #[case(ChannelPosition::LowFrequency, Some(3))]
+    #[case(ChannelPosition::SideRight, Some(5))]
+    #[case(ChannelPosition::BackLeft, None)]
+    fn index_of_works(#[case] position: ChannelPosition, #[case] expected: Option<usize>) {
+        assert_eq!(ChannelLayout::surround_5_1().index_of(position), expected);
+    }
+
+    #[rstest]
+    #[case(SampleLayout::Planar, vec![vec![1, 2, 3], vec![4, 5, 6]], 1, vec![4, 5, 6])]
+    #[case(SampleLayout::Packed, vec![vec![1, 4, 2, 5, 3, 6]], 0, vec![1, 2, 3])]
+    #[case(SampleLayout::Packed, vec![vec![1, 4, 2, 5, 3, 6]], 1, vec![4, 5, 6])]
+    #[case(SampleLayout::Packed, vec![vec![1, 4, 2, 5, 3, 6]], 2, vec![])]
+    fn channel_samples_works(
+        #[case] layout: SampleLayout,
+        #[case] planes: Vec<Vec<i32>>,
+        #[case] channel: usize,
+        #[case] expected: Vec<i32>,
+    ) {
+        let data = TestData { layout, planes };
+        assert_eq!(
+            data.channel_samples(channel).copied().collect::<Vec<_>>(),
+            expected
+        );
+    }
 }
@@ -0,0 +1,243 @@
+use std::f64::consts::FRAC_PI_2;
+
+use derive_new::new;
+
+use crate::Timestamp;
+
+/// Converts decibels to a linear gain factor.
+#[inline]
+pub fn db_to_gain(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+/// Converts a linear gain factor to decibels; silence is negative infinity.
+#[inline]
+pub fn gain_to_db(gain: f32) -> f32 {
+    20.0 * gain.log10()
+}
+
+/// Shape of a fade, mapping progress in `0.0..=1.0` to a gain in the same range.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FadeCurve {
+    Linear,
+    /// Keeps the perceived loudness constant when crossfading two uncorrelated signals.
+    #[default]
+    EqualPower,
+    /// Starts slowly and rises quickly.
+    Exponential,
+    /// Rises quickly and settles slowly.
+    Logarithmic,
+    /// Eases in and out.
+    SCurve,
+}
+
+impl FadeCurve {
+    pub fn gain(self, progress: f64) -> f32 {
+        let p = progress.clamp(0.0, 1.0);
+        (match self {
+            Self::Linear => p,
+            Self::EqualPower => (p * FRAC_PI_2).sin(),
+            Self::Exponential => p * p,
+            Self::Logarithmic => 1.0 - (1.0 - p) * (1.0 - p),
+            Self::SCurve => p * p * (3.0 - 2.0 * p),
+        }) as f32
+    }
+}
+
+#[derive(new, Clone, Copy, Debug, PartialEq)]
+pub struct EnvelopeKeyframe {
+    pub timestamp: Timestamp,
+    /// Linear gain factor.
+    pub gain: f32,
+}
+
+impl EnvelopeKeyframe {
+    #[inline]
+    pub fn from_db(timestamp: Timestamp, db: f32) -> Self {
+        Self::new(timestamp, db_to_gain(db))
+    }
+}
+
+/// Gain automation interpolated linearly between keyframes and held before the first and after the last.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct VolumeEnvelope {
+    keyframes: Vec<EnvelopeKeyframe>,
+}
+
+impl VolumeEnvelope {
+    pub fn new(mut keyframes: Vec<EnvelopeKeyframe>) -> Self {
+        keyframes.sort_by_key(|k| k.timestamp.as_microseconds());
+        Self { keyframes }
+    }
+    pub fn with_keyframe(mut self, keyframe: EnvelopeKeyframe) -> Self {
+        let index = self
+            .keyframes
+            .partition_point(|k| k.timestamp <= keyframe.timestamp);
+        self.keyframes.insert(index, keyframe);
+        self
+    }
+    #[inline]
+    pub fn keyframes(&self) -> &[EnvelopeKeyframe] {
+        &self.keyframes
+    }
+    pub fn gain_at(&self, timestamp: Timestamp) -> f32 {
+        self.gain_at_seconds(timestamp.as_seconds_float64())
+    }
+    fn gain_at_seconds(&self, seconds: f64) -> f32 {
+        let index = self
+            .keyframes
+            .partition_point(|k| k.timestamp.as_seconds_float64() <= seconds);
+        match (
+            index.checked_sub(1).map(|i| &self.keyframes[i]),
+            self.keyframes.get(index),
+        ) {
+            (None, None) => 1.0,
+            (Some(k), None) | (None, Some(k)) => k.gain,
+            (Some(from), Some(to)) => {
+                let from_seconds = from.timestamp.as_seconds_float64();
+                let span = to.timestamp.as_seconds_float64() - from_seconds;
+                let progress = ((seconds - from_seconds) / span) as f32;
+                from.gain + (to.gain - from.gain) * progress
+            }
+        }
+    }
+}
+
+/// A gain change applied to audio over time.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AudioEffect {
+    /// Constant gain in decibels.
+    Gain {
+        db: f32,
+    },
+    /// Rises from silence at `start` to full level after `duration`.
+    FadeIn {
+        start: Timestamp,
+        duration: Timestamp,
+        curve: FadeCurve,
+    },
+    /// Falls from full level to silence at `end`, starting `duration` earlier.
+    FadeOut {
+        end: Timestamp,
+        duration: Timestamp,
+        curve: FadeCurve,
+    },
+    Envelope(VolumeEnvelope),
+}
+
+impl AudioEffect {
+    #[inline]
+    pub fn gain_at(&self, timestamp: Timestamp) -> f32 {
+        self.gain_at_seconds(timestamp.as_seconds_float64())
+    }
+
+    /// Like [`AudioEffect::gain_at`], for callers stepping through samples.
+    pub fn gain_at_seconds(&self, seconds: f64) -> f32 {
+        match self {
+            Self::Gain { db } => db_to_gain(*db),
+            Self::FadeIn {
+                start,
+                duration,
+                curve,
+            } => curve.gain(fade_progress(
+                seconds - start.as_seconds_float64(),
+                *duration,
+            )),
+            Self::FadeOut {
+                end,
+                duration,
+                curve,
+            } => curve.gain(fade_progress(end.as_seconds_float64() - seconds, *duration)),
+            Self::Envelope(envelope) => envelope.gain_at_seconds(seconds),
+        }
+    }
+}
+
+/// How far into a fade `elapsed` seconds are; zero-length fades jump straight to the end.
+#[inline]
+fn fade_progress(elapsed: f64, duration: Timestamp) -> f64 {
+    let duration = duration.as_seconds_float64();
+    if duration > 0.0 {
+        elapsed / duration
+    } else if elapsed >= 0.0 {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(FadeCurve::Linear, 0.25, 0.25)]
+    #[case(FadeCurve::EqualPower, 1.0, 1.0)]
+    #[case(FadeCurve::Exponential, 0.5, 0.25)]
+    #[case(FadeCurve::Logarithmic, 0.5, 0.75)]
+    #[case(FadeCurve::SCurve, 0.5, 0.5)]
+    #[case(FadeCurve::Linear, 2.0, 1.0)]
+    #[case(FadeCurve::SCurve, -1.0, 0.0)]
+    fn fade_curve_works(#[case] curve: FadeCurve, #[case] progress: f64, #[case] expected: f32) {
+        assert_eq!(curve.gain(progress), expected);
+    }
+
+    #[rstest]
+    #[case(0, 0.0)]
+    #[case(500, 0.5)]
+    #[case(1500, 1.0)]
+    fn fade_in_works(#[case] milliseconds: i64, #[case] expected: f32) {
+        let effect = AudioEffect::FadeIn {
+            start: Timestamp::from_seconds(1),
+            duration: Timestamp::from_seconds(1),
+            curve: FadeCurve::Linear,
+        };
+        assert_eq!(
+            effect.gain_at(Timestamp::from_seconds(1) + Timestamp::from_milliseconds(milliseconds)),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case(0, 1.0)]
+    #[case(1500, 0.5)]
+    #[case(2000, 0.0)]
+    #[case(3000, 0.0)]
+    fn fade_out_works(#[case] milliseconds: i64, #[case] expected: f32) {
+        let effect = AudioEffect::FadeOut {
+            end: Timestamp::from_seconds(2),
+            duration: Timestamp::from_seconds(1),
+            curve: FadeCurve::Linear,
+        };
+        assert_eq!(
+            effect.gain_at(Timestamp::from_milliseconds(milliseconds)),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case(0, 0.5)]
+    #[case(1000, 0.5)]
+    #[case(1500, 0.75)]
+    #[case(2000, 1.0)]
+    #[case(5000, 1.0)]
+    fn envelope_works(#[case] milliseconds: i64, #[case] expected: f32) {
+        let envelope = VolumeEnvelope::default()
+            .with_keyframe(EnvelopeKeyframe::new(Timestamp::from_seconds(2), 1.0))
+            .with_keyframe(EnvelopeKeyframe::new(Timestamp::from_seconds(1), 0.5));
+        assert_eq!(
+            envelope.gain_at(Timestamp::from_milliseconds(milliseconds)),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case(0.0, 1.0)]
+    #[case(-20.0, 0.1)]
+    fn db_to_gain_works(#[case] db: f32, #[case] expected: f32) {
+        assert!((db_to_gain(db) - expected).abs() < 1e-6);
+        assert!((gain_to_db(expected) - db).abs() < 1e-4);
+    }
+}
@@ -1,11 +1,22 @@
-use crate::Timestamp;
+use crate::{RationalTimestamp, Result, TimeRange, Timestamp};
 
-pub trait InnerContent {}
+pub trait InnerContent {
+    /// Keeps the part of an item spanning `range` that lies inside `used`.
+    /// Items that can't be cut, such as video frames, are kept whole.
+    fn cut(self, range: TimeRange, used: TimeRange) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        let _ = (range, used);
+        Ok(self)
+    }
+}
 
 pub struct Content<I> {
     item: I,
     offset: Timestamp,
     duration: Timestamp,
+    native: Option<(RationalTimestamp, RationalTimestamp)>,
 }
 
 impl<I> Content<I> {
@@ -15,6 +26,18 @@ impl<I> Content<I> {
     pub fn item_mut(&mut self) -> &mut I {
         &mut self.item
     }
+    pub fn into_item(self) -> I {
+        self.item
+    }
+    /// Content with the same timing holding `item` instead.
+    pub fn with_item<J>(&self, item: J) -> Content<J> {
+        Content {
+            item,
+            offset: self.offset,
+            duration: self.duration,
+            native: self.native,
+        }
+    }
 
     pub fn offset(&self) -> Timestamp {
         self.offset
@@ -22,11 +45,98 @@ impl<I> Content<I> {
     pub fn duration(&self) -> Timestamp {
         self.duration
     }
+    /// The span from [`Content::offset`] lasting [`Content::duration`].
+    pub fn range(&self) -> TimeRange {
+        TimeRange::from_duration(self.offset, self.duration)
+    }
+    /// The exact offset in the stream's own time base, or [`Content::offset`] when it has none.
+    pub fn native_offset(&self) -> RationalTimestamp {
+        self.native.map_or(self.offset.into(), |(offset, _)| offset)
+    }
+    /// The exact duration in the stream's own time base, or [`Content::duration`] when it has none.
+    pub fn native_duration(&self) -> RationalTimestamp {
+        self.native
+            .map_or(self.duration.into(), |(_, duration)| duration)
+    }
     pub fn new(item: I, offset: Timestamp, duration: Timestamp) -> Self {
         Self {
             item,
             offset,
             duration,
+            native: None,
+        }
+    }
+    /// Content timed in a stream's own time base; the microsecond offset and duration are rounded from it.
+    pub fn from_native(item: I, offset: RationalTimestamp, duration: RationalTimestamp) -> Self {
+        Self {
+            item,
+            offset: offset.to_timestamp(),
+            duration: duration.to_timestamp(),
+            native: Some((offset, duration)),
         }
     }
 }
+
+impl<I: InnerContent> Content<I> {
+    /// Keeps only the part inside `range`, cutting the item with [`InnerContent::cut`].
+    pub fn cut(self, range: TimeRange) -> Result<Self> {
+        let own = self.range();
+        let used = range.clamp_range(&own);
+        if used == own {
+            return Ok(self);
+        }
+        let item = self.item.cut(own, used)?;
+        Ok(Self::new(item, used.start(), used.duration()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    /// Records the last cut as `(start, end)` relative to the item in milliseconds.
+    #[derive(Debug, PartialEq)]
+    struct Item(Option<(i64, i64)>);
+
+    impl InnerContent for Item {
+        fn cut(self, range: TimeRange, used: TimeRange) -> Result<Self> {
+            Ok(Self(Some((
+                (used.start() - range.start()).as_milliseconds(),
+                (used.end() - range.start()).as_milliseconds(),
+            ))))
+        }
+    }
+
+    #[rstest]
+    #[case(0, 100, None, (10, 10))]
+    #[case(12, 100, Some((2, 10)), (12, 8))]
+    #[case(0, 15, Some((0, 5)), (10, 5))]
+    #[case(12, 18, Some((2, 8)), (12, 6))]
+    fn cut_works(
+        #[case] start: i64,
+        #[case] end: i64,
+        #[case] expected_cut: Option<(i64, i64)>,
+        #[case] expected_timing: (i64, i64),
+    ) {
+        let content = Content::new(
+            Item(None),
+            Timestamp::from_milliseconds(10),
+            Timestamp::from_milliseconds(10),
+        )
+        .cut(TimeRange::new(
+            Timestamp::from_milliseconds(start),
+            Timestamp::from_milliseconds(end),
+        ))
+        .unwrap();
+        assert_eq!(
+            (
+                content.offset().as_milliseconds(),
+                content.duration().as_milliseconds()
+            ),
+            expected_timing
+        );
+        assert_eq!(content.into_item(), Item(expected_cut));
+    }
+}
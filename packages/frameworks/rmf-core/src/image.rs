@@ -1,7 +1,120 @@
+use derive_new::new;
+
 use crate::{InnerContent, Result, Size};
 
 pub trait Image: InnerContent + Clone {
+    /// Builds an RGBA image from tightly packed rows.
     fn new_size(size: Size, data: &[u8]) -> Result<Self>;
+    fn new_planes(format: PixelFormat, size: Size, planes: Vec<ImagePlane>) -> Result<Self>;
+    /// Every plane with its row padding removed, one after another. This copies; prefer [`Image::plane`].
     fn data_bytes(&self) -> Vec<u8>;
     fn size(&self) -> Size;
+    fn pixel_format(&self) -> PixelFormat;
+    /// Borrows the plane's pixels, row padding included, without copying them.
+    fn plane(&self, index: usize) -> Option<&[u8]>;
+    /// Bytes between the starts of two consecutive rows of the plane.
+    fn stride(&self, index: usize) -> Option<usize>;
+    #[inline]
+    fn plane_count(&self) -> usize {
+        self.pixel_format().plane_count()
+    }
+}
+
+#[derive(new, Clone, Debug, PartialEq, Eq)]
+pub struct ImagePlane {
+    pub data: Vec<u8>,
+    pub stride: usize,
+}
+
+/// Pixel layouts an image can hold. 16-bit and 10-bit formats store little endian 16-bit samples.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PixelFormat {
+    Rgba,
+    Bgra,
+    Rgb24,
+    Gray8,
+    Yuv420p,
+    Yuv422p,
+    Yuv444p,
+    Nv12,
+    Gray16,
+    Rgba64,
+    Yuv420p10,
+    Yuv422p10,
+    Yuv444p10,
+    P010,
+}
+
+impl PixelFormat {
+    pub const fn plane_count(self) -> usize {
+        match self {
+            Self::Rgba | Self::Bgra | Self::Rgb24 | Self::Gray8 | Self::Gray16 | Self::Rgba64 => 1,
+            Self::Nv12 | Self::P010 => 2,
+            Self::Yuv420p
+            | Self::Yuv422p
+            | Self::Yuv444p
+            | Self::Yuv420p10
+            | Self::Yuv422p10
+            | Self::Yuv444p10 => 3,
+        }
+    }
+
+    pub const fn bytes_per_sample(self) -> usize {
+        match self {
+            Self::Gray16
+            | Self::Rgba64
+            | Self::Yuv420p10
+            | Self::Yuv422p10
+            | Self::Yuv444p10
+            | Self::P010 => 2,
+            _ => 1,
+        }
+    }
+
+    /// Returns `(row_bytes, rows)` of `plane` for an image of `size`, without row padding.
+    pub const fn plane_layout(self, plane: usize, size: Size) -> Option<(usize, usize)> {
+        if plane >= self.plane_count() {
+            return None;
+        }
+        let (width, height) = (size.width as usize, size.height as usize);
+        let (half_width, half_height) = (width.div_ceil(2), height.div_ceil(2));
+        let (samples, rows) = match (self, plane) {
+            (Self::Rgba | Self::Bgra | Self::Rgba64, _) => (width * 4, height),
+            (Self::Rgb24, _) => (width * 3, height),
+            (Self::Gray8 | Self::Gray16, _) => (width, height),
+            (_, 0) => (width, height),
+            (Self::Yuv420p | Self::Yuv420p10, _) => (half_width, half_height),
+            (Self::Yuv422p | Self::Yuv422p10, _) => (half_width, height),
+            (Self::Yuv444p | Self::Yuv444p10, _) => (width, height),
+            (Self::Nv12 | Self::P010, _) => (half_width * 2, half_height),
+        };
+        Some((samples * self.bytes_per_sample(), rows))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(PixelFormat::Rgba, 0, Some((16, 3)))]
+    #[case(PixelFormat::Rgb24, 0, Some((12, 3)))]
+    #[case(PixelFormat::Rgba, 1, None)]
+    #[case(PixelFormat::Yuv420p, 0, Some((4, 3)))]
+    #[case(PixelFormat::Yuv420p, 2, Some((2, 2)))]
+    #[case(PixelFormat::Yuv422p, 1, Some((2, 3)))]
+    #[case(PixelFormat::Nv12, 1, Some((4, 2)))]
+    #[case(PixelFormat::P010, 1, Some((8, 2)))]
+    #[case(PixelFormat::Yuv444p10, 2, Some((8, 3)))]
+    #[case(PixelFormat::Gray16, 0, Some((8, 3)))]
+    fn plane_layout_works(
+        #[case] format: PixelFormat,
+        #[case] plane: usize,
+        #[case] expected: Option<(usize, usize)>,
+    ) {
+        assert_eq!(format.plane_layout(plane, Size::new(4, 3)), expected);
+    }
 }
@@ -1,14 +1,26 @@
 pub mod audio;
+pub mod audio_effect;
 mod content;
 pub mod image;
+pub mod loudness;
+pub mod output;
+pub mod probe;
+mod rational;
 mod result;
 mod service;
 mod size;
+mod stream;
+mod time_range;
+mod timecode;
 mod timestamp;
 pub mod video;
 
 pub use content::*;
+pub use rational::*;
 pub use result::*;
 pub use service::*;
 pub use size::*;
+pub use stream::*;
+pub use time_range::*;
+pub use timecode::*;
 pub use timestamp::*;
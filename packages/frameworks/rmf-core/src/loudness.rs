@@ -0,0 +1,507 @@
+use std::{collections::VecDeque, f64::consts::PI};
+
+use anyhow::anyhow;
+
+use crate::{
+    Error, Result, Timestamp,
+    audio::{ChannelLayout, ChannelPosition},
+};
+
+/// Loudness is measured in 100 ms steps; momentary windows span 4 of them and short-term windows 30.
+const STEPS_PER_SECOND: u32 = 10;
+const MOMENTARY_STEPS: usize = 4;
+const SHORT_TERM_STEPS: usize = 30;
+const ABSOLUTE_GATE: f64 = -70.0;
+const INTEGRATED_RELATIVE_GATE: f64 = -10.0;
+const RANGE_RELATIVE_GATE: f64 = -20.0;
+const TRUE_PEAK_TAPS: usize = 12;
+
+/// Loudness at the end of a measurement window, in LUFS.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LoudnessPoint {
+    pub timestamp: Timestamp,
+    pub loudness: f64,
+}
+
+/// Loudness figures of a whole programme, as required by EBU R128 delivery specs.
+/// Silent measurements are negative infinity.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LoudnessReport {
+    /// Gated loudness of the whole programme, in LUFS.
+    pub integrated: f64,
+    /// Loudness range (LRA), in LU.
+    pub range: f64,
+    /// Highest inter-sample peak, in dBTP.
+    pub true_peak: f64,
+    pub max_momentary: f64,
+    pub max_short_term: f64,
+    /// Loudness of 400 ms windows every 100 ms.
+    pub momentary: Vec<LoudnessPoint>,
+    /// Loudness of 3 s windows every 100 ms.
+    pub short_term: Vec<LoudnessPoint>,
+}
+
+/// Measures loudness following ITU-R BS.1770-4 and EBU Tech 3341/3342.
+///
+/// Feed it `f32` samples in `-1.0..=1.0` with [`LoudnessMeter::push`] and read the figures with
+/// [`LoudnessMeter::report`]. Samples not filling a whole 100 ms step are not measured yet.
+pub struct LoudnessMeter {
+    sample_rate: u32,
+    start: Timestamp,
+    channels: Vec<ChannelMeter>,
+    step_samples: usize,
+    step_position: usize,
+    steps_done: i64,
+    recent_steps: VecDeque<f64>,
+    gating_blocks: Vec<f64>,
+    short_term_blocks: Vec<f64>,
+    momentary: Vec<LoudnessPoint>,
+    short_term: Vec<LoudnessPoint>,
+}
+
+impl LoudnessMeter {
+    pub fn try_new(sample_rate: u32, channel_layout: &ChannelLayout) -> Result<Self> {
+        if sample_rate < STEPS_PER_SECOND {
+            return Err(Error::new_audio(anyhow!(
+                "can't measure loudness at {sample_rate} Hz"
+            )));
+        }
+        Ok(Self {
+            sample_rate,
+            start: Timestamp::default(),
+            channels: channel_layout
+                .positions()
+                .iter()
+                .map(|position| ChannelMeter::new(*position, sample_rate))
+                .collect(),
+            step_samples: (sample_rate / STEPS_PER_SECOND) as usize,
+            step_position: 0,
+            steps_done: 0,
+            recent_steps: VecDeque::with_capacity(SHORT_TERM_STEPS),
+            gating_blocks: vec![],
+            short_term_blocks: vec![],
+            momentary: vec![],
+            short_term: vec![],
+        })
+    }
+
+    /// Timestamp of the first pushed sample, which the reported points are relative to.
+    pub fn with_start(mut self, start: Timestamp) -> Self {
+        self.start = start;
+        self
+    }
+
+    #[inline]
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    #[inline]
+    pub fn channels(&self) -> usize {
+        self.channels.len()
+    }
+
+    /// Measures one line of samples per channel; every line should hold the same number of samples.
+    pub fn push(&mut self, lines: &[Vec<f32>]) -> Result<()> {
+        if lines.len() != self.channels.len() {
+            return Err(Error::new_audio(anyhow!(
+                "{} channels don't match a meter of {}",
+                lines.len(),
+                self.channels.len()
+            )));
+        }
+        let samples = lines.iter().map(Vec::len).min().unwrap_or(0);
+        let mut position = 0;
+        while position < samples {
+            let len = (self.step_samples - self.step_position).min(samples - position);
+            for (channel, line) in self.channels.iter_mut().zip(lines) {
+                channel.process(&line[position..position + len]);
+            }
+            position += len;
+            self.step_position += len;
+            if self.step_position == self.step_samples {
+                self.finish_step();
+            }
+        }
+        Ok(())
+    }
+
+    /// Loudness of the last 400 ms, in LUFS.
+    pub fn momentary_loudness(&self) -> f64 {
+        self.momentary
+            .last()
+            .map_or(f64::NEG_INFINITY, |point| point.loudness)
+    }
+
+    /// Loudness of the last 3 s, in LUFS.
+    pub fn short_term_loudness(&self) -> f64 {
+        self.short_term
+            .last()
+            .map_or(f64::NEG_INFINITY, |point| point.loudness)
+    }
+
+    pub fn report(&self) -> LoudnessReport {
+        LoudnessReport {
+            integrated: integrated_loudness(&self.gating_blocks),
+            range: loudness_range(&self.short_term_blocks),
+            true_peak: to_db(
+                self.channels
+                    .iter()
+                    .fold(0.0, |peak, channel| channel.true_peak.peak.max(peak)),
+            ),
+            max_momentary: max_loudness(&self.momentary),
+            max_short_term: max_loudness(&self.short_term),
+            momentary: self.momentary.clone(),
+            short_term: self.short_term.clone(),
+        }
+    }
+
+    /// Forgets everything measured so far.
+    pub fn reset(&mut self) {
+        let positions = self
+            .channels
+            .iter()
+            .map(|channel| channel.position)
+            .collect::<Vec<_>>();
+        self.channels = positions
+            .into_iter()
+            .map(|position| ChannelMeter::new(position, self.sample_rate))
+            .collect();
+        self.step_position = 0;
+        self.steps_done = 0;
+        self.recent_steps.clear();
+        self.gating_blocks.clear();
+        self.short_term_blocks.clear();
+        self.momentary.clear();
+        self.short_term.clear();
+    }
+
+    fn finish_step(&mut self) {
+        let energy = self
+            .channels
+            .iter_mut()
+            .map(|channel| channel.weight * std::mem::take(&mut channel.energy))
+            .sum::<f64>()
+            / self.step_samples as f64;
+        self.step_position = 0;
+        self.steps_done += 1;
+        if self.recent_steps.len() == SHORT_TERM_STEPS {
+            self.recent_steps.pop_front();
+        }
+        self.recent_steps.push_back(energy);
+        let timestamp = self.start
+            + Timestamp::from_microseconds(self.steps_done * 1_000_000 / STEPS_PER_SECOND as i64);
+        if self.recent_steps.len() >= MOMENTARY_STEPS {
+            let block = mean(self.recent_steps.iter().rev().take(MOMENTARY_STEPS));
+            self.gating_blocks.push(block);
+            self.momentary.push(LoudnessPoint {
+                timestamp,
+                loudness: to_loudness(block),
+            });
+        }
+        if self.recent_steps.len() == SHORT_TERM_STEPS {
+            let block = mean(self.recent_steps.iter());
+            self.short_term_blocks.push(block);
+            self.short_term.push(LoudnessPoint {
+                timestamp,
+                loudness: to_loudness(block),
+            });
+        }
+    }
+}
+
+struct ChannelMeter {
+    position: ChannelPosition,
+    weight: f64,
+    shelf: Biquad,
+    high_pass: Biquad,
+    energy: f64,
+    true_peak: TruePeak,
+}
+
+impl ChannelMeter {
+    fn new(position: ChannelPosition, sample_rate: u32) -> Self {
+        let (shelf, high_pass) = k_weighting(sample_rate as f64);
+        Self {
+            position,
+            weight: channel_weight(position),
+            shelf,
+            high_pass,
+            energy: 0.0,
+            true_peak: TruePeak::new(sample_rate),
+        }
+    }
+
+    fn process(&mut self, samples: &[f32]) {
+        for sample in samples {
+            let sample = *sample as f64;
+            self.true_peak.process(sample);
+            if self.weight > 0.0 {
+                let filtered = self.high_pass.process(self.shelf.process(sample));
+                self.energy += filtered * filtered;
+            }
+        }
+    }
+}
+
+/// BS.1770 channel weights: surround channels count 1.5 dB more and LFE channels not at all.
+fn channel_weight(position: ChannelPosition) -> f64 {
+    use ChannelPosition::*;
+    match position {
+        LowFrequency | LowFrequency2 => 0.0,
+        SideLeft | SideRight | BackLeft | BackRight | SurroundDirectLeft | SurroundDirectRight => {
+            1.41
+        }
+        _ => 1.0,
+    }
+}
+
+#[derive(Default)]
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 2],
+    x: [f64; 2],
+    y: [f64; 2],
+}
+
+impl Biquad {
+    #[inline]
+    fn process(&mut self, x: f64) -> f64 {
+        let y = self.b[0] * x + self.b[1] * self.x[0] + self.b[2] * self.x[1]
+            - self.a[0] * self.y[0]
+            - self.a[1] * self.y[1];
+        self.x = [x, self.x[0]];
+        self.y = [y, self.y[0]];
+        y
+    }
+}
+
+/// The K-weighting pre-filter and RLB high-pass of BS.1770, derived for any sample rate.
+fn k_weighting(sample_rate: f64) -> (Biquad, Biquad) {
+    let k = (PI * 1681.974450955533 / sample_rate).tan();
+    let q = 0.7071752369554196;
+    let vh = 10f64.powf(3.999843853973347 / 20.0);
+    let vb = vh.powf(0.4996667741545416);
+    let a0 = 1.0 + k / q + k * k;
+    let shelf = Biquad {
+        b: [
+            (vh + vb * k / q + k * k) / a0,
+            2.0 * (k * k - vh) / a0,
+            (vh - vb * k / q + k * k) / a0,
+        ],
+        a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        ..Default::default()
+    };
+    let k = (PI * 38.13547087602444 / sample_rate).tan();
+    let q = 0.5003270373238773;
+    let a0 = 1.0 + k / q + k * k;
+    let high_pass = Biquad {
+        b: [1.0, -2.0, 1.0],
+        a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        ..Default::default()
+    };
+    (shelf, high_pass)
+}
+
+/// Finds inter-sample peaks by oversampling below 192 kHz with a windowed-sinc interpolator.
+struct TruePeak {
+    phases: Vec<[f64; TRUE_PEAK_TAPS]>,
+    history: [f64; TRUE_PEAK_TAPS],
+    peak: f64,
+}
+
+impl TruePeak {
+    fn new(sample_rate: u32) -> Self {
+        let factor = match sample_rate {
+            ..96_000 => 4,
+            96_000..192_000 => 2,
+            _ => 1,
+        };
+        let len = TRUE_PEAK_TAPS * factor;
+        let center = (len - 1) as f64 / 2.0;
+        let phases = (0..factor)
+            .map(|phase| {
+                let mut taps = [0.0; TRUE_PEAK_TAPS];
+                for (k, tap) in taps.iter_mut().enumerate() {
+                    let n = (k * factor + phase) as f64;
+                    let x = (n - center) / factor as f64;
+                    let sinc = if x == 0.0 {
+                        1.0
+                    } else {
+                        (PI * x).sin() / (PI * x)
+                    };
+                    let window = 0.5 - 0.5 * (2.0 * PI * (n + 0.5) / len as f64).cos();
+                    *tap = sinc * window;
+                }
+                let sum = taps.iter().sum::<f64>();
+                taps.iter_mut().for_each(|tap| *tap /= sum);
+                taps
+            })
+            .collect();
+        Self {
+            phases,
+            history: [0.0; TRUE_PEAK_TAPS],
+            peak: 0.0,
+        }
+    }
+
+    #[inline]
+    fn process(&mut self, sample: f64) {
+        self.history.copy_within(..TRUE_PEAK_TAPS - 1, 1);
+        self.history[0] = sample;
+        self.peak = self.peak.max(sample.abs());
+        for taps in &self.phases {
+            let value = taps
+                .iter()
+                .zip(&self.history)
+                .map(|(tap, x)| tap * x)
+                .sum::<f64>();
+            self.peak = self.peak.max(value.abs());
+        }
+    }
+}
+
+fn integrated_loudness(blocks: &[f64]) -> f64 {
+    let Some(threshold) = relative_threshold(blocks, INTEGRATED_RELATIVE_GATE) else {
+        return f64::NEG_INFINITY;
+    };
+    to_loudness(mean(
+        blocks
+            .iter()
+            .filter(|block| to_loudness(**block) > threshold),
+    ))
+}
+
+/// Spread between the 10th and 95th percentile of gated short-term loudness (EBU Tech 3342).
+fn loudness_range(blocks: &[f64]) -> f64 {
+    let Some(threshold) = relative_threshold(blocks, RANGE_RELATIVE_GATE) else {
+        return 0.0;
+    };
+    let mut loudness = blocks
+        .iter()
+        .map(|block| to_loudness(*block))
+        .filter(|loudness| *loudness > threshold)
+        .collect::<Vec<_>>();
+    if loudness.is_empty() {
+        return 0.0;
+    }
+    loudness.sort_by(f64::total_cmp);
+    let percentile = |p: f64| loudness[((loudness.len() - 1) as f64 * p).round() as usize];
+    percentile(0.95) - percentile(0.10)
+}
+
+/// The gate `offset` LU below the mean of the blocks above the absolute gate, if there are any.
+fn relative_threshold(blocks: &[f64], offset: f64) -> Option<f64> {
+    let gated = blocks
+        .iter()
+        .filter(|block| to_loudness(**block) > ABSOLUTE_GATE)
+        .collect::<Vec<_>>();
+    (!gated.is_empty())
+        .then(|| to_loudness(mean(gated.into_iter())) + offset)
+        .map(|threshold| threshold.max(ABSOLUTE_GATE))
+}
+
+#[inline]
+fn mean<'a>(values: impl Iterator<Item = &'a f64>) -> f64 {
+    let (sum, len) = values.fold((0.0, 0), |(sum, len), value| (sum + value, len + 1));
+    if len == 0 { 0.0 } else { sum / len as f64 }
+}
+
+#[inline]
+fn to_loudness(energy: f64) -> f64 {
+    -0.691 + 10.0 * energy.log10()
+}
+
+#[inline]
+fn to_db(amplitude: f64) -> f64 {
+    20.0 * amplitude.log10()
+}
+
+fn max_loudness(points: &[LoudnessPoint]) -> f64 {
+    points
+        .iter()
+        .fold(f64::NEG_INFINITY, |max, point| point.loudness.max(max))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    fn sine(sample_rate: u32, seconds: f64, db: f64) -> Vec<f32> {
+        let amplitude = 10f64.powf(db / 20.0);
+        (0..(sample_rate as f64 * seconds) as usize)
+            .map(|i| (amplitude * (2.0 * PI * 1000.0 * i as f64 / sample_rate as f64).sin()) as f32)
+            .collect()
+    }
+
+    fn assert_near(actual: f64, expected: f64, tolerance: f64) {
+        assert!(
+            (actual - expected).abs() <= tolerance,
+            "{actual} is not within {tolerance} of {expected}"
+        );
+    }
+
+    #[rstest]
+    #[case(48000, ChannelLayout::stereo(), -23.0)]
+    #[case(44100, ChannelLayout::stereo(), -23.0)]
+    #[case(48000, ChannelLayout::mono(), -26.0)]
+    fn sine_loudness_works(
+        #[case] sample_rate: u32,
+        #[case] channel_layout: ChannelLayout,
+        #[case] expected: f64,
+    ) {
+        let mut meter = LoudnessMeter::try_new(sample_rate, &channel_layout).unwrap();
+        let line = sine(sample_rate, 4.0, -23.0);
+        meter.push(&vec![line; channel_layout.channels()]).unwrap();
+        let report = meter.report();
+        assert_near(report.integrated, expected, 0.1);
+        assert_near(report.max_momentary, expected, 0.1);
+        assert_near(report.max_short_term, expected, 0.1);
+        assert_near(report.true_peak, -23.0, 0.2);
+        assert_eq!(report.momentary.len(), 37);
+        assert_eq!(report.short_term.len(), 11);
+        assert_eq!(
+            report.momentary[0].timestamp,
+            Timestamp::from_milliseconds(400)
+        );
+    }
+
+    #[rstest]
+    fn loudness_range_works() {
+        // EBU Tech 3342 case 1: 20 s at -20 dBFS followed by 20 s at -30 dBFS measures 10 LU.
+        let sample_rate = 16000;
+        let mut meter = LoudnessMeter::try_new(sample_rate, &ChannelLayout::stereo()).unwrap();
+        for db in [-20.0, -30.0] {
+            let line = sine(sample_rate, 20.0, db);
+            meter.push(&[line.clone(), line]).unwrap();
+        }
+        assert_near(meter.report().range, 10.0, 1.0);
+    }
+
+    #[rstest]
+    fn silence_is_negative_infinity() {
+        let mut meter = LoudnessMeter::try_new(48000, &ChannelLayout::stereo()).unwrap();
+        meter.push(&[vec![0.0; 48000], vec![0.0; 48000]]).unwrap();
+        let report = meter.report();
+        assert_eq!(report.integrated, f64::NEG_INFINITY);
+        assert_eq!(report.true_peak, f64::NEG_INFINITY);
+        assert_eq!(report.range, 0.0);
+    }
+
+    #[rstest]
+    fn lfe_is_not_measured() {
+        let mut meter = LoudnessMeter::try_new(48000, &ChannelLayout::surround_5_1()).unwrap();
+        let mut lines = vec![vec![0.0; 48000]; 6];
+        lines[3] = sine(48000, 1.0, -10.0);
+        meter.push(&lines).unwrap();
+        assert_eq!(meter.report().integrated, f64::NEG_INFINITY);
+    }
+
+    #[rstest]
+    fn push_rejects_other_channel_counts() {
+        let mut meter = LoudnessMeter::try_new(48000, &ChannelLayout::stereo()).unwrap();
+        assert!(meter.push(&[vec![0.0; 10]]).is_err());
+    }
+}
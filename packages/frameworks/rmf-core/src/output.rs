@@ -0,0 +1,87 @@
+use derive_new::new;
+
+use crate::{Size, audio::AudioFormat, image::PixelFormat};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ContainerFormat {
+    Mp4,
+    Mkv,
+    WebM,
+    Mov,
+    /// A muxer short name as known by the backend.
+    Named(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VideoCodec {
+    H264,
+    Hevc,
+    Vp8,
+    Vp9,
+    Av1,
+    ProRes,
+    /// An encoder name as known by the backend, e.g. `libx264`.
+    Named(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AudioCodec {
+    Aac,
+    Opus,
+    Vorbis,
+    Mp3,
+    Flac,
+    PcmS16Le,
+    /// An encoder name as known by the backend, e.g. `libopus`.
+    Named(String),
+}
+
+#[derive(new, Clone, Debug)]
+pub struct VideoEncoderOptions {
+    pub codec: VideoCodec,
+    pub size: Size,
+    pub fps: f64,
+    #[new(default)]
+    pub bitrate: Option<u64>,
+    /// Falls back to the encoder's preferred format.
+    #[new(default)]
+    pub pixel_format: Option<PixelFormat>,
+}
+
+#[derive(new, Clone, Debug)]
+pub struct AudioEncoderOptions {
+    pub codec: AudioCodec,
+    pub sample_rate: u32,
+    pub channels: u32,
+    #[new(default)]
+    pub bitrate: Option<u64>,
+    /// Falls back to the encoder's preferred format. Packed or planar layout follows the encoder.
+    #[new(default)]
+    pub sample_format: Option<AudioFormat>,
+}
+
+#[derive(new, Clone, Debug, Default)]
+pub struct OutputOptions {
+    /// Guessed from the destination when omitted.
+    #[new(default)]
+    pub format: Option<ContainerFormat>,
+    #[new(default)]
+    pub video: Option<VideoEncoderOptions>,
+    #[new(default)]
+    pub audio: Option<AudioEncoderOptions>,
+}
+
+impl OutputOptions {
+    pub fn with_format(mut self, format: ContainerFormat) -> Self {
+        self.format = Some(format);
+        self
+    }
+    pub fn with_video(mut self, video: VideoEncoderOptions) -> Self {
+        self.video = Some(video);
+        self
+    }
+    pub fn with_audio(mut self, audio: AudioEncoderOptions) -> Self {
+        self.audio = Some(audio);
+        self
+    }
+}
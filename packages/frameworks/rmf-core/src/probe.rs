@@ -0,0 +1,177 @@
+use std::collections::BTreeMap;
+
+use anyhow::anyhow;
+
+use crate::{Error, Rational, Result, Size, StreamInfo, Timecode, Timestamp};
+
+const TIMECODE_TAG: &str = "timecode";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MediaType {
+    Video,
+    Audio,
+    Subtitle,
+    Data,
+    Attachment,
+    Unknown,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct VideoStreamReport {
+    pub size: Size,
+    /// Backend name of the pixel format, e.g. `yuv420p`.
+    pub pixel_format: Option<String>,
+    pub fps: Option<f64>,
+    /// Exact average frame rate, e.g. `30000/1001`.
+    pub frame_rate: Option<Rational>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AudioStreamReport {
+    pub sample_rate: u32,
+    pub channels: u32,
+    /// Backend description of the channel layout, e.g. `stereo` or `5.1`.
+    pub channel_layout: Option<String>,
+    /// Backend name of the sample format, e.g. `fltp`.
+    pub sample_format: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct StreamReport {
+    pub info: StreamInfo,
+    pub media_type: MediaType,
+    pub profile: Option<String>,
+    pub bitrate: Option<u64>,
+    /// As stored in the container; absent when it has to be counted by decoding.
+    pub frame_count: Option<u64>,
+    pub start_time: Option<Timestamp>,
+    pub duration: Option<Timestamp>,
+    pub tags: BTreeMap<String, String>,
+    pub video: Option<VideoStreamReport>,
+    pub audio: Option<AudioStreamReport>,
+}
+
+/// Container and stream metadata read without decoding any content.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProbeReport {
+    /// Backend short name of the container, e.g. `mov,mp4,m4a,3gp,3g2,mj2`.
+    pub format: String,
+    pub duration: Option<Timestamp>,
+    pub start_time: Option<Timestamp>,
+    pub bitrate: Option<u64>,
+    pub tags: BTreeMap<String, String>,
+    pub streams: Vec<StreamReport>,
+}
+
+impl StreamReport {
+    /// The `timecode` tag, as carried by MOV `tmcd` and video streams.
+    #[inline]
+    pub fn timecode_tag(&self) -> Option<&str> {
+        self.tags.get(TIMECODE_TAG).map(String::as_str)
+    }
+}
+
+impl ProbeReport {
+    pub fn streams_of(&self, media_type: MediaType) -> impl Iterator<Item = &StreamReport> {
+        self.streams
+            .iter()
+            .filter(move |stream| stream.media_type == media_type)
+    }
+
+    /// The `timecode` tag of the container, as MXF stores it, or else of the first stream carrying one.
+    pub fn timecode_tag(&self) -> Option<&str> {
+        self.tags
+            .get(TIMECODE_TAG)
+            .map(String::as_str)
+            .or_else(|| self.streams.iter().find_map(StreamReport::timecode_tag))
+    }
+
+    /// The start timecode from [`ProbeReport::timecode_tag`], counted at the first video stream's frame rate.
+    pub fn start_timecode(&self) -> Result<Option<Timecode>> {
+        let Some(tag) = self.timecode_tag() else {
+            return Ok(None);
+        };
+        let rate = self
+            .streams_of(MediaType::Video)
+            .filter_map(|stream| stream.video.as_ref())
+            .find_map(|video| {
+                video
+                    .frame_rate
+                    .or_else(|| Rational::from_f64(video.fps?, 1001))
+            })
+            .ok_or_else(|| {
+                Error::new_timecode(anyhow!("no video frame rate to count timecode `{tag}` at"))
+            })?;
+        Timecode::parse(tag, rate).map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    fn stream(media_type: MediaType, tags: &[(&str, &str)]) -> StreamReport {
+        StreamReport {
+            info: StreamInfo::new(0, "test".to_string()),
+            media_type,
+            profile: None,
+            bitrate: None,
+            frame_count: None,
+            start_time: None,
+            duration: None,
+            tags: tags
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            video: (media_type == MediaType::Video).then(|| VideoStreamReport {
+                size: Size::new(1920, 1080),
+                pixel_format: None,
+                fps: Some(30000.0 / 1001.0),
+                frame_rate: Some(Rational::new(30000, 1001)),
+            }),
+            audio: None,
+        }
+    }
+
+    fn report(tags: &[(&str, &str)], streams: Vec<StreamReport>) -> ProbeReport {
+        ProbeReport {
+            format: "test".to_string(),
+            duration: None,
+            start_time: None,
+            bitrate: None,
+            tags: tags
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            streams,
+        }
+    }
+
+    #[rstest]
+    #[case(report(&[("timecode", "01:00:00;00")], vec![stream(MediaType::Video, &[])]), Some("01:00:00;00"))]
+    #[case(
+        report(&[], vec![stream(MediaType::Video, &[]), stream(MediaType::Data, &[("timecode", "10:00:00;00")])]),
+        Some("10:00:00;00")
+    )]
+    #[case(report(&[], vec![stream(MediaType::Video, &[])]), None)]
+    fn start_timecode_works(#[case] report: ProbeReport, #[case] expected: Option<&str>) {
+        assert_eq!(
+            report
+                .start_timecode()
+                .unwrap()
+                .map(|timecode| timecode.to_string()),
+            expected.map(str::to_string)
+        );
+    }
+
+    #[rstest]
+    fn start_timecode_needs_a_video_rate() {
+        assert!(
+            report(&[("timecode", "01:00:00:00")], vec![])
+                .start_timecode()
+                .is_err()
+        );
+    }
+}
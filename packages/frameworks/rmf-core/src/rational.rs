@@ -0,0 +1,646 @@
+use std::{
+    cmp::Ordering,
+    fmt::Display,
+    hash::{Hash, Hasher},
+    ops::{Add, Div, Mul, Neg, Sub},
+};
+
+use crate::Timestamp;
+
+/// An exact fraction, always kept reduced with a positive denominator.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Rational {
+    numerator: i64,
+    denominator: i64,
+}
+
+impl Rational {
+    pub const ZERO: Self = Self::new(0, 1);
+    pub const ONE: Self = Self::new(1, 1);
+    /// The time base of [`Timestamp`].
+    pub const MICROSECOND: Self = Self::new(1, 1_000_000);
+
+    /// Panics if `denominator` is zero.
+    pub const fn new(numerator: i64, denominator: i64) -> Self {
+        match Self::checked_new(numerator, denominator) {
+            Some(rational) => rational,
+            None => panic!("rational with a zero denominator"),
+        }
+    }
+
+    pub const fn checked_new(numerator: i64, denominator: i64) -> Option<Self> {
+        if denominator == 0 {
+            return None;
+        }
+        Self::reduce(numerator as i128, denominator as i128)
+    }
+
+    #[inline]
+    pub const fn numerator(&self) -> i64 {
+        self.numerator
+    }
+
+    #[inline]
+    pub const fn denominator(&self) -> i64 {
+        self.denominator
+    }
+
+    /// Panics if `self` is zero.
+    #[inline]
+    pub const fn recip(self) -> Self {
+        Self::new(self.denominator, self.numerator)
+    }
+
+    #[inline]
+    pub const fn as_f64(&self) -> f64 {
+        self.numerator as f64 / self.denominator as f64
+    }
+
+    /// The closest fraction to `value` with a denominator up to `max_denominator`,
+    /// e.g. `29.97` becomes `30000/1001`.
+    pub fn from_f64(value: f64, max_denominator: i64) -> Option<Self> {
+        if !value.is_finite() || max_denominator < 1 {
+            return None;
+        }
+        // Continued fraction convergents until the denominator would grow too large.
+        let (mut h0, mut h1) = (0i128, 1i128);
+        let (mut k0, mut k1) = (1i128, 0i128);
+        let mut x = value;
+        loop {
+            let a = x.floor();
+            if a.abs() > i64::MAX as f64 {
+                return None;
+            }
+            let a = a as i128;
+            let (h2, k2) = (a * h1 + h0, a * k1 + k0);
+            if k2 > max_denominator as i128 {
+                break;
+            }
+            (h0, h1, k0, k1) = (h1, h2, k1, k2);
+            let fraction = x - a as f64;
+            if fraction.abs() < 1e-12 {
+                break;
+            }
+            x = 1.0 / fraction;
+        }
+        Self::reduce(h1, k1)
+    }
+
+    /// Rounds half away from zero.
+    #[inline]
+    pub const fn round(&self) -> i64 {
+        div_round(self.numerator as i128, self.denominator as i128) as i64
+    }
+
+    #[inline]
+    pub const fn floor(&self) -> i64 {
+        self.numerator.div_euclid(self.denominator)
+    }
+
+    #[inline]
+    pub const fn ceil(&self) -> i64 {
+        -(-self.numerator).div_euclid(self.denominator)
+    }
+
+    const fn reduce(numerator: i128, denominator: i128) -> Option<Self> {
+        let divisor = gcd(numerator, denominator);
+        let sign = if denominator < 0 { -1 } else { 1 };
+        let numerator = sign * numerator / divisor;
+        let denominator = sign * denominator / divisor;
+        if numerator < i64::MIN as i128
+            || numerator > i64::MAX as i128
+            || denominator > i64::MAX as i128
+        {
+            None
+        } else {
+            Some(Self {
+                numerator: numerator as i64,
+                denominator: denominator as i64,
+            })
+        }
+    }
+
+    #[inline]
+    fn from_i128(numerator: i128, denominator: i128) -> Self {
+        Self::reduce(numerator, denominator).expect("rational overflowed")
+    }
+}
+
+impl Default for Rational {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+impl From<i64> for Rational {
+    fn from(value: i64) -> Self {
+        Self::new(value, 1)
+    }
+}
+
+impl Display for Rational {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.numerator, self.denominator)
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.numerator as i128 * other.denominator as i128)
+            .cmp(&(other.numerator as i128 * self.denominator as i128))
+    }
+}
+
+impl Add for Rational {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        Self::from_i128(
+            self.numerator as i128 * rhs.denominator as i128
+                + rhs.numerator as i128 * self.denominator as i128,
+            self.denominator as i128 * rhs.denominator as i128,
+        )
+    }
+}
+
+impl Sub for Rational {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        self + -rhs
+    }
+}
+
+impl Mul for Rational {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        Self::from_i128(
+            self.numerator as i128 * rhs.numerator as i128,
+            self.denominator as i128 * rhs.denominator as i128,
+        )
+    }
+}
+
+impl Mul<i64> for Rational {
+    type Output = Self;
+    fn mul(self, rhs: i64) -> Self {
+        Self::from_i128(
+            self.numerator as i128 * rhs as i128,
+            self.denominator as i128,
+        )
+    }
+}
+
+/// Panics if `rhs` is zero.
+impl Div for Rational {
+    type Output = Self;
+    fn div(self, rhs: Self) -> Self {
+        assert!(rhs.numerator != 0, "rational divided by zero");
+        Self::from_i128(
+            self.numerator as i128 * rhs.denominator as i128,
+            self.denominator as i128 * rhs.numerator as i128,
+        )
+    }
+}
+
+impl Neg for Rational {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self {
+            numerator: -self.numerator,
+            denominator: self.denominator,
+        }
+    }
+}
+
+/// A tick count in a rational time base, such as a stream pts, kept without rounding.
+///
+/// Converting a [`Timestamp`] in and out is lossless, and so is the round trip the other way
+/// for any time base whose ticks are at least 2 µs apart, like `1001/30000` or `1/44100`.
+#[derive(Clone, Copy, Debug)]
+pub struct RationalTimestamp {
+    pts: i64,
+    time_base: Rational,
+}
+
+impl RationalTimestamp {
+    #[inline]
+    pub const fn new(pts: i64, time_base: Rational) -> Self {
+        Self { pts, time_base }
+    }
+
+    /// The tick of `time_base` closest to `timestamp`.
+    #[inline]
+    pub fn from_timestamp(timestamp: Timestamp, time_base: Rational) -> Self {
+        Self::from(timestamp).rescale(time_base)
+    }
+
+    #[inline]
+    pub const fn pts(&self) -> i64 {
+        self.pts
+    }
+
+    #[inline]
+    pub const fn time_base(&self) -> Rational {
+        self.time_base
+    }
+
+    /// The exact position in seconds.
+    /// Panics when it doesn't fit in a [`Rational`]; comparisons don't go through it and can't.
+    #[inline]
+    pub fn seconds(&self) -> Rational {
+        self.time_base * self.pts
+    }
+
+    /// Rounded to the closest microsecond.
+    #[inline]
+    pub fn to_timestamp(&self) -> Timestamp {
+        Timestamp::from_microseconds(self.rescale(Rational::MICROSECOND).pts)
+    }
+
+    /// The closest tick of `time_base`, saturating at the range of `i64`.
+    pub fn rescale(&self, time_base: Rational) -> Self {
+        let (numerator, denominator) = self.ratio_to(time_base);
+        let pts = match (self.pts as i128).checked_mul(numerator) {
+            Some(scaled) => div_round(scaled, denominator),
+            // Only reachable with time bases far outside any real stream's.
+            None => (self.pts as f64 * numerator as f64 / denominator as f64) as i128,
+        };
+        Self::new(
+            pts.clamp(i64::MIN as i128, i64::MAX as i128) as i64,
+            time_base,
+        )
+    }
+
+    /// The same position in `time_base`, if it falls exactly on one of its ticks within the range of `i64`.
+    pub fn checked_rescale(&self, time_base: Rational) -> Option<Self> {
+        let (numerator, denominator) = self.ratio_to(time_base);
+        if self.pts as i128 % denominator != 0 {
+            return None;
+        }
+        let pts = (self.pts as i128 / denominator).checked_mul(numerator)?;
+        i64::try_from(pts).ok().map(|pts| Self::new(pts, time_base))
+    }
+
+    /// The exact sum in a time base both operands fall on, or `None` when it doesn't fit.
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        let time_base = self.common_time_base(&rhs)?;
+        let lhs = self.checked_rescale(time_base)?;
+        let rhs = rhs.checked_rescale(time_base)?;
+        lhs.pts
+            .checked_add(rhs.pts)
+            .map(|pts| Self::new(pts, time_base))
+    }
+
+    /// The exact difference in a time base both operands fall on, or `None` when it doesn't fit.
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        self.checked_add(Self::new(rhs.pts.checked_neg()?, rhs.time_base))
+    }
+
+    /// Ticks of `time_base` of this time base, as a reduced fraction with a positive denominator.
+    fn ratio_to(&self, time_base: Rational) -> (i128, i128) {
+        let numerator = self.time_base.numerator as i128 * time_base.denominator as i128;
+        let denominator = self.time_base.denominator as i128 * time_base.numerator as i128;
+        let divisor = gcd(numerator, denominator) * if denominator < 0 { -1 } else { 1 };
+        (numerator / divisor, denominator / divisor)
+    }
+
+    /// The exact position in seconds as a reduced fraction, which unlike [`Rational`] can't overflow.
+    fn reduced_seconds(&self) -> (i128, i128) {
+        let numerator = self.pts as i128 * self.time_base.numerator as i128;
+        let denominator = self.time_base.denominator as i128;
+        let divisor = gcd(numerator, denominator);
+        (numerator / divisor, denominator / divisor)
+    }
+
+    /// The coarsest time base both operands fall exactly on, if it fits in a [`Rational`].
+    fn common_time_base(&self, other: &Self) -> Option<Rational> {
+        let a = self.time_base;
+        let b = other.time_base;
+        let denominator = a.denominator as i128 / gcd(a.denominator as i128, b.denominator as i128)
+            * b.denominator as i128;
+        Rational::reduce(gcd(a.numerator as i128, b.numerator as i128), denominator)
+    }
+}
+
+impl From<Timestamp> for RationalTimestamp {
+    fn from(value: Timestamp) -> Self {
+        Self::new(value.as_microseconds(), Rational::MICROSECOND)
+    }
+}
+
+impl From<RationalTimestamp> for Timestamp {
+    fn from(value: RationalTimestamp) -> Self {
+        value.to_timestamp()
+    }
+}
+
+impl PartialEq for RationalTimestamp {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for RationalTimestamp {}
+
+impl Hash for RationalTimestamp {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.reduced_seconds().hash(state);
+    }
+}
+
+impl PartialOrd for RationalTimestamp {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for RationalTimestamp {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (a, b) = (self.reduced_seconds(), other.reduced_seconds());
+        cmp_fractions(a.0, a.1, b.0, b.1)
+    }
+}
+
+impl Display for RationalTimestamp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}@{}", self.pts, self.time_base)
+    }
+}
+
+/// Exact, in a time base both operands fall on, whenever [`RationalTimestamp::checked_add`] is.
+/// Otherwise both are rounded to that time base, or to the left one's if there is none, and the sum saturates.
+impl Add for RationalTimestamp {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        self.checked_add(rhs).unwrap_or_else(|| {
+            let time_base = self.common_time_base(&rhs).unwrap_or(self.time_base);
+            let lhs = self.rescale(time_base);
+            let rhs = rhs.rescale(time_base);
+            Self::new(lhs.pts.saturating_add(rhs.pts), time_base)
+        })
+    }
+}
+
+/// Adds `rhs` negated, so it is exact and falls back exactly like [`Add`].
+impl Sub for RationalTimestamp {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        Add::add(self, Self::new(rhs.pts.saturating_neg(), rhs.time_base))
+    }
+}
+
+const fn gcd(a: i128, b: i128) -> i128 {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    if a == 0 { 1 } else { a }
+}
+
+/// Compares `a / b` with `c / d` for positive `b` and `d` without multiplying, so nothing can overflow.
+/// Compares the integer parts, then the reciprocals of the remainders, like Euclid's algorithm.
+fn cmp_fractions(mut a: i128, mut b: i128, mut c: i128, mut d: i128) -> Ordering {
+    loop {
+        let (p, q) = (a.div_euclid(b), c.div_euclid(d));
+        if p != q {
+            return p.cmp(&q);
+        }
+        let (r, s) = (a.rem_euclid(b), c.rem_euclid(d));
+        match (r, s) {
+            (0, 0) => return Ordering::Equal,
+            (0, _) => return Ordering::Less,
+            (_, 0) => return Ordering::Greater,
+            // r/b < s/d exactly when d/s < b/r.
+            _ => (a, b, c, d) = (d, s, b, r),
+        }
+    }
+}
+
+/// Integer division rounding half away from zero.
+const fn div_round(numerator: i128, denominator: i128) -> i128 {
+    let (numerator, denominator) = if denominator < 0 {
+        (-numerator, -denominator)
+    } else {
+        (numerator, denominator)
+    };
+    if numerator >= 0 {
+        (numerator + denominator / 2) / denominator
+    } else {
+        (numerator - denominator / 2) / denominator
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(Rational::new(2, 4), (1, 2))]
+    #[case(Rational::new(3, -6), (-1, 2))]
+    #[case(Rational::new(0, -5), (0, 1))]
+    #[case(Rational::new(1, 2) + Rational::new(1, 3), (5, 6))]
+    #[case(Rational::new(1, 2) - Rational::new(1, 3), (1, 6))]
+    #[case(Rational::new(2, 3) * Rational::new(3, 4), (1, 2))]
+    #[case(Rational::new(2, 3) / Rational::new(4, 3), (1, 2))]
+    fn rational_works(#[case] rational: Rational, #[case] expected: (i64, i64)) {
+        assert_eq!((rational.numerator(), rational.denominator()), expected);
+    }
+
+    #[rstest]
+    #[case(Rational::new(7, 2), 4, 3, 4)]
+    #[case(Rational::new(-7, 2), -4, -4, -3)]
+    #[case(Rational::new(5, 3), 2, 1, 2)]
+    fn rational_rounding_works(
+        #[case] rational: Rational,
+        #[case] round: i64,
+        #[case] floor: i64,
+        #[case] ceil: i64,
+    ) {
+        assert_eq!(
+            (rational.round(), rational.floor(), rational.ceil()),
+            (round, floor, ceil)
+        );
+    }
+
+    #[rstest]
+    #[case(29.97, 1001, Some(Rational::new(2997, 100)))]
+    #[case(30000.0 / 1001.0, 1001, Some(Rational::new(30000, 1001)))]
+    #[case(0.5, 10, Some(Rational::new(1, 2)))]
+    #[case(f64::NAN, 10, None)]
+    fn rational_from_f64_works(
+        #[case] value: f64,
+        #[case] max_denominator: i64,
+        #[case] expected: Option<Rational>,
+    ) {
+        assert_eq!(Rational::from_f64(value, max_denominator), expected);
+    }
+
+    #[rstest]
+    fn rational_ordering_works() {
+        assert!(Rational::new(1, 3) < Rational::new(1, 2));
+        assert!(Rational::new(-1, 2) < Rational::ZERO);
+        assert_eq!(Rational::new(2, 4), Rational::new(1, 2));
+    }
+
+    #[rstest]
+    #[case(Rational::new(1001, 30000))]
+    #[case(Rational::new(1, 44100))]
+    #[case(Rational::new(1, 48000))]
+    #[case(Rational::new(1, 90000))]
+    fn rational_timestamp_round_trips(#[case] time_base: Rational) {
+        for pts in (0..100_000).step_by(997) {
+            let timestamp = RationalTimestamp::new(pts, time_base);
+            assert_eq!(
+                RationalTimestamp::from_timestamp(timestamp.to_timestamp(), time_base).pts(),
+                pts
+            );
+        }
+    }
+
+    #[rstest]
+    #[case(Timestamp::from_microseconds(1_234_567))]
+    #[case(Timestamp::from_microseconds(-42))]
+    fn timestamp_round_trips(#[case] timestamp: Timestamp) {
+        assert_eq!(
+            Timestamp::from(RationalTimestamp::from(timestamp)),
+            timestamp
+        );
+    }
+
+    #[rstest]
+    fn rational_timestamp_arithmetic_is_exact() {
+        let frame = Rational::new(1001, 30000);
+        let sample = Rational::new(1, 48000);
+        let sum = RationalTimestamp::new(3, frame) + RationalTimestamp::new(1, sample);
+        assert_eq!(
+            sum.seconds(),
+            Rational::new(3003, 30000) + Rational::new(1, 48000)
+        );
+        assert_eq!(
+            sum - RationalTimestamp::new(1, sample),
+            RationalTimestamp::new(3, frame)
+        );
+        assert_eq!(
+            RationalTimestamp::new(3, frame).checked_rescale(Rational::new(1, 30000)),
+            Some(RationalTimestamp::new(3003, Rational::new(1, 30000)))
+        );
+        assert_eq!(
+            RationalTimestamp::new(1, frame).checked_rescale(Rational::new(1, 1000)),
+            None
+        );
+    }
+
+    #[rstest]
+    #[case(
+        RationalTimestamp::new(2, Rational::new(1, 2)),
+        RationalTimestamp::new(1, Rational::ONE),
+        Ordering::Equal
+    )]
+    #[case(
+        RationalTimestamp::new(1001, Rational::new(1, 30000)),
+        RationalTimestamp::new(1, Rational::new(1, 30)),
+        Ordering::Greater
+    )]
+    #[case(RationalTimestamp::new(-1, Rational::new(1, 3)), RationalTimestamp::new(-1, Rational::new(1, 2)), Ordering::Greater)]
+    #[case(
+        RationalTimestamp::new(i64::MAX, Rational::new(i64::MAX, 3)),
+        RationalTimestamp::new(i64::MAX, Rational::new(i64::MAX, 2)),
+        Ordering::Less
+    )]
+    #[case(RationalTimestamp::new(i64::MAX, Rational::new(i64::MAX, 1)), RationalTimestamp::new(i64::MAX - 1, Rational::new(i64::MAX, 1)), Ordering::Greater)]
+    #[case(
+        RationalTimestamp::new(i64::MIN, Rational::new(i64::MAX, 1)),
+        RationalTimestamp::new(i64::MIN, Rational::new(i64::MAX, 1)),
+        Ordering::Equal
+    )]
+    fn rational_timestamp_ordering_never_overflows(
+        #[case] a: RationalTimestamp,
+        #[case] b: RationalTimestamp,
+        #[case] expected: Ordering,
+    ) {
+        use std::hash::{BuildHasher, RandomState};
+        assert_eq!(a.cmp(&b), expected);
+        assert_eq!(b.cmp(&a), expected.reverse());
+        if expected == Ordering::Equal {
+            let state = RandomState::new();
+            assert_eq!(state.hash_one(a), state.hash_one(b));
+        }
+    }
+
+    #[rstest]
+    #[case(RationalTimestamp::new(i64::MAX, Rational::ONE), i64::MAX)]
+    #[case(RationalTimestamp::new(i64::MIN, Rational::ONE), i64::MIN)]
+    #[case(RationalTimestamp::new(i64::MAX, Rational::new(i64::MAX, 1)), i64::MAX)]
+    #[case(RationalTimestamp::new(3, Rational::new(1, 2)), 1_500_000)]
+    fn rescale_saturates(#[case] timestamp: RationalTimestamp, #[case] expected: i64) {
+        assert_eq!(timestamp.rescale(Rational::MICROSECOND).pts(), expected);
+    }
+
+    #[rstest]
+    #[case(
+        RationalTimestamp::new(i64::MAX, Rational::ONE),
+        RationalTimestamp::new(1, Rational::ONE),
+        RationalTimestamp::new(i64::MAX, Rational::ONE)
+    )]
+    #[case(
+        RationalTimestamp::new(i64::MIN, Rational::ONE),
+        RationalTimestamp::new(-1, Rational::ONE),
+        RationalTimestamp::new(i64::MIN, Rational::ONE)
+    )]
+    #[case(
+        RationalTimestamp::new(i64::MAX, Rational::ONE),
+        RationalTimestamp::new(1, Rational::new(1, 2)),
+        RationalTimestamp::new(i64::MAX, Rational::new(1, 2))
+    )]
+    #[case(
+        RationalTimestamp::new(3, Rational::new(1, i64::MAX)),
+        RationalTimestamp::new(1, Rational::new(1, i64::MAX - 1)),
+        RationalTimestamp::new(4, Rational::new(1, i64::MAX))
+    )]
+    fn add_saturates_in_one_time_base(
+        #[case] lhs: RationalTimestamp,
+        #[case] rhs: RationalTimestamp,
+        #[case] expected: RationalTimestamp,
+    ) {
+        assert_eq!(lhs.checked_add(rhs), None);
+        let sum = lhs + rhs;
+        assert_eq!(
+            (sum.pts(), sum.time_base()),
+            (expected.pts(), expected.time_base())
+        );
+    }
+
+    #[rstest]
+    fn checked_add_works() {
+        let frame = Rational::new(1001, 30000);
+        let sample = Rational::new(1, 48000);
+        assert_eq!(
+            RationalTimestamp::new(3, frame).checked_add(RationalTimestamp::new(1, sample)),
+            Some(RationalTimestamp::new(3, frame) + RationalTimestamp::new(1, sample))
+        );
+        assert_eq!(
+            RationalTimestamp::new(3, frame).checked_sub(RationalTimestamp::new(3, frame)),
+            Some(RationalTimestamp::new(0, frame))
+        );
+        assert_eq!(
+            RationalTimestamp::new(0, Rational::ONE)
+                .checked_sub(RationalTimestamp::new(i64::MIN, Rational::ONE)),
+            None
+        );
+    }
+
+    #[rstest]
+    fn checked_rescale_rejects_overflow() {
+        assert_eq!(
+            RationalTimestamp::new(i64::MAX, Rational::ONE).checked_rescale(Rational::MICROSECOND),
+            None
+        );
+    }
+}
@@ -8,6 +8,10 @@ pub enum Error {
     Audio(anyhow::Error),
     #[error("input error {0}")]
     Input(anyhow::Error),
+    #[error("output error {0}")]
+    Output(anyhow::Error),
+    #[error("timecode error {0}")]
+    Timecode(anyhow::Error),
     #[error("end of file")]
     Eof,
 }
@@ -1,10 +1,55 @@
-use std::path::PathBuf;
+use std::{
+    io::{Read, Seek},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
 
 use derive_new::new;
 
+use crate::{Content, Result, audio::Audio, image::Image};
+
+pub trait InputReader: Read + Seek + Send {}
+
+impl<T: Read + Seek + Send> InputReader for T {}
+
 #[derive(new, Clone)]
 pub enum InputSource {
     Path(PathBuf),
+    Bytes(Arc<[u8]>),
+    /// Shared by every cursor opened from this source; each one seeks to its own position before reading.
+    Reader(Arc<Mutex<dyn InputReader>>),
+}
+
+impl InputSource {
+    pub fn from_reader(reader: impl InputReader + 'static) -> Self {
+        Self::Reader(Arc::new(Mutex::new(reader)))
+    }
+}
+
+/// How a content cursor positions itself on `seek`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SeekMode {
+    /// Lands on the nearest keyframe at or before the timestamp. Fast, but earlier content may be read first.
+    #[default]
+    Keyframe,
+    /// Decodes forward from the keyframe so the next read is the content covering the timestamp.
+    Exact,
+}
+
+#[derive(new, Clone)]
+pub enum OutputDestination {
+    Path(PathBuf),
 }
 
-pub trait OutputService {}
+pub trait OutputService {
+    type Image: Image;
+    type Audio: Audio;
+    /// Whether the output was configured with a video stream; `write_video` fails otherwise.
+    fn has_video(&self) -> bool;
+    /// Whether the output was configured with an audio stream; `write_audio` fails otherwise.
+    fn has_audio(&self) -> bool;
+    fn write_video(&mut self, content: &Content<Self::Image>) -> Result<()>;
+    fn write_audio(&mut self, content: &Content<Self::Audio>) -> Result<()>;
+    /// Drains the encoders and writes the container trailer. Nothing can be written afterwards.
+    fn finish(&mut self) -> Result<()>;
+}
@@ -1,7 +1,7 @@
 use derive_new::new;
 
 #[repr(C)]
-#[derive(new, Default, Debug)]
+#[derive(new, Default, Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Size {
     pub width: u32,
     pub height: u32,
@@ -0,0 +1,20 @@
+use derive_new::new;
+
+use crate::Rational;
+
+/// A stream of a given media type found in an input.
+#[derive(new, Clone, Debug, PartialEq, Eq)]
+pub struct StreamInfo {
+    /// Container-level index, as accepted when selecting a stream.
+    pub index: usize,
+    pub codec: String,
+    #[new(default)]
+    pub language: Option<String>,
+    #[new(default)]
+    pub title: Option<String>,
+    #[new(default)]
+    pub is_default: bool,
+    /// Time base of the stream's own timestamps.
+    #[new(value = "Rational::MICROSECOND")]
+    pub time_base: Rational,
+}
@@ -0,0 +1,270 @@
+use std::fmt::Display;
+
+use crate::Timestamp;
+
+/// A half-open span of time: `start` is inside, `end` is not.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct TimeRange {
+    start: Timestamp,
+    end: Timestamp,
+}
+
+impl TimeRange {
+    /// An `end` before `start` makes an empty range at `start`.
+    #[inline]
+    pub fn new(start: Timestamp, end: Timestamp) -> Self {
+        Self {
+            start,
+            end: end.max(start),
+        }
+    }
+
+    #[inline]
+    pub fn from_duration(start: Timestamp, duration: Timestamp) -> Self {
+        Self::new(start, start + duration)
+    }
+
+    #[inline]
+    pub fn start(&self) -> Timestamp {
+        self.start
+    }
+
+    #[inline]
+    pub fn end(&self) -> Timestamp {
+        self.end
+    }
+
+    #[inline]
+    pub fn duration(&self) -> Timestamp {
+        self.end - self.start
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.end <= self.start
+    }
+
+    #[inline]
+    pub fn contains(&self, timestamp: Timestamp) -> bool {
+        self.start <= timestamp && timestamp < self.end
+    }
+
+    /// Whether `other` lies completely inside; an empty range is inside when its position is.
+    #[inline]
+    pub fn contains_range(&self, other: &Self) -> bool {
+        self.start <= other.start && other.end <= self.end
+    }
+
+    /// Whether the ranges share any time; ranges that only touch, or are empty, don't.
+    #[inline]
+    pub fn overlaps(&self, other: &Self) -> bool {
+        self.start < other.end && other.start < self.end && !self.is_empty() && !other.is_empty()
+    }
+
+    /// The time both ranges share, if any.
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        self.overlaps(other)
+            .then(|| Self::new(self.start.max(other.start), self.end.min(other.end)))
+    }
+
+    /// Both ranges as one, if they overlap or touch so that no gap would be covered.
+    pub fn union(&self, other: &Self) -> Option<Self> {
+        (self.start <= other.end && other.start <= self.end).then(|| self.hull(other))
+    }
+
+    /// The smallest range covering both, including any gap between them.
+    pub fn hull(&self, other: &Self) -> Self {
+        Self::new(self.start.min(other.start), self.end.max(other.end))
+    }
+
+    /// Splits into `start..timestamp` and `timestamp..end` when `timestamp` falls strictly inside.
+    pub fn split_at(&self, timestamp: Timestamp) -> Option<(Self, Self)> {
+        (self.start < timestamp && timestamp < self.end).then(|| {
+            (
+                Self::new(self.start, timestamp),
+                Self::new(timestamp, self.end),
+            )
+        })
+    }
+
+    /// The closest timestamp in `start..=end`.
+    pub fn clamp(&self, timestamp: Timestamp) -> Timestamp {
+        timestamp.clamp(self.start, self.end)
+    }
+
+    /// The part of `other` inside this range, collapsed to the nearest edge when they don't overlap.
+    pub fn clamp_range(&self, other: &Self) -> Self {
+        Self::new(self.clamp(other.start), self.clamp(other.end))
+    }
+
+    /// The same length, moved by `delta`.
+    #[inline]
+    pub fn shifted(&self, delta: Timestamp) -> Self {
+        Self::new(self.start + delta, self.end + delta)
+    }
+
+    /// Consecutive ranges of `step` from `start`; the last one is cut at `end`.
+    /// Yields nothing unless `step` is positive.
+    pub fn steps(&self, step: Timestamp) -> impl Iterator<Item = Self> + use<> {
+        let range = *self;
+        let step = step.as_microseconds();
+        (0..)
+            .map(move |i| range.start + Timestamp::from_microseconds(i * step))
+            .take_while(move |start| step > 0 && *start < range.end)
+            .map(move |start| {
+                Self::new(
+                    start,
+                    range.clamp(start + Timestamp::from_microseconds(step)),
+                )
+            })
+    }
+
+    /// One range per frame at `fps`, counted from `start` and rounded to microseconds without drifting.
+    /// The last one is cut at `end`. Yields nothing unless `fps` is positive.
+    pub fn frames(&self, fps: f64) -> impl Iterator<Item = Self> + use<> {
+        let range = *self;
+        let frame_start = move |i: i64| {
+            range.start
+                + Timestamp::from_microseconds((i as f64 * 1_000_000.0 / fps).round() as i64)
+        };
+        (0..)
+            .map(move |i| (frame_start(i), frame_start(i + 1)))
+            .take_while(move |(start, _)| fps > 0.0 && *start < range.end)
+            .map(move |(start, end)| Self::new(start, range.clamp(end)))
+    }
+}
+
+impl Display for TimeRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}..{}", self.start, self.end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    fn range(start: i64, end: i64) -> TimeRange {
+        TimeRange::new(
+            Timestamp::from_milliseconds(start),
+            Timestamp::from_milliseconds(end),
+        )
+    }
+
+    #[rstest]
+    #[case(range(0, 10), 0, true)]
+    #[case(range(0, 10), 9, true)]
+    #[case(range(0, 10), 10, false)]
+    #[case(range(0, 10), -1, false)]
+    #[case(range(5, 5), 5, false)]
+    fn contains_works(#[case] time_range: TimeRange, #[case] ms: i64, #[case] expected: bool) {
+        assert_eq!(
+            time_range.contains(Timestamp::from_milliseconds(ms)),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case(range(0, 10), range(5, 15), Some(range(5, 10)))]
+    #[case(range(0, 10), range(2, 4), Some(range(2, 4)))]
+    #[case(range(0, 10), range(10, 20), None)]
+    #[case(range(0, 10), range(5, 5), None)]
+    fn intersection_works(
+        #[case] a: TimeRange,
+        #[case] b: TimeRange,
+        #[case] expected: Option<TimeRange>,
+    ) {
+        assert_eq!(a.intersection(&b), expected);
+        assert_eq!(b.intersection(&a), expected);
+    }
+
+    #[rstest]
+    #[case(range(0, 10), range(5, 15), Some(range(0, 15)))]
+    #[case(range(0, 10), range(10, 20), Some(range(0, 20)))]
+    #[case(range(0, 10), range(11, 20), None)]
+    fn union_works(
+        #[case] a: TimeRange,
+        #[case] b: TimeRange,
+        #[case] expected: Option<TimeRange>,
+    ) {
+        assert_eq!(a.union(&b), expected);
+        assert_eq!(b.union(&a), expected);
+    }
+
+    #[rstest]
+    fn hull_covers_gaps() {
+        assert_eq!(range(0, 10).hull(&range(15, 20)), range(0, 20));
+    }
+
+    #[rstest]
+    #[case(range(0, 10), range(0, 10), true)]
+    #[case(range(0, 10), range(10, 10), true)]
+    #[case(range(0, 10), range(5, 11), false)]
+    fn contains_range_works(#[case] a: TimeRange, #[case] b: TimeRange, #[case] expected: bool) {
+        assert_eq!(a.contains_range(&b), expected);
+    }
+
+    #[rstest]
+    #[case(range(0, 10), 5, Some((range(0, 5), range(5, 10))))]
+    #[case(range(0, 10), 0, None)]
+    #[case(range(0, 10), 10, None)]
+    fn split_at_works(
+        #[case] time_range: TimeRange,
+        #[case] ms: i64,
+        #[case] expected: Option<(TimeRange, TimeRange)>,
+    ) {
+        assert_eq!(
+            time_range.split_at(Timestamp::from_milliseconds(ms)),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case(-5, 0)]
+    #[case(5, 5)]
+    #[case(15, 10)]
+    fn clamp_works(#[case] ms: i64, #[case] expected: i64) {
+        assert_eq!(
+            range(0, 10).clamp(Timestamp::from_milliseconds(ms)),
+            Timestamp::from_milliseconds(expected)
+        );
+    }
+
+    #[rstest]
+    fn clamp_range_works() {
+        assert_eq!(range(0, 10).clamp_range(&range(-5, 5)), range(0, 5));
+        assert_eq!(range(0, 10).clamp_range(&range(20, 30)), range(10, 10));
+    }
+
+    #[rstest]
+    fn new_keeps_end_after_start() {
+        assert!(range(10, 5).is_empty());
+        assert_eq!(range(10, 5).start(), Timestamp::from_milliseconds(10));
+    }
+
+    #[rstest]
+    #[case(4, vec![range(0, 4), range(4, 8), range(8, 10)])]
+    #[case(0, vec![])]
+    fn steps_works(#[case] step: i64, #[case] expected: Vec<TimeRange>) {
+        assert_eq!(
+            range(0, 10)
+                .steps(Timestamp::from_milliseconds(step))
+                .collect::<Vec<_>>(),
+            expected
+        );
+    }
+
+    #[rstest]
+    fn frames_do_not_drift() {
+        let frames = TimeRange::new(Timestamp::default(), Timestamp::from_seconds(10))
+            .frames(30000.0 / 1001.0)
+            .collect::<Vec<_>>();
+        assert_eq!(frames.len(), 300);
+        assert_eq!(frames[0].end(), Timestamp::from_microseconds(33367));
+        assert_eq!(frames[299].start(), Timestamp::from_microseconds(9_976_633));
+        assert!(frames.windows(2).all(|w| w[0].end() == w[1].start()));
+        assert_eq!(frames[299].end(), Timestamp::from_seconds(10));
+    }
+}
@@ -0,0 +1,269 @@
+use std::fmt::Display;
+
+use anyhow::anyhow;
+
+use crate::{Error, Rational, RationalTimestamp, Result, Timestamp};
+
+const NTSC_RATES: [Rational; 2] = [Rational::new(30000, 1001), Rational::new(60000, 1001)];
+
+/// A SMPTE `HH:MM:SS:FF` timecode at a given frame rate.
+///
+/// Drop-frame timecodes (`HH:MM:SS;FF`, only at 29.97 and 59.94 fps) skip the first frame numbers of
+/// every minute except each tenth, so the clock stays in step with real time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Timecode {
+    frame: i64,
+    rate: Rational,
+    drop_frame: bool,
+}
+
+impl Timecode {
+    /// The timecode of the `frame`-th frame counted from `00:00:00:00`.
+    pub fn try_from_frame(frame: i64, rate: Rational, drop_frame: bool) -> Result<Self> {
+        if rate <= Rational::ZERO {
+            return Err(Error::new_timecode(anyhow!(
+                "frame rate {rate} isn't positive"
+            )));
+        }
+        if drop_frame && !NTSC_RATES.contains(&rate) {
+            return Err(Error::new_timecode(anyhow!(
+                "drop-frame timecode needs 29.97 or 59.94 fps, not {rate}"
+            )));
+        }
+        if frame < 0 {
+            return Err(Error::new_timecode(anyhow!(
+                "frame {frame} is before 00:00:00:00"
+            )));
+        }
+        Ok(Self {
+            frame,
+            rate,
+            drop_frame,
+        })
+    }
+
+    pub fn try_new(
+        hours: u32,
+        minutes: u32,
+        seconds: u32,
+        frames: u32,
+        rate: Rational,
+        drop_frame: bool,
+    ) -> Result<Self> {
+        let timecode = Self::try_from_frame(0, rate, drop_frame)?;
+        let nominal = timecode.nominal_fps() as u32;
+        if minutes >= 60 || seconds >= 60 || frames >= nominal {
+            return Err(Error::new_timecode(anyhow!(
+                "{hours:02}:{minutes:02}:{seconds:02}:{frames:02} is out of range at {nominal} fps"
+            )));
+        }
+        let dropped = timecode.dropped_per_minute();
+        if dropped > 0 && seconds == 0 && !minutes.is_multiple_of(10) && frames < dropped as u32 {
+            return Err(Error::new_timecode(anyhow!(
+                "{hours:02}:{minutes:02}:{seconds:02};{frames:02} is skipped by drop-frame counting"
+            )));
+        }
+        let total_minutes = 60 * hours as i64 + minutes as i64;
+        let frame = nominal as i64 * (60 * total_minutes + seconds as i64) + frames as i64
+            - dropped * (total_minutes - total_minutes / 10);
+        Self::try_from_frame(frame, rate, drop_frame)
+    }
+
+    /// The timecode of the frame closest to `timestamp`.
+    pub fn from_timestamp(timestamp: Timestamp, rate: Rational, drop_frame: bool) -> Result<Self> {
+        let timecode = Self::try_from_frame(0, rate, drop_frame)?;
+        Self::try_from_frame(
+            RationalTimestamp::from_timestamp(timestamp, rate.recip()).pts(),
+            timecode.rate,
+            drop_frame,
+        )
+    }
+
+    /// Parses `HH:MM:SS:FF`; a `;` or `.` before the frames marks drop-frame timecode.
+    pub fn parse(value: &str, rate: Rational) -> Result<Self> {
+        let invalid = || Error::new_timecode(anyhow!("`{value}` isn't a HH:MM:SS:FF timecode"));
+        let value = value.trim();
+        let split = value.rfind([':', ';', '.']).ok_or_else(invalid)?;
+        let drop_frame = value[split..].starts_with([';', '.']);
+        let mut parts = value[..split]
+            .split(':')
+            .chain([&value[split + 1..]])
+            .map(|part| {
+                part.parse::<u32>()
+                    .ok()
+                    .filter(|_| !part.is_empty() && part.len() <= 3)
+            });
+        let mut next = || parts.next().flatten().ok_or_else(invalid);
+        let (hours, minutes, seconds, frames) = (next()?, next()?, next()?, next()?);
+        if parts.next().is_some() {
+            return Err(invalid());
+        }
+        Self::try_new(hours, minutes, seconds, frames, rate, drop_frame)
+    }
+
+    /// Frames counted from `00:00:00:00`.
+    #[inline]
+    pub fn frame(&self) -> i64 {
+        self.frame
+    }
+
+    #[inline]
+    pub fn rate(&self) -> Rational {
+        self.rate
+    }
+
+    #[inline]
+    pub fn is_drop_frame(&self) -> bool {
+        self.drop_frame
+    }
+
+    /// Frames per timecode second, e.g. 30 at 29.97 fps.
+    #[inline]
+    pub fn nominal_fps(&self) -> i64 {
+        self.rate.round().max(1)
+    }
+
+    /// Presentation time of the frame.
+    #[inline]
+    pub fn to_timestamp(&self) -> Timestamp {
+        RationalTimestamp::new(self.frame, self.rate.recip()).to_timestamp()
+    }
+
+    /// The timecode `frames` frames later, or earlier when negative.
+    pub fn offset_frames(&self, frames: i64) -> Result<Self> {
+        Self::try_from_frame(self.frame + frames, self.rate, self.drop_frame)
+    }
+
+    /// Hours, minutes, seconds and frames as displayed.
+    pub fn components(&self) -> (u32, u32, u32, u32) {
+        let nominal = self.nominal_fps();
+        let dropped = self.dropped_per_minute();
+        let mut frame = self.frame;
+        if dropped > 0 {
+            let per_ten_minutes = nominal * 600 - dropped * 9;
+            let per_minute = nominal * 60 - dropped;
+            let tens = frame / per_ten_minutes;
+            let rest = frame % per_ten_minutes;
+            frame += dropped * 9 * tens;
+            if rest > dropped {
+                frame += dropped * ((rest - dropped) / per_minute);
+            }
+        }
+        (
+            (frame / (nominal * 3600)) as u32,
+            (frame / (nominal * 60) % 60) as u32,
+            (frame / nominal % 60) as u32,
+            (frame % nominal) as u32,
+        )
+    }
+
+    #[inline]
+    fn dropped_per_minute(&self) -> i64 {
+        if self.drop_frame {
+            self.nominal_fps() / 15
+        } else {
+            0
+        }
+    }
+}
+
+impl Display for Timecode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (hours, minutes, seconds, frames) = self.components();
+        let separator = if self.drop_frame { ';' } else { ':' };
+        write!(
+            f,
+            "{hours:02}:{minutes:02}:{seconds:02}{separator}{frames:02}"
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    const NTSC: Rational = Rational::new(30000, 1001);
+
+    #[rstest]
+    #[case("00:00:00:00", Rational::new(25, 1), 0)]
+    #[case("01:00:00:00", Rational::new(25, 1), 90000)]
+    #[case("00:00:59;29", NTSC, 1799)]
+    #[case("00:01:00;02", NTSC, 1800)]
+    #[case("00:10:00;00", NTSC, 17982)]
+    #[case("01:00:00;00", NTSC, 107892)]
+    #[case("00:01:00:00", NTSC, 1800)]
+    #[case("00:01:00.04", Rational::new(60000, 1001), 3600)]
+    fn parse_works(#[case] value: &str, #[case] rate: Rational, #[case] expected: i64) {
+        let timecode = Timecode::parse(value, rate).unwrap();
+        assert_eq!(timecode.frame(), expected);
+        assert_eq!(
+            timecode.to_string(),
+            value.replace('.', ";"),
+            "formatting {value}"
+        );
+    }
+
+    #[rstest]
+    #[case("00:01:00;00", NTSC)]
+    #[case("00:01:00;01", NTSC)]
+    #[case("00:00:00:25", Rational::new(25, 1))]
+    #[case("00:60:00:00", Rational::new(25, 1))]
+    #[case("00:00:00;00", Rational::new(25, 1))]
+    #[case("00:00:00", Rational::new(25, 1))]
+    #[case("aa:00:00:00", Rational::new(25, 1))]
+    fn parse_rejects_invalid(#[case] value: &str, #[case] rate: Rational) {
+        assert!(Timecode::parse(value, rate).is_err());
+    }
+
+    #[rstest]
+    fn drop_frame_round_trips() {
+        for frame in (0..200_000).step_by(7) {
+            let timecode = Timecode::try_from_frame(frame, NTSC, true).unwrap();
+            assert_eq!(
+                Timecode::parse(&timecode.to_string(), NTSC)
+                    .unwrap()
+                    .frame(),
+                frame
+            );
+        }
+    }
+
+    #[rstest]
+    #[case(Timestamp::from_seconds(3600), NTSC, true, "01:00:00;00")]
+    #[case(Timestamp::from_seconds(3600), NTSC, false, "00:59:56:12")]
+    #[case(Timestamp::from_microseconds(3_603_600_000), NTSC, true, "01:00:03;18")]
+    #[case(
+        Timestamp::from_milliseconds(1040),
+        Rational::new(25, 1),
+        false,
+        "00:00:01:01"
+    )]
+    fn from_timestamp_works(
+        #[case] timestamp: Timestamp,
+        #[case] rate: Rational,
+        #[case] drop_frame: bool,
+        #[case] expected: &str,
+    ) {
+        assert_eq!(
+            Timecode::from_timestamp(timestamp, rate, drop_frame)
+                .unwrap()
+                .to_string(),
+            expected
+        );
+    }
+
+    #[rstest]
+    fn to_timestamp_works() {
+        let timecode = Timecode::parse("00:00:01;00", NTSC).unwrap();
+        assert_eq!(
+            timecode.to_timestamp(),
+            Timestamp::from_microseconds(1_001_000)
+        );
+        assert_eq!(
+            Timecode::from_timestamp(timecode.to_timestamp(), NTSC, true).unwrap(),
+            timecode
+        );
+    }
+}
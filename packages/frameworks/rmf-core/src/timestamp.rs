@@ -4,8 +4,10 @@ use std::{
     time::Duration,
 };
 
+use crate::Rational;
+
 #[repr(C)]
-#[derive(Clone, Copy, PartialEq, Debug, Default, PartialOrd)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default, PartialOrd, Ord)]
 pub struct Timestamp {
     raw_microseconds: i64,
 }
@@ -15,6 +17,10 @@ const SECONDS_RATIO: i64 = 1000 * MILLISECONDS_RATIO;
 const MILLISECONDS_RATIO: i64 = 1000;
 
 impl Timestamp {
+    pub const ZERO: Self = Self::from_microseconds(0);
+    pub const MIN: Self = Self::from_microseconds(i64::MIN);
+    pub const MAX: Self = Self::from_microseconds(i64::MAX);
+
     pub const fn as_seconds_float64(&self) -> f64 {
         self.raw_microseconds as f64 / SECONDS_RATIO as f64
     }
@@ -57,6 +63,78 @@ impl Timestamp {
             raw_microseconds: (seconds * SECONDS_RATIO as f32) as i64,
         }
     }
+
+    /// Saturates at [`Timestamp::MAX`], since [`Timestamp::MIN`] has no positive counterpart.
+    pub const fn abs(&self) -> Self {
+        Self::from_microseconds(self.raw_microseconds.saturating_abs())
+    }
+    pub const fn checked_abs(&self) -> Option<Self> {
+        match self.raw_microseconds.checked_abs() {
+            Some(value) => Some(Self::from_microseconds(value)),
+            None => None,
+        }
+    }
+
+    pub const fn checked_add(self, rhs: Self) -> Option<Self> {
+        match self.raw_microseconds.checked_add(rhs.raw_microseconds) {
+            Some(value) => Some(Self::from_microseconds(value)),
+            None => None,
+        }
+    }
+    pub const fn checked_sub(self, rhs: Self) -> Option<Self> {
+        match self.raw_microseconds.checked_sub(rhs.raw_microseconds) {
+            Some(value) => Some(Self::from_microseconds(value)),
+            None => None,
+        }
+    }
+    pub const fn checked_mul(self, rhs: i64) -> Option<Self> {
+        match self.raw_microseconds.checked_mul(rhs) {
+            Some(value) => Some(Self::from_microseconds(value)),
+            None => None,
+        }
+    }
+    /// `None` when `rhs` is zero or the result overflows.
+    pub const fn checked_div(self, rhs: i64) -> Option<Self> {
+        match self.raw_microseconds.checked_div(rhs) {
+            Some(value) => Some(Self::from_microseconds(value)),
+            None => None,
+        }
+    }
+
+    pub const fn saturating_add(self, rhs: Self) -> Self {
+        Self::from_microseconds(self.raw_microseconds.saturating_add(rhs.raw_microseconds))
+    }
+    pub const fn saturating_sub(self, rhs: Self) -> Self {
+        Self::from_microseconds(self.raw_microseconds.saturating_sub(rhs.raw_microseconds))
+    }
+    pub const fn saturating_mul(self, rhs: i64) -> Self {
+        Self::from_microseconds(self.raw_microseconds.saturating_mul(rhs))
+    }
+
+    /// The start of the frame at `frame_rate` that contains this timestamp.
+    pub fn floor_to_frame(&self, frame_rate: Rational) -> Self {
+        self.snap_to_frame(frame_rate, i128::div_euclid)
+    }
+    /// The start of the first frame at `frame_rate` at or after this timestamp.
+    pub fn ceil_to_frame(&self, frame_rate: Rational) -> Self {
+        self.snap_to_frame(frame_rate, |a, b| -(-a).div_euclid(b))
+    }
+    /// The nearest frame start at `frame_rate`, rounding halves up.
+    pub fn round_to_frame(&self, frame_rate: Rational) -> Self {
+        self.snap_to_frame(frame_rate, |a, b| (2 * a + b).div_euclid(2 * b))
+    }
+
+    /// Frame starts are rounded to the nearest microsecond, so consecutive frames never drift.
+    /// Returns `self` unless `frame_rate` is positive.
+    fn snap_to_frame(&self, frame_rate: Rational, frame: impl Fn(i128, i128) -> i128) -> Self {
+        if frame_rate <= Rational::ZERO {
+            return *self;
+        }
+        let rate = frame_rate.numerator() as i128;
+        let scale = SECONDS_RATIO as i128 * frame_rate.denominator() as i128;
+        let index = frame(self.raw_microseconds as i128 * rate, scale);
+        Self::from_microseconds((2 * index * scale + rate).div_euclid(2 * rate) as i64)
+    }
 }
 
 impl Display for Timestamp {
@@ -91,29 +169,51 @@ impl SubAssign for Timestamp {
     }
 }
 
+/// The ratio between two spans, such as playback speed.
 impl Div for Timestamp {
-    type Output = Timestamp;
+    type Output = f64;
     fn div(self, rhs: Self) -> Self::Output {
-        Self::from_microseconds(self.raw_microseconds.div(rhs.raw_microseconds))
+        self.raw_microseconds as f64 / rhs.raw_microseconds as f64
+    }
+}
+
+impl Div<i64> for Timestamp {
+    type Output = Timestamp;
+    fn div(self, rhs: i64) -> Self::Output {
+        Self::from_microseconds(self.raw_microseconds.div(rhs))
+    }
+}
+
+impl DivAssign<i64> for Timestamp {
+    fn div_assign(&mut self, rhs: i64) {
+        self.raw_microseconds.div_assign(rhs)
     }
 }
 
-impl DivAssign for Timestamp {
-    fn div_assign(&mut self, rhs: Self) {
-        self.raw_microseconds.div_assign(rhs.raw_microseconds)
+impl Mul<i64> for Timestamp {
+    type Output = Timestamp;
+    fn mul(self, rhs: i64) -> Self::Output {
+        Self::from_microseconds(self.raw_microseconds.mul(rhs))
     }
 }
 
-impl Mul for Timestamp {
+impl MulAssign<i64> for Timestamp {
+    fn mul_assign(&mut self, rhs: i64) {
+        self.raw_microseconds.mul_assign(rhs);
+    }
+}
+
+/// Rounds to the nearest microsecond, saturating at [`Timestamp::MIN`] and [`Timestamp::MAX`].
+impl Mul<f64> for Timestamp {
     type Output = Timestamp;
-    fn mul(self, rhs: Self) -> Self::Output {
-        Self::from_microseconds(self.raw_microseconds.mul(rhs.raw_microseconds))
+    fn mul(self, rhs: f64) -> Self::Output {
+        Self::from_microseconds((self.raw_microseconds as f64 * rhs).round() as i64)
     }
 }
 
-impl MulAssign for Timestamp {
-    fn mul_assign(&mut self, rhs: Self) {
-        self.raw_microseconds.mul_assign(rhs.raw_microseconds);
+impl MulAssign<f64> for Timestamp {
+    fn mul_assign(&mut self, rhs: f64) {
+        *self = *self * rhs;
     }
 }
 
@@ -230,46 +330,128 @@ mod tests {
     }
 
     #[rstest]
-    #[case(Timestamp { raw_microseconds: -12000000 },Timestamp { raw_microseconds: 12000000 },Timestamp { raw_microseconds: -1 })]
-    #[case(Timestamp { raw_microseconds: -12000000 },Timestamp { raw_microseconds: -12000000 },Timestamp { raw_microseconds: 1 })]
-    #[case(Timestamp { raw_microseconds: 12000000 },Timestamp { raw_microseconds: 12000000 },Timestamp { raw_microseconds: 1 })]
-    fn div_works(#[case] base: Timestamp, #[case] rhs: Timestamp, #[case] expected: Timestamp) {
+    #[case(Timestamp { raw_microseconds: -12000000 },Timestamp { raw_microseconds: 12000000 },-1.0)]
+    #[case(Timestamp { raw_microseconds: 6000000 },Timestamp { raw_microseconds: 12000000 },0.5)]
+    #[case(Timestamp { raw_microseconds: 12000000 },Timestamp { raw_microseconds: 12000000 },1.0)]
+    fn div_works(#[case] base: Timestamp, #[case] rhs: Timestamp, #[case] expected: f64) {
         assert_eq!(base / rhs, expected)
     }
+
     #[rstest]
-    #[case(Timestamp { raw_microseconds: -12000000 },Timestamp { raw_microseconds: 12000000 },Timestamp { raw_microseconds: -1 })]
-    #[case(Timestamp { raw_microseconds: -12000000 },Timestamp { raw_microseconds: -12000000 },Timestamp { raw_microseconds: 1 })]
-    #[case(Timestamp { raw_microseconds: 12000000 },Timestamp { raw_microseconds: 12000000 },Timestamp { raw_microseconds: 1 })]
-    fn div_assign_works(
+    #[case(Timestamp { raw_microseconds: -12000000 },4,Timestamp { raw_microseconds: -3000000 })]
+    #[case(Timestamp { raw_microseconds: 7 },2,Timestamp { raw_microseconds: 3 })]
+    fn div_scalar_works(
         #[case] mut base: Timestamp,
-        #[case] rhs: Timestamp,
+        #[case] rhs: i64,
         #[case] expected: Timestamp,
     ) {
+        assert_eq!(base / rhs, expected);
         base /= rhs;
         assert_eq!(base, expected)
     }
 
     #[rstest]
-    #[case(Timestamp { raw_microseconds: -12 },Timestamp { raw_microseconds: 100 },Timestamp { raw_microseconds: -1200 })]
-    #[case(Timestamp { raw_microseconds: -12 },Timestamp { raw_microseconds: 0 },Timestamp { raw_microseconds: 0 })]
-    #[case(Timestamp { raw_microseconds: 12000000 },Timestamp { raw_microseconds: 1},Timestamp { raw_microseconds: 12000000 })]
-    fn mul_works(#[case] base: Timestamp, #[case] rhs: Timestamp, #[case] expected: Timestamp) {
-        assert_eq!(base * rhs, expected)
+    #[case(Timestamp { raw_microseconds: -12 },100,Timestamp { raw_microseconds: -1200 })]
+    #[case(Timestamp { raw_microseconds: -12 },0,Timestamp { raw_microseconds: 0 })]
+    #[case(Timestamp { raw_microseconds: 12000000 },1,Timestamp { raw_microseconds: 12000000 })]
+    fn mul_works(#[case] mut base: Timestamp, #[case] rhs: i64, #[case] expected: Timestamp) {
+        assert_eq!(base * rhs, expected);
+        base *= rhs;
+        assert_eq!(base, expected)
     }
 
     #[rstest]
-    #[case(Timestamp { raw_microseconds: -12 },Timestamp { raw_microseconds: 100 },Timestamp { raw_microseconds: -1200 })]
-    #[case(Timestamp { raw_microseconds: -12 },Timestamp { raw_microseconds: 0 },Timestamp { raw_microseconds: 0 })]
-    #[case(Timestamp { raw_microseconds: 12000000 },Timestamp { raw_microseconds: 1},Timestamp { raw_microseconds: 12000000 })]
-    fn mul_assign_works(
-        #[case] mut base: Timestamp,
-        #[case] rhs: Timestamp,
-        #[case] expected: Timestamp,
-    ) {
+    #[case(Timestamp { raw_microseconds: 1000 },1.5,Timestamp { raw_microseconds: 1500 })]
+    #[case(Timestamp { raw_microseconds: 3 },0.5,Timestamp { raw_microseconds: 2 })]
+    #[case(Timestamp { raw_microseconds: -3 },0.5,Timestamp { raw_microseconds: -2 })]
+    #[case(Timestamp::MAX, 2.0, Timestamp::MAX)]
+    fn mul_float_works(#[case] mut base: Timestamp, #[case] rhs: f64, #[case] expected: Timestamp) {
+        assert_eq!(base * rhs, expected);
         base *= rhs;
         assert_eq!(base, expected)
     }
 
+    #[rstest]
+    fn checked_works() {
+        let one = Timestamp::from_microseconds(1);
+        assert_eq!(one.checked_add(one), Some(Timestamp::from_microseconds(2)));
+        assert_eq!(Timestamp::MAX.checked_add(one), None);
+        assert_eq!(Timestamp::MIN.checked_sub(one), None);
+        assert_eq!(Timestamp::MAX.checked_mul(2), None);
+        assert_eq!(one.checked_div(0), None);
+        assert_eq!(
+            Timestamp::from_seconds(1).checked_div(4),
+            Some(Timestamp::from_milliseconds(250))
+        );
+    }
+
+    #[rstest]
+    fn saturating_works() {
+        let one = Timestamp::from_microseconds(1);
+        assert_eq!(Timestamp::MAX.saturating_add(one), Timestamp::MAX);
+        assert_eq!(Timestamp::MIN.saturating_sub(one), Timestamp::MIN);
+        assert_eq!(Timestamp::MIN.saturating_mul(2), Timestamp::MIN);
+        assert_eq!(one.saturating_mul(3), Timestamp::from_microseconds(3));
+    }
+
+    #[rstest]
+    fn ordering_works() {
+        let a = Timestamp::from_milliseconds(-5);
+        let b = Timestamp::from_milliseconds(5);
+        assert_eq!(a.max(b), b);
+        assert_eq!(a.min(b), a);
+        let mut sorted = vec![b, Timestamp::ZERO, a];
+        sorted.sort();
+        assert_eq!(sorted, vec![a, Timestamp::ZERO, b]);
+    }
+
+    #[rstest]
+    #[case(Rational::new(25, 1), 50000, (40000, 40000, 80000))]
+    #[case(Rational::new(25, 1), 60000, (40000, 80000, 80000))]
+    #[case(Rational::new(25, 1), 80000, (80000, 80000, 80000))]
+    #[case(Rational::new(25, 1), -10000, (-40000, 0, 0))]
+    #[case(Rational::new(30000, 1001), 1_000_000, (967633, 1001000, 1001000))]
+    #[case(Rational::new(30000, 1001), 3_600_000_000, (3_599_996_400, 3_599_996_400, 3_600_029_767))]
+    fn snap_to_frame_works(
+        #[case] frame_rate: Rational,
+        #[case] microseconds: i64,
+        #[case] expected: (i64, i64, i64),
+    ) {
+        let timestamp = Timestamp::from_microseconds(microseconds);
+        assert_eq!(
+            (
+                timestamp.floor_to_frame(frame_rate).as_microseconds(),
+                timestamp.round_to_frame(frame_rate).as_microseconds(),
+                timestamp.ceil_to_frame(frame_rate).as_microseconds(),
+            ),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case(Timestamp::from_milliseconds(-5), Timestamp::from_milliseconds(5), Some(Timestamp::from_milliseconds(5)))]
+    #[case(
+        Timestamp::from_milliseconds(5),
+        Timestamp::from_milliseconds(5),
+        Some(Timestamp::from_milliseconds(5))
+    )]
+    #[case(Timestamp::MAX, Timestamp::MAX, Some(Timestamp::MAX))]
+    #[case(Timestamp::MIN, Timestamp::MAX, None)]
+    fn abs_works(
+        #[case] timestamp: Timestamp,
+        #[case] expected: Timestamp,
+        #[case] expected_checked: Option<Timestamp>,
+    ) {
+        assert_eq!(timestamp.abs(), expected);
+        assert_eq!(timestamp.checked_abs(), expected_checked);
+    }
+
+    #[rstest]
+    fn snap_to_frame_ignores_non_positive_rates() {
+        let timestamp = Timestamp::from_microseconds(12345);
+        assert_eq!(timestamp.round_to_frame(Rational::ZERO), timestamp);
+    }
+
     #[rstest]
     #[case(Duration::from_secs(33),Timestamp { raw_microseconds: 33000000 })]
     fn from_duration_works(#[case] value: Duration, #[case] expected: Timestamp) {
@@ -1,13 +1,26 @@
 use dyn_clone::DynClone;
 
-use crate::{Content, Result, Timestamp, image::Image};
+use crate::{
+    Content, Rational, RationalTimestamp, Result, SeekMode, Size, StreamInfo, Timestamp,
+    image::{Image, PixelFormat},
+};
 
 pub trait VideoContentCursor {
     type Item: Image;
     fn offset(&self) -> Timestamp;
     fn fps(&self) -> f64;
+    /// Time base of the stream's own timestamps, which offsets are rounded from.
+    fn time_base(&self) -> Rational {
+        Rational::MICROSECOND
+    }
+    /// [`VideoContentCursor::offset`] in [`VideoContentCursor::time_base`], without rounding.
+    fn native_offset(&self) -> RationalTimestamp {
+        self.offset().into()
+    }
+    /// Returns `Ok(None)` once every decodable content has been returned, and keeps doing so until the next seek.
     fn read(&mut self) -> Result<Option<Content<Self::Item>>>;
     fn seek(&mut self, timestamp: Timestamp) -> Result<()>;
+    fn seek_with_mode(&mut self, timestamp: Timestamp, mode: SeekMode) -> Result<()>;
 }
 
 pub trait VideoInput: DynClone {
@@ -15,7 +28,124 @@ pub trait VideoInput: DynClone {
     type ContentCursor: VideoContentCursor;
     fn duration(&self) -> Timestamp;
     fn fps(&self) -> f64;
+    /// Every video stream of the input.
+    fn streams(&self) -> &[StreamInfo];
+    /// The stream read by cursors of this input.
+    fn stream_index(&self) -> usize;
     fn cursor(&self) -> Result<Self::ContentCursor>;
+    fn cursor_with_options(&self, options: VideoCursorOptions) -> Result<Self::ContentCursor>;
 }
 
 dyn_clone::clone_trait_object!(<I,C> VideoInput<Item = I,ContentCursor=C> where I:Image ,C:VideoContentCursor);
+
+/// How a frame is made to match a target size.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AspectRatioPolicy {
+    /// Keeps the aspect ratio and the whole frame; the result may be smaller than the target on one axis.
+    #[default]
+    Fit,
+    /// Keeps the aspect ratio and fills the target, cropping the overflow around the center.
+    Fill,
+    /// Scales each axis independently to the target.
+    Stretch,
+}
+
+impl AspectRatioPolicy {
+    /// Returns the centered region of `source` to keep and the size it is scaled to.
+    pub fn resolve(self, source: Size, target: Size) -> (Size, Size) {
+        let (source_width, source_height) = (source.width as f64, source.height as f64);
+        let (target_width, target_height) = (target.width as f64, target.height as f64);
+        let scaled = |length: f64, scale: f64| ((length * scale).round() as u32).max(1);
+        match self {
+            _ if source.width == 0 || source.height == 0 => (source, source),
+            Self::Fit => {
+                let scale = (target_width / source_width).min(target_height / source_height);
+                (
+                    source,
+                    Size::new(scaled(source_width, scale), scaled(source_height, scale)),
+                )
+            }
+            Self::Fill => {
+                let scale = (target_width / source_width).max(target_height / source_height);
+                let region = Size::new(
+                    scaled(target_width, 1.0 / scale).min(source.width),
+                    scaled(target_height, 1.0 / scale).min(source.height),
+                );
+                (region, target)
+            }
+            Self::Stretch => (source, target),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ScalingAlgorithm {
+    FastBilinear,
+    Bilinear,
+    #[default]
+    Bicubic,
+    Nearest,
+    Area,
+    Lanczos,
+    Spline,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VideoCursorOptions {
+    /// Format of the delivered frames; `None` keeps the decoder's native format when the image supports it.
+    pub pixel_format: Option<PixelFormat>,
+    /// Size to scale frames to according to `aspect_ratio`; `None` keeps the source size.
+    pub size: Option<Size>,
+    pub aspect_ratio: AspectRatioPolicy,
+    pub scaling: ScalingAlgorithm,
+}
+
+impl Default for VideoCursorOptions {
+    fn default() -> Self {
+        Self {
+            pixel_format: Some(PixelFormat::Rgba),
+            size: None,
+            aspect_ratio: AspectRatioPolicy::default(),
+            scaling: ScalingAlgorithm::default(),
+        }
+    }
+}
+
+impl VideoCursorOptions {
+    pub fn with_pixel_format(mut self, pixel_format: Option<PixelFormat>) -> Self {
+        self.pixel_format = pixel_format;
+        self
+    }
+    pub fn with_size(mut self, size: Size, aspect_ratio: AspectRatioPolicy) -> Self {
+        self.size = Some(size);
+        self.aspect_ratio = aspect_ratio;
+        self
+    }
+    pub fn with_scaling(mut self, scaling: ScalingAlgorithm) -> Self {
+        self.scaling = scaling;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(AspectRatioPolicy::Fit, Size::new(1920, 1080), Size::new(320, 320), (Size::new(1920, 1080), Size::new(320, 180)))]
+    #[case(AspectRatioPolicy::Fit, Size::new(1080, 1920), Size::new(320, 320), (Size::new(1080, 1920), Size::new(180, 320)))]
+    #[case(AspectRatioPolicy::Fill, Size::new(1920, 1080), Size::new(320, 320), (Size::new(1080, 1080), Size::new(320, 320)))]
+    #[case(AspectRatioPolicy::Fill, Size::new(1920, 1080), Size::new(640, 180), (Size::new(1920, 540), Size::new(640, 180)))]
+    #[case(AspectRatioPolicy::Stretch, Size::new(1920, 1080), Size::new(320, 320), (Size::new(1920, 1080), Size::new(320, 320)))]
+    #[case(AspectRatioPolicy::Fit, Size::new(0, 0), Size::new(320, 320), (Size::new(0, 0), Size::new(0, 0)))]
+    fn resolve_works(
+        #[case] policy: AspectRatioPolicy,
+        #[case] source: Size,
+        #[case] target: Size,
+        #[case] expected: (Size, Size),
+    ) {
+        assert_eq!(policy.resolve(source, target), expected);
+    }
+}
@@ -0,0 +1,41 @@
+use rmf_core::{Content, SeekMode, Timestamp};
+use rmf_static::{AudioChunker, AudioChunking};
+
+use crate::{Result, audio::Audio, service::ContentCursorTrait};
+
+/// Wraps an audio cursor so it returns blocks of a fixed length, e.g. one per video frame.
+/// The chunking itself is [`AudioChunker::read_from`], shared with `rmf_static::ChunkedAudioContentCursor`.
+pub struct ChunkedAudioContentCursor<C> {
+    inner: C,
+    chunker: AudioChunker,
+}
+
+impl<C: ContentCursorTrait<Item = Audio>> ChunkedAudioContentCursor<C> {
+    pub fn try_new(inner: C, chunking: AudioChunking) -> Result<Self> {
+        Ok(Self {
+            inner,
+            chunker: AudioChunker::try_new(chunking)?,
+        })
+    }
+    /// The offset of the last block read.
+    #[inline]
+    pub fn offset(&self) -> Timestamp {
+        self.chunker.offset()
+    }
+}
+
+impl<C: ContentCursorTrait<Item = Audio>> ContentCursorTrait for ChunkedAudioContentCursor<C> {
+    type Item = Audio;
+    fn read(&mut self) -> Result<Option<Content<Self::Item>>> {
+        self.chunker.read_from(|| self.inner.read())
+    }
+    #[inline]
+    fn seek(&mut self, timestamp: Timestamp) -> Result<()> {
+        self.seek_with_mode(timestamp, SeekMode::Keyframe)
+    }
+    fn seek_with_mode(&mut self, timestamp: Timestamp, mode: SeekMode) -> Result<()> {
+        self.inner.seek_with_mode(timestamp, mode)?;
+        self.chunker.reset();
+        Ok(())
+    }
+}
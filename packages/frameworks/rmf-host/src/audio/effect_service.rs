@@ -0,0 +1,123 @@
+use rmf_core::{
+    Content, SeekMode, Timestamp,
+    audio_effect::{AudioEffect, FadeCurve},
+};
+use rmf_static::AudioEffectChain;
+
+use crate::{
+    Result,
+    audio::Audio,
+    context::{ContextAudioContentCursor, ContextAudioContentStreamService},
+    service::{
+        AudioContentStreamServiceTrait, ContentCursorTrait, ContentStreamServiceTrait, ServiceTrait,
+    },
+};
+
+/// Applies gain, fade and envelope effects to another audio stream.
+/// Effect timestamps are on the wrapped stream's clock, so a clip added to a track keeps its fades
+/// wherever it is placed.
+#[derive(Clone)]
+pub struct AudioEffectService {
+    service: ContextAudioContentStreamService,
+    effects: Vec<AudioEffect>,
+}
+
+impl AudioEffectService {
+    pub fn new(service: impl Into<ContextAudioContentStreamService>) -> Self {
+        Self {
+            service: service.into(),
+            effects: vec![],
+        }
+    }
+    pub fn with_effect(mut self, effect: AudioEffect) -> Self {
+        self.effects.push(effect);
+        self
+    }
+    /// Constant gain in decibels.
+    pub fn with_gain_db(self, db: f32) -> Self {
+        self.with_effect(AudioEffect::Gain { db })
+    }
+    /// Fades in from the start of the stream.
+    pub fn with_fade_in(self, duration: Timestamp, curve: FadeCurve) -> Self {
+        self.with_effect(AudioEffect::FadeIn {
+            start: Timestamp::default(),
+            duration,
+            curve,
+        })
+    }
+    /// Fades out towards the end of the stream.
+    pub fn with_fade_out(self, duration: Timestamp, curve: FadeCurve) -> Self {
+        let end = self.service.duration();
+        self.with_effect(AudioEffect::FadeOut {
+            end,
+            duration,
+            curve,
+        })
+    }
+    #[inline]
+    pub fn service(&self) -> &ContextAudioContentStreamService {
+        &self.service
+    }
+    #[inline]
+    pub fn effects(&self) -> &[AudioEffect] {
+        &self.effects
+    }
+    #[inline]
+    pub fn effects_mut(&mut self) -> &mut Vec<AudioEffect> {
+        &mut self.effects
+    }
+}
+
+impl ServiceTrait for AudioEffectService {}
+
+impl ContentStreamServiceTrait for AudioEffectService {
+    type Item = Audio;
+    type ContentCursor = AudioEffectContentCursor;
+    #[inline]
+    fn duration(&self) -> Timestamp {
+        self.service.duration()
+    }
+    fn cursor(&self) -> Result<Self::ContentCursor> {
+        Ok(AudioEffectContentCursor::new(
+            self.service.cursor()?,
+            self.effects.clone(),
+        ))
+    }
+}
+
+impl AudioContentStreamServiceTrait for AudioEffectService {}
+
+/// Wraps an audio cursor and applies effects to everything it reads.
+/// The effects themselves are [`AudioEffectChain::read_from`], shared with `rmf_static::AudioEffectContentCursor`.
+pub struct AudioEffectContentCursor<C = ContextAudioContentCursor> {
+    inner: C,
+    chain: AudioEffectChain,
+}
+
+impl<C: ContentCursorTrait<Item = Audio>> AudioEffectContentCursor<C> {
+    pub fn new(inner: C, effects: Vec<AudioEffect>) -> Self {
+        Self {
+            inner,
+            chain: AudioEffectChain::new(effects),
+        }
+    }
+    #[inline]
+    pub fn effects(&self) -> &[AudioEffect] {
+        self.chain.effects()
+    }
+}
+
+impl<C: ContentCursorTrait<Item = Audio>> ContentCursorTrait for AudioEffectContentCursor<C> {
+    type Item = Audio;
+    fn read(&mut self) -> Result<Option<Content<Self::Item>>> {
+        self.chain.read_from(|| self.inner.read())
+    }
+    #[inline]
+    fn seek(&mut self, timestamp: Timestamp) -> Result<()> {
+        self.inner.seek(timestamp)
+    }
+    #[inline]
+    fn seek_with_mode(&mut self, timestamp: Timestamp, mode: SeekMode) -> Result<()> {
+        self.inner.seek_with_mode(timestamp, mode)
+    }
+}
@@ -1,6 +1,6 @@
 use rmf_core::{
-    InputSource,
-    audio::{AudioContentCursor, AudioInput},
+    InputSource, StreamInfo,
+    audio::{AudioContentCursor, AudioCursorOptions, AudioInput},
 };
 use rmf_static::{Audio, DefaultAudioContentCursor, DefaultAudioInput, DefaultAudioInputProvider};
 
@@ -26,6 +26,18 @@ impl rmf_core::audio::AudioContentCursor for ContextAudioContextCursor {
         }
     }
     #[inline]
+    fn time_base(&self) -> rmf_core::Rational {
+        match self {
+            Self::Default(d) => d.time_base(),
+        }
+    }
+    #[inline]
+    fn native_offset(&self) -> rmf_core::RationalTimestamp {
+        match self {
+            Self::Default(d) => d.native_offset(),
+        }
+    }
+    #[inline]
     fn read(&mut self) -> rmf_core::Result<Option<rmf_core::Content<Self::Item>>> {
         match self {
             Self::Default(d) => d.read(),
@@ -37,6 +49,16 @@ impl rmf_core::audio::AudioContentCursor for ContextAudioContextCursor {
             Self::Default(d) => d.seek(timestamp),
         }
     }
+    #[inline]
+    fn seek_with_mode(
+        &mut self,
+        timestamp: rmf_core::Timestamp,
+        mode: rmf_core::SeekMode,
+    ) -> rmf_core::Result<()> {
+        match self {
+            Self::Default(d) => d.seek_with_mode(timestamp, mode),
+        }
+    }
 }
 
 impl rmf_core::audio::AudioInput for ContextAudioInput {
@@ -50,6 +72,18 @@ impl rmf_core::audio::AudioInput for ContextAudioInput {
         }
     }
 
+    #[inline]
+    fn cursor_with_options(
+        &self,
+        options: AudioCursorOptions,
+    ) -> rmf_core::Result<Self::ContentCursor> {
+        match self {
+            Self::Default(d) => Ok(ContextAudioContextCursor::Default(
+                d.cursor_with_options(options)?,
+            )),
+        }
+    }
+
     #[inline]
     fn duration(&self) -> rmf_core::Timestamp {
         match self {
@@ -63,11 +97,26 @@ impl rmf_core::audio::AudioInput for ContextAudioInput {
             Self::Default(d) => d.sample_rate(),
         }
     }
+
+    #[inline]
+    fn streams(&self) -> &[rmf_core::StreamInfo] {
+        match self {
+            Self::Default(d) => d.streams(),
+        }
+    }
+
+    #[inline]
+    fn stream_index(&self) -> usize {
+        match self {
+            Self::Default(d) => d.stream_index(),
+        }
+    }
 }
 
 #[derive(Clone)]
 pub struct AudioInputService {
     inner: ContextAudioInput,
+    cursor_options: AudioCursorOptions,
 }
 
 pub struct AudioInputContentCursor {
@@ -78,6 +127,30 @@ impl AudioInputService {
     pub fn try_new(source: InputSource) -> crate::Result<Self> {
         Ok(Self::from(DefaultAudioInputProvider::provide(source)?))
     }
+    pub fn try_new_with_stream(source: InputSource, stream_index: usize) -> crate::Result<Self> {
+        Ok(Self::from(DefaultAudioInputProvider::provide_stream(
+            source,
+            stream_index,
+        )?))
+    }
+    /// Every audio stream of the input; pass an index to `try_new_with_stream` to read another one.
+    #[inline]
+    pub fn streams(&self) -> &[StreamInfo] {
+        self.inner.streams()
+    }
+    #[inline]
+    pub fn stream_index(&self) -> usize {
+        self.inner.stream_index()
+    }
+    /// Sets how cursors deliver audio, e.g. as packed `f32` stereo at 48 kHz.
+    pub fn with_cursor_options(mut self, options: AudioCursorOptions) -> Self {
+        self.cursor_options = options;
+        self
+    }
+    #[inline]
+    pub fn cursor_options(&self) -> &AudioCursorOptions {
+        &self.cursor_options
+    }
 }
 
 impl ContentCursorTrait for AudioInputContentCursor {
@@ -88,12 +161,20 @@ impl ContentCursorTrait for AudioInputContentCursor {
     fn seek(&mut self, timestamp: rmf_core::Timestamp) -> crate::Result<()> {
         Ok(self.inner.seek(timestamp)?)
     }
+    fn seek_with_mode(
+        &mut self,
+        timestamp: rmf_core::Timestamp,
+        mode: rmf_core::SeekMode,
+    ) -> crate::Result<()> {
+        Ok(self.inner.seek_with_mode(timestamp, mode)?)
+    }
 }
 
 impl From<DefaultAudioInput> for AudioInputService {
     fn from(value: DefaultAudioInput) -> Self {
         AudioInputService {
             inner: ContextAudioInput::Default(value),
+            cursor_options: AudioCursorOptions::default(),
         }
     }
 }
@@ -111,7 +192,9 @@ impl ContentStreamServiceTrait for AudioInputService {
     #[inline]
     fn cursor(&self) -> crate::Result<Self::ContentCursor> {
         Ok(AudioInputContentCursor {
-            inner: self.inner.cursor()?,
+            inner: self
+                .inner
+                .cursor_with_options(self.cursor_options.clone())?,
         })
     }
 }
@@ -0,0 +1,18 @@
+use rmf_core::loudness::LoudnessReport;
+use rmf_static::LoudnessAnalyzer;
+
+use crate::{
+    Result,
+    service::{AudioContentStreamServiceTrait, ContentCursorTrait},
+};
+
+/// Reads `service` from the start to the end and measures its loudness, e.g. to check a
+/// deliverable against EBU R128 before writing it.
+pub fn analyze_loudness(service: &impl AudioContentStreamServiceTrait) -> Result<LoudnessReport> {
+    let mut cursor = service.cursor()?;
+    let mut analyzer = LoudnessAnalyzer::new();
+    while let Some(content) = cursor.read()? {
+        analyzer.push(&content)?;
+    }
+    Ok(analyzer.report()?)
+}
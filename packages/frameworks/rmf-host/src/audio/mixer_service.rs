@@ -0,0 +1,435 @@
+use rmf_core::{Content, SeekMode, Timestamp};
+use rmf_static::{AudioData, sample_position, sample_timestamp};
+
+use crate::{
+    Result,
+    audio::{Audio, AudioDataContext, ChannelLayout, ChannelPosition},
+    context::{ContextAudioContentCursor, ContextAudioContentStreamService},
+    service::{
+        AudioContentStreamServiceTrait, ContentCursorTrait, ContentStreamServiceTrait, ServiceTrait,
+    },
+    timeline::audio_lane::{AudioLane, accumulate},
+};
+
+/// One input of an [`AudioMixerService`].
+#[derive(Clone)]
+pub struct MixerSource {
+    service: ContextAudioContentStreamService,
+    gain: f32,
+    pan: f32,
+    offset: Timestamp,
+}
+
+impl MixerSource {
+    pub fn new(service: impl Into<ContextAudioContentStreamService>) -> Self {
+        Self {
+            service: service.into(),
+            gain: 1.0,
+            pan: 0.0,
+            offset: Timestamp::default(),
+        }
+    }
+    /// Linear gain; `1.0` keeps the level.
+    pub fn with_gain(mut self, gain: f32) -> Self {
+        self.gain = gain;
+        self
+    }
+    /// Balance from `-1.0` (left only) to `1.0` (right only).
+    pub fn with_pan(mut self, pan: f32) -> Self {
+        self.pan = pan.clamp(-1.0, 1.0);
+        self
+    }
+    /// Where the source starts in the mix.
+    pub fn with_offset(mut self, offset: Timestamp) -> Self {
+        self.offset = offset;
+        self
+    }
+    #[inline]
+    pub fn gain(&self) -> f32 {
+        self.gain
+    }
+    #[inline]
+    pub fn pan(&self) -> f32 {
+        self.pan
+    }
+    #[inline]
+    pub fn offset(&self) -> Timestamp {
+        self.offset
+    }
+}
+
+/// How the mix is kept within `-1.0..=1.0`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MixerClipping {
+    /// Clamps every sample.
+    Hard,
+    /// Leaves samples below `knee` untouched and bends louder ones smoothly towards full scale.
+    Soft { knee: f32 },
+    /// Scales each block down to its peak, recovering by `release` of gain per block.
+    Limit { release: f32 },
+}
+
+impl Default for MixerClipping {
+    fn default() -> Self {
+        Self::Soft { knee: 0.8 }
+    }
+}
+
+/// Mixes several audio streams into one `f32` planar stream.
+/// Sources at other sample rates are resampled, and their channels remixed to the mixer's layout.
+#[derive(Clone)]
+pub struct AudioMixerService {
+    sources: Vec<MixerSource>,
+    sample_rate: u32,
+    channel_layout: ChannelLayout,
+    block_samples: usize,
+    clipping: MixerClipping,
+}
+
+impl AudioMixerService {
+    pub fn new(sample_rate: u32, channel_layout: ChannelLayout) -> Self {
+        Self {
+            sources: vec![],
+            sample_rate,
+            channel_layout,
+            block_samples: 1024,
+            clipping: MixerClipping::default(),
+        }
+    }
+    pub fn with_source(mut self, source: MixerSource) -> Self {
+        self.add_source(source);
+        self
+    }
+    #[inline]
+    pub fn add_source(&mut self, source: MixerSource) {
+        self.sources.push(source);
+    }
+    #[inline]
+    pub fn sources(&self) -> &[MixerSource] {
+        &self.sources
+    }
+    #[inline]
+    pub fn sources_mut(&mut self) -> &mut Vec<MixerSource> {
+        &mut self.sources
+    }
+    /// Samples per channel in each mixed block.
+    pub fn with_block_samples(mut self, block_samples: usize) -> Self {
+        self.block_samples = block_samples.max(1);
+        self
+    }
+    pub fn with_clipping(mut self, clipping: MixerClipping) -> Self {
+        self.clipping = clipping;
+        self
+    }
+    #[inline]
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+    #[inline]
+    pub fn channel_layout(&self) -> &ChannelLayout {
+        &self.channel_layout
+    }
+    #[inline]
+    pub fn clipping(&self) -> MixerClipping {
+        self.clipping
+    }
+}
+
+impl ServiceTrait for AudioMixerService {}
+
+impl ContentStreamServiceTrait for AudioMixerService {
+    type Item = Audio;
+    type ContentCursor = AudioMixerContentCursor;
+    fn duration(&self) -> Timestamp {
+        self.sources
+            .iter()
+            .map(|s| s.offset + s.service.duration())
+            .fold(Timestamp::default(), |max, duration| {
+                if duration > max { duration } else { max }
+            })
+    }
+    fn cursor(&self) -> Result<Self::ContentCursor> {
+        let channels = self.channel_layout.channels();
+        let lanes = self
+            .sources
+            .iter()
+            .map(|source| {
+                Ok(MixerLane {
+                    lane: AudioLane::new(source.service.cursor()?, channels, self.sample_rate),
+                    gains: self
+                        .channel_layout
+                        .positions()
+                        .iter()
+                        .map(|position| source.gain * pan_gain(*position, source.pan))
+                        .collect(),
+                    offset: sample_position(source.offset, self.sample_rate),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(AudioMixerContentCursor {
+            lanes,
+            sample_rate: self.sample_rate,
+            channel_layout: self.channel_layout.clone(),
+            block_samples: self.block_samples,
+            clipping: self.clipping,
+            limiter_gain: 1.0,
+            position: 0,
+            end: sample_position(self.duration(), self.sample_rate),
+        })
+    }
+}
+
+impl AudioContentStreamServiceTrait for AudioMixerService {}
+
+struct MixerLane<C = ContextAudioContentCursor> {
+    lane: AudioLane<C>,
+    gains: Vec<f32>,
+    offset: i64,
+}
+
+pub struct AudioMixerContentCursor {
+    lanes: Vec<MixerLane>,
+    sample_rate: u32,
+    channel_layout: ChannelLayout,
+    block_samples: usize,
+    clipping: MixerClipping,
+    limiter_gain: f32,
+    position: i64,
+    end: i64,
+}
+
+impl AudioMixerContentCursor {
+    fn clip(&mut self, mix: &mut [Vec<f32>]) {
+        match self.clipping {
+            MixerClipping::Hard => {}
+            MixerClipping::Soft { knee } => {
+                let knee = knee.clamp(0.0, 1.0);
+                mix.iter_mut()
+                    .flatten()
+                    .for_each(|sample| *sample = soft_clip(*sample, knee));
+            }
+            MixerClipping::Limit { release } => {
+                self.limiter_gain = limit(mix, self.limiter_gain, release);
+            }
+        }
+        mix.iter_mut()
+            .flatten()
+            .for_each(|sample| *sample = sample.clamp(-1.0, 1.0));
+    }
+}
+
+impl ContentCursorTrait for AudioMixerContentCursor {
+    type Item = Audio;
+    fn read(&mut self) -> Result<Option<Content<Self::Item>>> {
+        if self.position >= self.end {
+            return Ok(None);
+        }
+        let start = self.position;
+        let end = (start + self.block_samples as i64).min(self.end);
+        let mut mix = mix_lanes(&mut self.lanes, start, end, self.channel_layout.channels())?;
+        self.clip(&mut mix);
+        self.position = end;
+        let offset = sample_timestamp(start, self.sample_rate);
+        Ok(Some(Content::new(
+            Audio::tyr_new(
+                AudioDataContext::F32(AudioData::from_channels(mix)?),
+                self.sample_rate,
+                self.channel_layout.clone(),
+            )?,
+            offset,
+            sample_timestamp(end, self.sample_rate) - offset,
+        )))
+    }
+    #[inline]
+    fn seek(&mut self, timestamp: Timestamp) -> Result<()> {
+        self.seek_with_mode(timestamp, SeekMode::Exact)
+    }
+    /// Always exact, since sources are cut at sample positions.
+    fn seek_with_mode(&mut self, timestamp: Timestamp, _mode: SeekMode) -> Result<()> {
+        self.position = sample_position(timestamp, self.sample_rate).max(0);
+        for lane in self.lanes.iter_mut() {
+            let source_position = self.position - lane.offset;
+            lane.lane.seek(if source_position > 0 {
+                sample_timestamp(source_position, self.sample_rate)
+            } else {
+                Timestamp::default()
+            })?;
+        }
+        self.limiter_gain = 1.0;
+        Ok(())
+    }
+}
+
+/// Sums the lanes' gained samples in `start..end` of the mix, with silence outside each source.
+fn mix_lanes<C: ContentCursorTrait<Item = Audio>>(
+    lanes: &mut [MixerLane<C>],
+    start: i64,
+    end: i64,
+    channels: usize,
+) -> Result<Vec<Vec<f32>>> {
+    let mut mix = vec![vec![0.0f32; (end - start) as usize]; channels];
+    for lane in lanes.iter_mut() {
+        let mut lines = lane.lane.take(start - lane.offset, end - lane.offset)?;
+        for (line, gain) in lines.iter_mut().zip(&lane.gains) {
+            line.iter_mut().for_each(|sample| *sample *= gain);
+        }
+        accumulate(&mut mix, lines);
+    }
+    Ok(mix)
+}
+
+/// Scales `mix` down to its peak at once, or recovers from `gain` by `release` otherwise; returns the gain applied.
+fn limit(mix: &mut [Vec<f32>], gain: f32, release: f32) -> f32 {
+    let peak = mix
+        .iter()
+        .flatten()
+        .fold(0.0f32, |peak, s| peak.max(s.abs()));
+    let target = if peak > 1.0 { 1.0 / peak } else { 1.0 };
+    let gain = if target < gain {
+        target
+    } else {
+        (gain + release.max(0.0)).min(target)
+    };
+    mix.iter_mut().flatten().for_each(|sample| *sample *= gain);
+    gain
+}
+
+/// Balance gain of a channel: panning right attenuates left channels and the other way round.
+fn pan_gain(position: ChannelPosition, pan: f32) -> f32 {
+    use ChannelPosition::*;
+    match position {
+        FrontLeft | BackLeft | SideLeft | FrontLeftOfCenter | TopFrontLeft | TopBackLeft
+        | StereoLeft | WideLeft | SurroundDirectLeft | TopSideLeft | BottomFrontLeft => {
+            (1.0 - pan).min(1.0)
+        }
+        FrontRight | BackRight | SideRight | FrontRightOfCenter | TopFrontRight | TopBackRight
+        | StereoRight | WideRight | SurroundDirectRight | TopSideRight | BottomFrontRight => {
+            (1.0 + pan).min(1.0)
+        }
+        _ => 1.0,
+    }
+}
+
+#[inline]
+fn soft_clip(sample: f32, knee: f32) -> f32 {
+    let magnitude = sample.abs();
+    if magnitude <= knee || knee >= 1.0 {
+        sample
+    } else {
+        let headroom = 1.0 - knee;
+        sample.signum() * (knee + headroom * ((magnitude - knee) / headroom).tanh())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{Blocks, BlocksCursor, RATE};
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    /// A mono source of `len` samples in one block, where sample `i` is `i + 1`.
+    fn lane(len: usize, offset: i64, gain: f32) -> MixerLane<BlocksCursor> {
+        MixerLane {
+            lane: AudioLane::new(Blocks::ramp(len, len, 1.0).cursor().unwrap(), 1, RATE),
+            gains: vec![gain],
+            offset,
+        }
+    }
+
+    #[rstest]
+    #[case(ChannelPosition::FrontLeft, 0.0, 1.0)]
+    #[case(ChannelPosition::FrontRight, 0.0, 1.0)]
+    #[case(ChannelPosition::FrontLeft, 0.5, 0.5)]
+    #[case(ChannelPosition::FrontRight, 0.5, 1.0)]
+    #[case(ChannelPosition::FrontLeft, -0.25, 1.0)]
+    #[case(ChannelPosition::SideRight, -0.25, 0.75)]
+    #[case(ChannelPosition::BackLeft, 1.0, 0.0)]
+    #[case(ChannelPosition::FrontRight, -1.0, 0.0)]
+    #[case(ChannelPosition::FrontCenter, 1.0, 1.0)]
+    #[case(ChannelPosition::LowFrequency, -1.0, 1.0)]
+    fn pan_gain_works(#[case] position: ChannelPosition, #[case] pan: f32, #[case] expected: f32) {
+        assert_eq!(pan_gain(position, pan), expected);
+    }
+
+    #[rstest]
+    #[case(0.5, 0.8, 0.5)]
+    #[case(-0.8, 0.8, -0.8)]
+    #[case(1.5, 1.0, 1.5)]
+    #[case(0.0, 0.0, 0.0)]
+    fn soft_clip_keeps_samples_below_knee(
+        #[case] sample: f32,
+        #[case] knee: f32,
+        #[case] expected: f32,
+    ) {
+        assert_eq!(soft_clip(sample, knee), expected);
+    }
+
+    #[rstest]
+    #[case(0.9, 0.8)]
+    #[case(1.0, 0.8)]
+    #[case(-3.0, 0.8)]
+    #[case(100.0, 0.5)]
+    #[case(0.5, 0.0)]
+    fn soft_clip_bends_towards_full_scale(#[case] sample: f32, #[case] knee: f32) {
+        let clipped = soft_clip(sample, knee);
+        assert_eq!(clipped.signum(), sample.signum());
+        assert!(clipped.abs() > knee && clipped.abs() < sample.abs().min(1.0) + f32::EPSILON);
+        assert!(soft_clip(sample * 2.0, knee).abs() >= clipped.abs());
+    }
+
+    #[rstest]
+    #[case(vec![2.0, 0.5, 0.5, 0.5, 0.5, 0.5, 0.5], 0.2, vec![0.5, 0.7, 0.9, 1.0, 1.0, 1.0, 1.0])]
+    #[case(vec![4.0, 1.0, 2.0, 1.0], 0.5, vec![0.25, 0.75, 0.5, 1.0])]
+    #[case(vec![2.0, 1.6, 1.0], 0.25, vec![0.5, 0.625, 0.875])]
+    #[case(vec![2.0, 0.5, 0.5], 0.0, vec![0.5, 0.5, 0.5])]
+    #[case(vec![0.5, 1.0], 0.1, vec![1.0, 1.0])]
+    fn limit_releases_gradually(
+        #[case] peaks: Vec<f32>,
+        #[case] release: f32,
+        #[case] expected: Vec<f32>,
+    ) {
+        let mut gain = 1.0;
+        let gains = peaks
+            .into_iter()
+            .map(|peak| {
+                let mut mix = vec![vec![peak, -peak / 2.0]];
+                gain = limit(&mut mix, gain, release);
+                assert_eq!(mix, vec![vec![peak * gain, -peak / 2.0 * gain]]);
+                assert!(mix[0][0] <= 1.0);
+                (gain * 1000.0).round() / 1000.0
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(gains, expected);
+    }
+
+    #[rstest]
+    #[case(0, 0, 6, vec![1.0, 2.0, 3.0, 4.0, 0.0, 0.0])]
+    #[case(2, 0, 8, vec![0.0, 0.0, 1.0, 2.0, 3.0, 4.0, 0.0, 0.0])]
+    #[case(-2, 0, 4, vec![3.0, 4.0, 0.0, 0.0])]
+    #[case(-10, 0, 3, vec![0.0, 0.0, 0.0])]
+    #[case(10, 0, 3, vec![0.0, 0.0, 0.0])]
+    #[case(1, 3, 7, vec![3.0, 4.0, 0.0, 0.0])]
+    fn mix_lanes_places_sources_at_offset(
+        #[case] offset: i64,
+        #[case] start: i64,
+        #[case] end: i64,
+        #[case] expected: Vec<f32>,
+    ) {
+        let mut lanes = [lane(4, offset, 1.0)];
+        assert_eq!(
+            mix_lanes(&mut lanes, start, end, 1).unwrap(),
+            vec![expected]
+        );
+    }
+
+    #[rstest]
+    fn mix_lanes_sums_gained_sources_over_consecutive_blocks() {
+        let mut lanes = [lane(4, 0, 0.5), lane(3, 2, 2.0)];
+        let blocks = [(0, 3), (3, 6)]
+            .into_iter()
+            .map(|(start, end)| mix_lanes(&mut lanes, start, end, 1).unwrap().remove(0))
+            .collect::<Vec<_>>();
+        assert_eq!(blocks, vec![vec![0.5, 1.0, 3.5], vec![6.0, 6.0, 0.0]]);
+    }
+}
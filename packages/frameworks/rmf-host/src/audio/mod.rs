@@ -1,5 +1,18 @@
+mod chunked_cursor;
+mod effect_service;
 mod input_service;
+mod loudness;
+mod mixer_service;
 
+pub use chunked_cursor::*;
+pub use effect_service::*;
 pub use input_service::*;
+pub use loudness::*;
+pub use mixer_service::*;
 
-pub use rmf_static::audio::{Audio, AudioDataContext};
+pub use rmf_core::audio::{
+    AudioCursorOptions, AudioFormat, ChannelLayout, ChannelPosition, SampleLayout,
+};
+pub use rmf_core::audio_effect::*;
+pub use rmf_core::loudness::{LoudnessMeter, LoudnessPoint, LoudnessReport};
+pub use rmf_static::audio::{Audio, AudioChunking, AudioDataContext};
@@ -0,0 +1,100 @@
+use rmf_static::Audio;
+
+use crate::{
+    audio::{
+        AudioEffectContentCursor, AudioEffectService, AudioInputContentCursor, AudioInputService,
+    },
+    service::{
+        AudioContentStreamServiceTrait, ContentCursorTrait, ContentStreamServiceTrait, ServiceTrait,
+    },
+    tracks::{AudioTrack, AudioTrackContentCursor},
+};
+
+pub enum ContextAudioContentCursor {
+    AudioInputContentCursor(AudioInputContentCursor),
+    AudioTrackContentCursor(Box<AudioTrackContentCursor>),
+    AudioEffectContentCursor(Box<AudioEffectContentCursor>),
+}
+
+impl ContentCursorTrait for ContextAudioContentCursor {
+    type Item = Audio;
+    fn read(&mut self) -> crate::Result<Option<rmf_core::Content<Self::Item>>> {
+        match self {
+            Self::AudioInputContentCursor(a) => a.read(),
+            Self::AudioTrackContentCursor(t) => t.read(),
+            Self::AudioEffectContentCursor(e) => e.read(),
+        }
+    }
+    fn seek(&mut self, timestamp: rmf_core::Timestamp) -> crate::Result<()> {
+        match self {
+            Self::AudioInputContentCursor(a) => a.seek(timestamp),
+            Self::AudioTrackContentCursor(t) => t.seek(timestamp),
+            Self::AudioEffectContentCursor(e) => e.seek(timestamp),
+        }
+    }
+    fn seek_with_mode(
+        &mut self,
+        timestamp: rmf_core::Timestamp,
+        mode: rmf_core::SeekMode,
+    ) -> crate::Result<()> {
+        match self {
+            Self::AudioInputContentCursor(a) => a.seek_with_mode(timestamp, mode),
+            Self::AudioTrackContentCursor(t) => t.seek_with_mode(timestamp, mode),
+            Self::AudioEffectContentCursor(e) => e.seek_with_mode(timestamp, mode),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub enum ContextAudioContentStreamService {
+    AudioInputService(AudioInputService),
+    AudioTrack(Box<AudioTrack>),
+    AudioEffectService(Box<AudioEffectService>),
+}
+
+impl From<AudioInputService> for ContextAudioContentStreamService {
+    fn from(value: AudioInputService) -> Self {
+        ContextAudioContentStreamService::AudioInputService(value)
+    }
+}
+
+impl From<AudioTrack> for ContextAudioContentStreamService {
+    fn from(value: AudioTrack) -> Self {
+        ContextAudioContentStreamService::AudioTrack(Box::new(value))
+    }
+}
+
+impl From<AudioEffectService> for ContextAudioContentStreamService {
+    fn from(value: AudioEffectService) -> Self {
+        ContextAudioContentStreamService::AudioEffectService(Box::new(value))
+    }
+}
+
+impl ServiceTrait for ContextAudioContentStreamService {}
+
+impl ContentStreamServiceTrait for ContextAudioContentStreamService {
+    type Item = Audio;
+    type ContentCursor = ContextAudioContentCursor;
+    fn duration(&self) -> rmf_core::Timestamp {
+        match self {
+            ContextAudioContentStreamService::AudioTrack(t) => t.duration(),
+            ContextAudioContentStreamService::AudioInputService(i) => i.duration(),
+            ContextAudioContentStreamService::AudioEffectService(e) => e.duration(),
+        }
+    }
+    fn cursor(&self) -> crate::Result<Self::ContentCursor> {
+        Ok(match self {
+            ContextAudioContentStreamService::AudioTrack(t) => {
+                ContextAudioContentCursor::AudioTrackContentCursor(Box::new(t.cursor()?))
+            }
+            ContextAudioContentStreamService::AudioInputService(i) => {
+                ContextAudioContentCursor::AudioInputContentCursor(i.cursor()?)
+            }
+            ContextAudioContentStreamService::AudioEffectService(e) => {
+                ContextAudioContentCursor::AudioEffectContentCursor(Box::new(e.cursor()?))
+            }
+        })
+    }
+}
+
+impl AudioContentStreamServiceTrait for ContextAudioContentStreamService {}
@@ -1,3 +1,5 @@
+mod audio;
 mod video;
 
+pub use audio::*;
 pub use video::*;
@@ -27,6 +27,16 @@ impl ContentCursorTrait for ContextVideoContentCursor {
             Self::VideoTrackContentCursor(t) => t.seek(timestamp),
         }
     }
+    fn seek_with_mode(
+        &mut self,
+        timestamp: rmf_core::Timestamp,
+        mode: rmf_core::SeekMode,
+    ) -> crate::Result<()> {
+        match self {
+            Self::VideoInputContentCursor(v) => v.seek_with_mode(timestamp, mode),
+            Self::VideoTrackContentCursor(t) => t.seek_with_mode(timestamp, mode),
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -71,7 +81,7 @@ impl ContentStreamServiceTrait for ContextVideoContentStreamService {
 }
 
 impl VideoContentStreamServiceTrait for ContextVideoContentStreamService {
-    fn fps(&self) -> f64 {
+    fn fps(&self) -> Option<f64> {
         match self {
             ContextVideoContentStreamService::VideoTrack(t) => t.fps(),
             ContextVideoContentStreamService::VideoInputService(i) => i.fps(),
@@ -1 +1,4 @@
-pub use rmf_core::{Content, InputSource, Timestamp};
+pub use rmf_core::{
+    Content, InputSource, OutputDestination, Rational, RationalTimestamp, SeekMode, StreamInfo,
+    TimeRange, Timecode, Timestamp,
+};
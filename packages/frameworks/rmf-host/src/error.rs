@@ -4,6 +4,8 @@ use derive_new::new;
 pub enum Error {
     #[error("{0}")]
     Core(rmf_core::Error),
+    #[error("track error {0}")]
+    Track(anyhow::Error),
 }
 
 impl From<rmf_core::Error> for Error {
@@ -1 +1,2 @@
+pub use rmf_core::image::{ImagePlane, PixelFormat};
 pub use rmf_static::Image;
@@ -1,10 +1,15 @@
 pub mod audio;
-mod context;
+pub mod context;
 mod core;
 mod error;
 pub mod image;
+pub mod output;
+pub mod probe;
 mod result;
 pub mod service;
+#[cfg(test)]
+mod testing;
+pub mod timeline;
 pub mod tracks;
 pub mod video;
 
@@ -0,0 +1,8 @@
+mod output_service;
+
+pub use output_service::*;
+
+pub use rmf_core::output::{
+    AudioCodec, AudioEncoderOptions, ContainerFormat, OutputOptions, VideoCodec,
+    VideoEncoderOptions,
+};
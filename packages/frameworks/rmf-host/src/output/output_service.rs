@@ -0,0 +1,211 @@
+use rmf_core::OutputService as _;
+use rmf_core::output::OutputOptions;
+use rmf_static::output::{DefaultOutput, DefaultOutputProvider};
+
+use crate::{
+    Content, OutputDestination, Result,
+    audio::Audio,
+    image::Image,
+    service::{ContentCursorTrait, ContentStreamServiceTrait},
+    timeline::{Timeline, TimelineFrame},
+};
+
+enum ContextOutput {
+    Default(DefaultOutput),
+}
+
+impl rmf_core::OutputService for ContextOutput {
+    type Image = Image;
+    type Audio = Audio;
+    #[inline]
+    fn has_video(&self) -> bool {
+        match self {
+            Self::Default(d) => d.has_video(),
+        }
+    }
+    #[inline]
+    fn has_audio(&self) -> bool {
+        match self {
+            Self::Default(d) => d.has_audio(),
+        }
+    }
+    #[inline]
+    fn write_video(&mut self, content: &Content<Image>) -> rmf_core::Result<()> {
+        match self {
+            Self::Default(d) => d.write_video(content),
+        }
+    }
+    #[inline]
+    fn write_audio(&mut self, content: &Content<Audio>) -> rmf_core::Result<()> {
+        match self {
+            Self::Default(d) => d.write_audio(content),
+        }
+    }
+    #[inline]
+    fn finish(&mut self) -> rmf_core::Result<()> {
+        match self {
+            Self::Default(d) => d.finish(),
+        }
+    }
+}
+
+pub struct OutputService {
+    inner: ContextOutput,
+}
+
+impl From<DefaultOutput> for OutputService {
+    fn from(value: DefaultOutput) -> Self {
+        OutputService {
+            inner: ContextOutput::Default(value),
+        }
+    }
+}
+
+impl OutputService {
+    pub fn try_new(destination: OutputDestination, options: OutputOptions) -> Result<Self> {
+        Ok(Self::from(DefaultOutputProvider::provide(
+            destination,
+            options,
+        )?))
+    }
+
+    #[inline]
+    pub fn has_video(&self) -> bool {
+        self.inner.has_video()
+    }
+
+    #[inline]
+    pub fn has_audio(&self) -> bool {
+        self.inner.has_audio()
+    }
+
+    #[inline]
+    pub fn write_video(&mut self, content: &Content<Image>) -> Result<()> {
+        Ok(self.inner.write_video(content)?)
+    }
+
+    #[inline]
+    pub fn write_audio(&mut self, content: &Content<Audio>) -> Result<()> {
+        Ok(self.inner.write_audio(content)?)
+    }
+
+    /// Writes the parts of a tick the output has a stream for.
+    pub fn write_timeline_frame(&mut self, content: Content<TimelineFrame>) -> Result<()> {
+        Ok(write_tick(&mut self.inner, content)?)
+    }
+
+    /// Renders every tick of `timeline` and finishes the output.
+    pub fn write_timeline(&mut self, timeline: &Timeline) -> Result<()> {
+        let mut cursor = timeline.cursor()?;
+        while let Some(content) = cursor.read()? {
+            self.write_timeline_frame(content)?;
+        }
+        self.finish()
+    }
+
+    #[inline]
+    pub fn finish(&mut self) -> Result<()> {
+        Ok(self.inner.finish()?)
+    }
+}
+
+fn write_tick<O: rmf_core::OutputService<Image = Image, Audio = Audio>>(
+    output: &mut O,
+    content: Content<TimelineFrame>,
+) -> rmf_core::Result<()> {
+    let (offset, duration) = (content.offset(), content.duration());
+    let (image, audio) = content.into_item().into_parts();
+    if output.has_video() {
+        output.write_video(&Content::new(image, offset, duration))?;
+    }
+    if output.has_audio() {
+        output.write_audio(&Content::new(audio, offset, duration))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::{AudioDataContext, ChannelLayout};
+    use anyhow::anyhow;
+    use pretty_assertions::assert_eq;
+    use rmf_core::{Size, Timestamp};
+    use rmf_static::AudioData;
+    use rstest::rstest;
+
+    /// Records what is written and, like a real output, fails for streams it wasn't configured with.
+    struct Recorder {
+        video: bool,
+        audio: bool,
+        written: Vec<(&'static str, i64)>,
+    }
+
+    impl rmf_core::OutputService for Recorder {
+        type Image = Image;
+        type Audio = Audio;
+        fn has_video(&self) -> bool {
+            self.video
+        }
+        fn has_audio(&self) -> bool {
+            self.audio
+        }
+        fn write_video(&mut self, content: &Content<Image>) -> rmf_core::Result<()> {
+            if !self.video {
+                return Err(rmf_core::Error::new_output(anyhow!(
+                    "no video stream configured"
+                )));
+            }
+            self.written
+                .push(("video", content.offset().as_milliseconds()));
+            Ok(())
+        }
+        fn write_audio(&mut self, content: &Content<Audio>) -> rmf_core::Result<()> {
+            if !self.audio {
+                return Err(rmf_core::Error::new_output(anyhow!(
+                    "no audio stream configured"
+                )));
+            }
+            self.written
+                .push(("audio", content.offset().as_milliseconds()));
+            Ok(())
+        }
+        fn finish(&mut self) -> rmf_core::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn tick(offset: i64) -> Content<TimelineFrame> {
+        let image = Image::new_size(Size::new(1, 1), &[0; 4]).unwrap();
+        let audio = Audio::tyr_new(
+            AudioDataContext::F32(AudioData::from_channels(vec![vec![0.0; 4]]).unwrap()),
+            100,
+            ChannelLayout::mono(),
+        )
+        .unwrap();
+        Content::new(
+            TimelineFrame::new(image, audio),
+            Timestamp::from_milliseconds(offset),
+            Timestamp::from_milliseconds(40),
+        )
+    }
+
+    #[rstest]
+    #[case(true, false, vec![("video", 0), ("video", 40)])]
+    #[case(false, true, vec![("audio", 0), ("audio", 40)])]
+    #[case(true, true, vec![("video", 0), ("audio", 0), ("video", 40), ("audio", 40)])]
+    fn write_tick_skips_missing_streams(
+        #[case] video: bool,
+        #[case] audio: bool,
+        #[case] expected: Vec<(&'static str, i64)>,
+    ) {
+        let mut output = Recorder {
+            video,
+            audio,
+            written: vec![],
+        };
+        write_tick(&mut output, tick(0)).unwrap();
+        write_tick(&mut output, tick(40)).unwrap();
+        assert_eq!(output.written, expected);
+    }
+}
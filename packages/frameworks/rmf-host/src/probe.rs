@@ -0,0 +1,12 @@
+use rmf_static::probe::DefaultProbeProvider;
+
+use crate::{InputSource, Result};
+
+pub use rmf_core::probe::{
+    AudioStreamReport, MediaType, ProbeReport, StreamReport, VideoStreamReport,
+};
+
+/// Reads container and stream metadata of `source` without decoding, e.g. to validate uploads.
+pub fn probe(source: &InputSource) -> Result<ProbeReport> {
+    Ok(DefaultProbeProvider::probe(source)?)
+}
@@ -1,4 +1,4 @@
-use rmf_core::{Content, InnerContent, Timestamp};
+use rmf_core::{Content, InnerContent, SeekMode, Timestamp};
 use rmf_static::{Audio, Image};
 
 use crate::Result;
@@ -9,17 +9,19 @@ pub trait ContentCursorTrait {
     type Item: InnerContent;
     fn read(&mut self) -> Result<Option<Content<Self::Item>>>;
     fn seek(&mut self, timestamp: Timestamp) -> Result<()>;
+    fn seek_with_mode(&mut self, timestamp: Timestamp, mode: SeekMode) -> Result<()>;
 }
 
 pub trait ContentStreamServiceTrait: ServiceTrait {
     type Item: InnerContent;
-    type ContentCursor: ContentCursorTrait;
+    type ContentCursor: ContentCursorTrait<Item = Self::Item>;
     fn duration(&self) -> Timestamp;
     fn cursor(&self) -> Result<Self::ContentCursor>;
 }
 
 pub trait VideoContentStreamServiceTrait: ContentStreamServiceTrait<Item = Image> {
-    fn fps(&self) -> f64;
+    /// `None` when there is no rate to report, e.g. for an empty track or an input without one.
+    fn fps(&self) -> Option<f64>;
 }
 
 pub trait VideoInputServiceTrait: ContentStreamServiceTrait<Item = Image> {}
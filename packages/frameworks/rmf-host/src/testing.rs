@@ -0,0 +1,99 @@
+//! Fakes shared by the unit tests.
+
+use rmf_core::{Content, SeekMode, Timestamp};
+use rmf_static::{AudioData, sample_position, sample_timestamp};
+
+use crate::{
+    Result,
+    audio::{Audio, AudioDataContext, ChannelLayout},
+    service::{ContentCursorTrait, ContentStreamServiceTrait, ServiceTrait},
+};
+
+/// Sample rate of [`Blocks`].
+pub(crate) const RATE: u32 = 100;
+
+/// Mono `f32` audio at [`RATE`] read as `(first sample, samples)` blocks, with silence between them.
+#[derive(Clone)]
+pub(crate) struct Blocks(Vec<(i64, Vec<f32>)>);
+
+impl Blocks {
+    pub(crate) fn new(blocks: Vec<(i64, Vec<f32>)>) -> Self {
+        Self(blocks)
+    }
+
+    /// `len` samples in blocks of `block_len`, where every sample holds `first` plus its index.
+    pub(crate) fn ramp(len: usize, block_len: usize, first: f32) -> Self {
+        Self(
+            (0..len)
+                .step_by(block_len.max(1))
+                .map(|start| {
+                    let end = (start + block_len).min(len);
+                    (
+                        start as i64,
+                        (start..end).map(|i| first + i as f32).collect(),
+                    )
+                })
+                .collect(),
+        )
+    }
+}
+
+impl ServiceTrait for Blocks {}
+
+impl ContentStreamServiceTrait for Blocks {
+    type Item = Audio;
+    type ContentCursor = BlocksCursor;
+    fn duration(&self) -> Timestamp {
+        let end = self
+            .0
+            .iter()
+            .map(|(start, samples)| start + samples.len() as i64)
+            .max()
+            .unwrap_or(0);
+        sample_timestamp(end, RATE)
+    }
+    fn cursor(&self) -> Result<Self::ContentCursor> {
+        Ok(BlocksCursor {
+            blocks: self.0.clone(),
+            position: 0,
+        })
+    }
+}
+
+/// Every block is a keyframe: seeking lands on the start of the block covering the target.
+pub(crate) struct BlocksCursor {
+    blocks: Vec<(i64, Vec<f32>)>,
+    position: usize,
+}
+
+impl ContentCursorTrait for BlocksCursor {
+    type Item = Audio;
+    fn read(&mut self) -> Result<Option<Content<Audio>>> {
+        let Some((start, samples)) = self.blocks.get(self.position).cloned() else {
+            return Ok(None);
+        };
+        self.position += 1;
+        let end = start + samples.len() as i64;
+        let audio = Audio::tyr_new(
+            AudioDataContext::F32(AudioData::from_channels(vec![samples])?),
+            RATE,
+            ChannelLayout::mono(),
+        )?;
+        let offset = sample_timestamp(start, RATE);
+        Ok(Some(Content::new(
+            audio,
+            offset,
+            sample_timestamp(end, RATE) - offset,
+        )))
+    }
+    fn seek(&mut self, timestamp: Timestamp) -> Result<()> {
+        self.seek_with_mode(timestamp, SeekMode::Keyframe)
+    }
+    fn seek_with_mode(&mut self, timestamp: Timestamp, _mode: SeekMode) -> Result<()> {
+        let position = sample_position(timestamp, RATE);
+        self.position = self
+            .blocks
+            .partition_point(|(start, samples)| start + samples.len() as i64 <= position);
+        Ok(())
+    }
+}
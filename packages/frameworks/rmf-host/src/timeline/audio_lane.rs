@@ -0,0 +1,224 @@
+use rmf_core::{
+    Timestamp,
+    audio::{AudioCursorOptions, AudioFormat, ChannelLayout, SampleLayout},
+};
+use rmf_static::{AudioConverter, sample_position};
+
+use crate::{Result, audio::Audio, service::ContentCursorTrait, tracks::AudioTrackContentCursor};
+
+/// Buffers a cursor's decoded audio as `f32` planar samples so it can be cut at arbitrary sample positions.
+/// Audio at another sample rate is resampled, and layouts other than mono are remixed to the lane's channels.
+pub(crate) struct AudioLane<C = AudioTrackContentCursor> {
+    cursor: C,
+    buffer: Vec<Vec<f32>>,
+    sample_rate: u32,
+    converter: Option<AudioConverter>,
+    position: i64,
+    exhausted: bool,
+}
+
+impl<C: ContentCursorTrait<Item = Audio>> AudioLane<C> {
+    pub(crate) fn new(cursor: C, channels: usize, sample_rate: u32) -> Self {
+        Self {
+            cursor,
+            buffer: vec![vec![]; channels],
+            sample_rate,
+            converter: None,
+            position: 0,
+            exhausted: false,
+        }
+    }
+
+    pub(crate) fn seek(&mut self, timestamp: Timestamp) -> Result<()> {
+        self.cursor.seek(timestamp)?;
+        self.buffer.iter_mut().for_each(Vec::clear);
+        if let Some(converter) = &mut self.converter {
+            converter.reset();
+        }
+        self.position = sample_position(timestamp, self.sample_rate);
+        self.exhausted = false;
+        Ok(())
+    }
+
+    /// Returns the samples in `start..end`, with silence wherever the cursor has no audio.
+    pub(crate) fn take(&mut self, start: i64, end: i64) -> Result<Vec<Vec<f32>>> {
+        while !self.exhausted && self.buffered_end() < end {
+            match self.cursor.read()? {
+                Some(content) => {
+                    let start = sample_position(content.offset(), self.sample_rate);
+                    if let Some(audio) = self.convert(content.item())? {
+                        self.push(start, &audio);
+                    }
+                }
+                None => {
+                    if let Some(audio) = self
+                        .converter
+                        .as_mut()
+                        .map(AudioConverter::flush)
+                        .transpose()?
+                        .flatten()
+                    {
+                        self.push(self.buffered_end(), &audio);
+                    }
+                    self.exhausted = true;
+                }
+            }
+        }
+        self.align_to(start);
+        let len = (end - start) as usize;
+        let taken = self
+            .buffer
+            .iter_mut()
+            .map(|line| {
+                let mut taken = line.drain(..len.min(line.len())).collect::<Vec<_>>();
+                taken.resize(len, 0.0);
+                taken
+            })
+            .collect();
+        self.position = end;
+        Ok(taken)
+    }
+
+    /// Converts audio the buffer can't take as is; `None` while the resampler holds every sample back.
+    fn convert(&mut self, audio: &Audio) -> Result<Option<Audio>> {
+        let channels = audio.channel_layout().channels();
+        let remix = channels != 1 && channels != self.buffer.len();
+        if audio.sample_rate() == self.sample_rate && !remix {
+            return Ok(Some(audio.clone()));
+        }
+        let mut options = AudioCursorOptions::default()
+            .with_format(AudioFormat::F32)
+            .with_layout(SampleLayout::Planar)
+            .with_sample_rate(self.sample_rate);
+        if remix {
+            options = options.with_channel_layout(ChannelLayout::default_for(self.buffer.len()));
+        }
+        let converter = match &mut self.converter {
+            Some(converter) if *converter.options() == options => converter,
+            converter => converter.insert(AudioConverter::new(options)),
+        };
+        Ok(converter.convert(audio)?)
+    }
+
+    #[inline]
+    fn buffered_end(&self) -> i64 {
+        self.position + self.buffer.first().map_or(0, Vec::len) as i64
+    }
+
+    fn push(&mut self, start: i64, audio: &Audio) {
+        let lines = audio.to_f32_lines();
+        if lines.is_empty() {
+            return;
+        }
+        let buffered_end = self.buffered_end();
+        if start > buffered_end {
+            let silence = (start - buffered_end) as usize;
+            self.buffer
+                .iter_mut()
+                .for_each(|line| line.resize(line.len() + silence, 0.0));
+        }
+        let skip = (self.buffered_end() - start).max(0) as usize;
+        for (channel, line) in self.buffer.iter_mut().enumerate() {
+            // Mono sources are spread over every output channel.
+            if let Some(source) = lines
+                .get(channel)
+                .or_else(|| lines.first().filter(|_| lines.len() == 1))
+            {
+                line.extend(source.iter().skip(skip));
+            }
+        }
+        let end = self.buffer.iter().map(Vec::len).max().unwrap_or(0);
+        self.buffer
+            .iter_mut()
+            .for_each(|line| line.resize(end, 0.0));
+    }
+
+    /// Moves the buffer start to `start`, dropping older samples or padding with silence.
+    fn align_to(&mut self, start: i64) {
+        if start > self.position {
+            let len = (start - self.position) as usize;
+            self.buffer.iter_mut().for_each(|line| {
+                line.drain(..len.min(line.len()));
+            });
+        } else if start < self.position {
+            let len = (self.position - start) as usize;
+            self.buffer.iter_mut().for_each(|line| {
+                line.splice(0..0, std::iter::repeat_n(0.0, len));
+            });
+        }
+        self.position = start;
+    }
+}
+
+/// Adds `lines` into `mix`, which must have at least as many channels.
+pub(crate) fn accumulate(mix: &mut [Vec<f32>], lines: Vec<Vec<f32>>) {
+    for (mixed, line) in mix.iter_mut().zip(lines) {
+        mixed
+            .iter_mut()
+            .zip(line)
+            .for_each(|(mixed, sample)| *mixed += sample);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        service::ContentStreamServiceTrait,
+        testing::{Blocks, BlocksCursor, RATE},
+    };
+    use pretty_assertions::assert_eq;
+    use rmf_static::sample_timestamp;
+    use rstest::rstest;
+
+    /// Samples 1..=4 at 0, a gap, then 5..=8 at 6.
+    fn lane(channels: usize) -> AudioLane<BlocksCursor> {
+        let blocks = Blocks::new(vec![
+            (0, vec![1.0, 2.0, 3.0, 4.0]),
+            (6, vec![5.0, 6.0, 7.0, 8.0]),
+        ]);
+        AudioLane::new(blocks.cursor().unwrap(), channels, RATE)
+    }
+
+    #[rstest]
+    #[case(vec![(0, 12)], vec![vec![1.0, 2.0, 3.0, 4.0, 0.0, 0.0, 5.0, 6.0, 7.0, 8.0, 0.0, 0.0]])]
+    #[case(vec![(0, 3), (3, 7), (7, 10)], vec![
+        vec![1.0, 2.0, 3.0],
+        vec![4.0, 0.0, 0.0, 5.0],
+        vec![6.0, 7.0, 8.0],
+    ])]
+    #[case(vec![(2, 5), (8, 11)], vec![vec![3.0, 4.0, 0.0], vec![7.0, 8.0, 0.0]])]
+    #[case(vec![(-2, 1)], vec![vec![0.0, 0.0, 1.0]])]
+    #[case(vec![(20, 22)], vec![vec![0.0, 0.0]])]
+    fn take_fills_gaps_with_silence(
+        #[case] ranges: Vec<(i64, i64)>,
+        #[case] expected: Vec<Vec<f32>>,
+    ) {
+        let mut lane = lane(1);
+        let taken = ranges
+            .into_iter()
+            .map(|(start, end)| lane.take(start, end).unwrap().remove(0))
+            .collect::<Vec<_>>();
+        assert_eq!(taken, expected);
+    }
+
+    #[rstest]
+    fn take_spreads_mono_over_every_channel() {
+        let mut lane = lane(2);
+        let expected = vec![1.0, 2.0, 3.0, 4.0, 0.0, 0.0, 5.0];
+        assert_eq!(lane.take(0, 7).unwrap(), vec![expected.clone(), expected]);
+    }
+
+    #[rstest]
+    fn take_after_seek_works() {
+        let mut lane = lane(1);
+        lane.take(0, 8).unwrap();
+        lane.seek(sample_timestamp(2, RATE)).unwrap();
+        assert_eq!(
+            lane.take(2, 8).unwrap(),
+            vec![vec![3.0, 4.0, 0.0, 0.0, 5.0, 6.0]]
+        );
+        lane.seek(sample_timestamp(5, RATE)).unwrap();
+        assert_eq!(lane.take(5, 8).unwrap(), vec![vec![0.0, 5.0, 6.0]]);
+    }
+}
@@ -0,0 +1,5 @@
+pub(crate) mod audio_lane;
+mod timeline_service;
+mod video_lane;
+
+pub use timeline_service::*;
@@ -0,0 +1,410 @@
+use rmf_core::{Content, InnerContent, SeekMode, Size, Timestamp};
+use rmf_static::{AudioData, calculate_samples_to_position};
+
+use crate::{
+    Result,
+    audio::{Audio, AudioDataContext, ChannelLayout},
+    image::Image,
+    service::{ContentCursorTrait, ContentStreamServiceTrait, ServiceTrait},
+    timeline::{
+        audio_lane::{AudioLane, accumulate},
+        video_lane::{VideoLane, composite},
+    },
+    tracks::{AudioTrack, VideoTrack},
+};
+
+/// A track placed on a [`Timeline`] together with its mixing flags.
+/// `z_order` only affects video tracks: higher values are drawn on top.
+#[derive(Clone)]
+pub struct TimelineTrack<T> {
+    track: T,
+    z_order: i32,
+    enabled: bool,
+    muted: bool,
+    solo: bool,
+}
+
+pub type TimelineVideoTrack = TimelineTrack<VideoTrack>;
+pub type TimelineAudioTrack = TimelineTrack<AudioTrack>;
+
+impl<T> TimelineTrack<T> {
+    pub fn new(track: T) -> Self {
+        Self {
+            track,
+            z_order: 0,
+            enabled: true,
+            muted: false,
+            solo: false,
+        }
+    }
+    #[inline]
+    pub fn track(&self) -> &T {
+        &self.track
+    }
+    #[inline]
+    pub fn track_mut(&mut self) -> &mut T {
+        &mut self.track
+    }
+    #[inline]
+    pub fn z_order(&self) -> i32 {
+        self.z_order
+    }
+    #[inline]
+    pub fn set_z_order(&mut self, z_order: i32) {
+        self.z_order = z_order;
+    }
+    #[inline]
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+    #[inline]
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+    #[inline]
+    pub fn muted(&self) -> bool {
+        self.muted
+    }
+    #[inline]
+    pub fn set_muted(&mut self, muted: bool) {
+        self.muted = muted;
+    }
+    #[inline]
+    pub fn solo(&self) -> bool {
+        self.solo
+    }
+    #[inline]
+    pub fn set_solo(&mut self, solo: bool) {
+        self.solo = solo;
+    }
+}
+
+impl<T> From<T> for TimelineTrack<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+/// Picks the tracks that are heard or seen: enabled, not muted, and soloed whenever any track of the same kind is.
+fn active_tracks<T>(tracks: &[TimelineTrack<T>]) -> impl Iterator<Item = &TimelineTrack<T>> {
+    let any_solo = tracks.iter().any(|t| t.enabled && t.solo);
+    tracks
+        .iter()
+        .filter(move |t| t.enabled && !t.muted && (!any_solo || t.solo))
+}
+
+/// The active video tracks bottom to top; tracks with the same `z_order` keep their order.
+fn video_layers<T>(tracks: &[TimelineTrack<T>]) -> Vec<&TimelineTrack<T>> {
+    let mut layers = active_tracks(tracks).collect::<Vec<_>>();
+    layers.sort_by_key(|t| t.z_order);
+    layers
+}
+
+/// When the tick at `position` starts.
+#[inline]
+fn tick_timestamp(fps: f64, position: isize) -> Timestamp {
+    Timestamp::from_seconds_float64(position as f64 / fps)
+}
+
+/// The timeline samples mixed into the tick at `position`; consecutive ticks share their bounds.
+#[inline]
+fn tick_samples(fps: f64, sample_rate: u32, position: isize) -> (i64, i64) {
+    (
+        calculate_samples_to_position(fps, sample_rate, position) as i64,
+        calculate_samples_to_position(fps, sample_rate, position + 1) as i64,
+    )
+}
+
+#[derive(Clone)]
+pub struct TimelineFrame {
+    image: Image,
+    audio: Audio,
+}
+
+impl InnerContent for TimelineFrame {}
+
+impl TimelineFrame {
+    #[inline]
+    pub(crate) fn new(image: Image, audio: Audio) -> Self {
+        Self { image, audio }
+    }
+    #[inline]
+    pub fn image(&self) -> &Image {
+        &self.image
+    }
+    #[inline]
+    pub fn audio(&self) -> &Audio {
+        &self.audio
+    }
+    #[inline]
+    pub fn into_parts(self) -> (Image, Audio) {
+        (self.image, self.audio)
+    }
+}
+
+/// Composes video tracks and mixes audio tracks into one frame and one `f32` planar audio block per tick.
+/// Audio at other sample rates is resampled to the timeline's.
+#[derive(Clone)]
+pub struct Timeline {
+    size: Size,
+    fps: f64,
+    sample_rate: u32,
+    channels: usize,
+    video_tracks: Vec<TimelineVideoTrack>,
+    audio_tracks: Vec<TimelineAudioTrack>,
+}
+
+impl Timeline {
+    pub fn new(size: Size, fps: f64, sample_rate: u32, channels: usize) -> Self {
+        Self {
+            size,
+            fps,
+            sample_rate,
+            channels,
+            video_tracks: vec![],
+            audio_tracks: vec![],
+        }
+    }
+
+    #[inline]
+    pub fn size(&self) -> Size {
+        self.size
+    }
+    #[inline]
+    pub fn fps(&self) -> f64 {
+        self.fps
+    }
+    #[inline]
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+    #[inline]
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+
+    pub fn push_video_track(&mut self, track: impl Into<TimelineVideoTrack>) -> usize {
+        self.video_tracks.push(track.into());
+        self.video_tracks.len() - 1
+    }
+    #[inline]
+    pub fn video_tracks(&self) -> &[TimelineVideoTrack] {
+        &self.video_tracks
+    }
+    #[inline]
+    pub fn video_track_mut(&mut self, index: usize) -> Option<&mut TimelineVideoTrack> {
+        self.video_tracks.get_mut(index)
+    }
+    pub fn remove_video_track(&mut self, index: usize) -> Option<TimelineVideoTrack> {
+        (index < self.video_tracks.len()).then(|| self.video_tracks.remove(index))
+    }
+
+    pub fn push_audio_track(&mut self, track: impl Into<TimelineAudioTrack>) -> usize {
+        self.audio_tracks.push(track.into());
+        self.audio_tracks.len() - 1
+    }
+    #[inline]
+    pub fn audio_tracks(&self) -> &[TimelineAudioTrack] {
+        &self.audio_tracks
+    }
+    #[inline]
+    pub fn audio_track_mut(&mut self, index: usize) -> Option<&mut TimelineAudioTrack> {
+        self.audio_tracks.get_mut(index)
+    }
+    pub fn remove_audio_track(&mut self, index: usize) -> Option<TimelineAudioTrack> {
+        (index < self.audio_tracks.len()).then(|| self.audio_tracks.remove(index))
+    }
+}
+
+impl ServiceTrait for Timeline {}
+
+impl ContentStreamServiceTrait for Timeline {
+    type Item = TimelineFrame;
+    type ContentCursor = TimelineContentCursor;
+    fn duration(&self) -> Timestamp {
+        self.video_tracks
+            .iter()
+            .map(|t| t.track.duration())
+            .chain(self.audio_tracks.iter().map(|t| t.track.duration()))
+            .fold(Timestamp::default(), |max, duration| {
+                if duration > max { duration } else { max }
+            })
+    }
+    fn cursor(&self) -> Result<Self::ContentCursor> {
+        let video_lanes = video_layers(&self.video_tracks)
+            .into_iter()
+            .map(|t| Ok(VideoLane::new(t.track.cursor()?)))
+            .collect::<Result<Vec<_>>>()?;
+        let audio_lanes = active_tracks(&self.audio_tracks)
+            .map(|t| {
+                Ok(AudioLane::new(
+                    t.track.cursor()?,
+                    self.channels,
+                    self.sample_rate,
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(TimelineContentCursor {
+            size: self.size,
+            fps: self.fps,
+            sample_rate: self.sample_rate,
+            channels: self.channels,
+            duration: self.duration(),
+            video_lanes,
+            audio_lanes,
+            position: 0,
+        })
+    }
+}
+
+pub struct TimelineContentCursor {
+    size: Size,
+    fps: f64,
+    sample_rate: u32,
+    channels: usize,
+    duration: Timestamp,
+    video_lanes: Vec<VideoLane>,
+    audio_lanes: Vec<AudioLane>,
+    position: isize,
+}
+
+impl TimelineContentCursor {
+    fn compose_video(&mut self, timestamp: Timestamp) -> Result<Image> {
+        let mut layers = Vec::with_capacity(self.video_lanes.len());
+        for lane in self.video_lanes.iter_mut() {
+            if let Some(image) = lane.frame_at(timestamp)? {
+                layers.push(image);
+            }
+        }
+        composite(self.size, layers.into_iter())
+    }
+
+    fn mix_audio(&mut self) -> Result<Audio> {
+        let (start, end) = tick_samples(self.fps, self.sample_rate, self.position);
+        let mut mix = vec![vec![0.0f32; (end - start).max(0) as usize]; self.channels];
+        for lane in self.audio_lanes.iter_mut() {
+            accumulate(&mut mix, lane.take(start, end)?);
+        }
+        mix.iter_mut()
+            .flatten()
+            .for_each(|sample| *sample = sample.clamp(-1.0, 1.0));
+        Ok(Audio::tyr_new(
+            AudioDataContext::F32(AudioData::from_channels(mix)?),
+            self.sample_rate,
+            ChannelLayout::default_for(self.channels),
+        )?)
+    }
+}
+
+impl ContentCursorTrait for TimelineContentCursor {
+    type Item = TimelineFrame;
+    fn read(&mut self) -> Result<Option<Content<Self::Item>>> {
+        let offset = tick_timestamp(self.fps, self.position);
+        if self.fps <= 0.0 || offset >= self.duration {
+            return Ok(None);
+        }
+        let image = self.compose_video(offset)?;
+        let audio = self.mix_audio()?;
+        let duration = tick_timestamp(self.fps, self.position + 1) - offset;
+        self.position += 1;
+        Ok(Some(Content::new(
+            TimelineFrame::new(image, audio),
+            offset,
+            duration,
+        )))
+    }
+    fn seek(&mut self, timestamp: Timestamp) -> Result<()> {
+        self.seek_with_mode(timestamp, SeekMode::Exact)
+    }
+    /// Always lands on the tick containing `timestamp`: lanes discard whatever precedes it.
+    fn seek_with_mode(&mut self, timestamp: Timestamp, _mode: SeekMode) -> Result<()> {
+        self.position = (timestamp.as_seconds_float64() * self.fps).floor() as isize;
+        let tick = tick_timestamp(self.fps, self.position);
+        for lane in self.video_lanes.iter_mut() {
+            lane.seek(tick)?;
+        }
+        for lane in self.audio_lanes.iter_mut() {
+            lane.seek(tick)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rmf_static::sample_position;
+    use rstest::rstest;
+
+    /// Tracks named after themselves, with `(z_order, enabled, muted, solo)`.
+    fn tracks(flags: &[(&'static str, i32, bool, bool, bool)]) -> Vec<TimelineTrack<&'static str>> {
+        flags
+            .iter()
+            .map(|&(name, z_order, enabled, muted, solo)| {
+                let mut track = TimelineTrack::new(name);
+                track.set_z_order(z_order);
+                track.set_enabled(enabled);
+                track.set_muted(muted);
+                track.set_solo(solo);
+                track
+            })
+            .collect()
+    }
+
+    #[rstest]
+    #[case(vec![("a", 0, true, false, false), ("b", 0, true, false, false)], vec!["a", "b"])]
+    #[case(vec![("a", 2, true, false, false), ("b", 1, true, false, false)], vec!["b", "a"])]
+    #[case(vec![("a", 1, true, false, false), ("b", -1, true, false, false), ("c", 1, true, false, false)], vec!["b", "a", "c"])]
+    #[case(vec![("a", 0, false, false, false), ("b", 0, true, true, false), ("c", 0, true, false, false)], vec!["c"])]
+    #[case(vec![("a", 0, true, false, true), ("b", 0, true, false, false), ("c", 1, true, false, true)], vec!["a", "c"])]
+    #[case(vec![("a", 0, true, true, true), ("b", 0, true, false, false)], vec![])]
+    #[case(vec![("a", 0, false, false, true), ("b", 0, true, false, false)], vec!["b"])]
+    fn video_layers_works(
+        #[case] flags: Vec<(&'static str, i32, bool, bool, bool)>,
+        #[case] expected: Vec<&'static str>,
+    ) {
+        let tracks = tracks(&flags);
+        let layers = video_layers(&tracks)
+            .into_iter()
+            .map(|t| *t.track())
+            .collect::<Vec<_>>();
+        assert_eq!(layers, expected);
+    }
+
+    #[rstest]
+    #[case(30.0, 48_000, 0, (0, 1600))]
+    #[case(30.0, 48_000, 7, (11_200, 12_800))]
+    #[case(25.0, 44_100, 3, (5292, 7056))]
+    #[case(30_000.0 / 1001.0, 48_000, 0, (0, 1602))]
+    #[case(30_000.0 / 1001.0, 48_000, 1, (1602, 3203))]
+    #[case(30_000.0 / 1001.0, 48_000, 4, (6406, 8008))]
+    fn tick_samples_works(
+        #[case] fps: f64,
+        #[case] sample_rate: u32,
+        #[case] position: isize,
+        #[case] expected: (i64, i64),
+    ) {
+        assert_eq!(tick_samples(fps, sample_rate, position), expected);
+    }
+
+    #[rstest]
+    #[case(30.0, 48_000)]
+    #[case(30_000.0 / 1001.0, 48_000)]
+    #[case(24_000.0 / 1001.0, 44_100)]
+    #[case(60.0, 22_050)]
+    fn ticks_align_audio_with_video(#[case] fps: f64, #[case] sample_rate: u32) {
+        let mut end = 0;
+        for position in 0..10_000 {
+            let (start, next) = tick_samples(fps, sample_rate, position);
+            assert_eq!(start, end);
+            assert!(next > start);
+            let video = sample_position(tick_timestamp(fps, position), sample_rate);
+            assert!(
+                (video - start).abs() <= 1,
+                "tick {position}: {video} vs {start}"
+            );
+            end = next;
+        }
+    }
+}
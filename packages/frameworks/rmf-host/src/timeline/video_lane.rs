@@ -0,0 +1,252 @@
+use anyhow::anyhow;
+use rmf_core::{Content, Size, Timestamp, image::PixelFormat};
+
+use crate::{
+    Error, Result, image::Image, service::ContentCursorTrait, tracks::VideoTrackContentCursor,
+};
+
+pub(crate) struct VideoLane<C = VideoTrackContentCursor> {
+    cursor: C,
+    current: Option<Content<Image>>,
+    next: Option<Content<Image>>,
+    exhausted: bool,
+}
+
+impl<C: ContentCursorTrait<Item = Image>> VideoLane<C> {
+    pub(crate) fn new(cursor: C) -> Self {
+        Self {
+            cursor,
+            current: None,
+            next: None,
+            exhausted: false,
+        }
+    }
+
+    pub(crate) fn seek(&mut self, timestamp: Timestamp) -> Result<()> {
+        self.cursor.seek(timestamp)?;
+        self.current = None;
+        self.next = None;
+        self.exhausted = false;
+        Ok(())
+    }
+
+    /// Returns the frame on screen at `timestamp`. Timestamps must not go backwards between seeks.
+    /// Fails on frames that aren't RGBA, the only format [`composite`] can draw.
+    pub(crate) fn frame_at(&mut self, timestamp: Timestamp) -> Result<Option<&Image>> {
+        loop {
+            if self.next.is_none() && !self.exhausted {
+                self.next = self.cursor.read()?;
+                self.exhausted = self.next.is_none();
+                if let Some(next) = &self.next
+                    && next.item().pixel_format() != PixelFormat::Rgba
+                {
+                    return Err(Error::new_track(anyhow!(
+                        "track delivered a {:?} frame at {}, timeline tracks must deliver RGBA",
+                        next.item().pixel_format(),
+                        next.offset()
+                    )));
+                }
+            }
+            match &self.next {
+                Some(next) if next.offset() <= timestamp => self.current = self.next.take(),
+                _ => break,
+            }
+        }
+        Ok(self
+            .current
+            .as_ref()
+            .filter(|c| {
+                c.duration() == Timestamp::default() || timestamp < c.offset() + c.duration()
+            })
+            .map(|c| c.item()))
+    }
+}
+
+/// Draws `layers` bottom to top, each centered on a transparent canvas of `size`.
+pub(crate) fn composite<'a>(size: Size, layers: impl Iterator<Item = &'a Image>) -> Result<Image> {
+    let (width, height) = (size.width as i64, size.height as i64);
+    let mut canvas = vec![0u8; (width * height * 4) as usize];
+    for layer in layers {
+        if layer.pixel_format() != PixelFormat::Rgba {
+            return Err(Error::new_track(anyhow!(
+                "can't composite {:?} frames, timeline tracks must deliver RGBA",
+                layer.pixel_format()
+            )));
+        }
+        let layer_size = layer.size();
+        let (layer_width, layer_height) = (layer_size.width as i64, layer_size.height as i64);
+        let (Some(data), Some(stride)) = (layer.plane(0), layer.stride(0)) else {
+            return Err(Error::new_track(anyhow!("frame has no pixel plane")));
+        };
+        let left = (width - layer_width) / 2;
+        let top = (height - layer_height) / 2;
+        for y in top.max(0)..(top + layer_height).min(height) {
+            for x in left.max(0)..(left + layer_width).min(width) {
+                let src = (y - top) as usize * stride + ((x - left) * 4) as usize;
+                let dst = ((y * width + x) * 4) as usize;
+                blend_pixel(&mut canvas[dst..dst + 4], &data[src..src + 4]);
+            }
+        }
+    }
+    Ok(Image::new_size(size, &canvas)?)
+}
+
+/// Porter-Duff "source over" on straight (non-premultiplied) RGBA.
+#[inline]
+fn blend_pixel(dst: &mut [u8], src: &[u8]) {
+    let src_alpha = src[3] as u32;
+    if src_alpha == 255 {
+        dst.copy_from_slice(src);
+        return;
+    }
+    let dst_alpha = dst[3] as u32 * (255 - src_alpha) / 255;
+    let out_alpha = src_alpha + dst_alpha;
+    if out_alpha == 0 {
+        dst.fill(0);
+        return;
+    }
+    for i in 0..3 {
+        dst[i] = ((src[i] as u32 * src_alpha + dst[i] as u32 * dst_alpha) / out_alpha) as u8;
+    }
+    dst[3] = out_alpha as u8;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rmf_core::image::ImagePlane;
+    use rstest::rstest;
+
+    /// Plays `frames` in order; seeking skips the ones that end before the target.
+    struct Frames {
+        frames: Vec<Content<Image>>,
+        position: usize,
+    }
+
+    impl ContentCursorTrait for Frames {
+        type Item = Image;
+        fn read(&mut self) -> Result<Option<Content<Image>>> {
+            let frame = self
+                .frames
+                .get(self.position)
+                .map(|frame| Content::new(frame.item().clone(), frame.offset(), frame.duration()));
+            self.position += 1;
+            Ok(frame)
+        }
+        fn seek(&mut self, timestamp: Timestamp) -> Result<()> {
+            self.seek_with_mode(timestamp, rmf_core::SeekMode::Exact)
+        }
+        fn seek_with_mode(
+            &mut self,
+            timestamp: Timestamp,
+            _mode: rmf_core::SeekMode,
+        ) -> Result<()> {
+            self.position = self
+                .frames
+                .partition_point(|frame| frame.offset() + frame.duration() <= timestamp);
+            Ok(())
+        }
+    }
+
+    fn ms(milliseconds: i64) -> Timestamp {
+        Timestamp::from_milliseconds(milliseconds)
+    }
+
+    fn solid(size: Size, pixel: [u8; 4]) -> Image {
+        Image::new_size(size, &pixel.repeat((size.width * size.height) as usize)).unwrap()
+    }
+
+    /// 100 ms frames whose red channel is their index, with a gap from 200 ms to 300 ms.
+    fn lane() -> VideoLane<Frames> {
+        let frames = [0, 100, 300]
+            .into_iter()
+            .enumerate()
+            .map(|(i, offset)| {
+                Content::new(
+                    solid(Size::new(1, 1), [i as u8, 0, 0, 255]),
+                    ms(offset),
+                    ms(100),
+                )
+            })
+            .collect();
+        VideoLane::new(Frames {
+            frames,
+            position: 0,
+        })
+    }
+
+    fn red(image: Option<&Image>) -> Option<u8> {
+        image.map(|image| image.plane(0).unwrap()[0])
+    }
+
+    #[rstest]
+    #[case(vec![0, 50, 99], vec![Some(0), Some(0), Some(0)])]
+    #[case(vec![0, 100, 199], vec![Some(0), Some(1), Some(1)])]
+    #[case(vec![150, 200, 250, 300], vec![Some(1), None, None, Some(2)])]
+    #[case(vec![250, 399, 400, 1000], vec![None, Some(2), None, None])]
+    fn frame_at_leaves_gaps_empty(#[case] timestamps: Vec<i64>, #[case] expected: Vec<Option<u8>>) {
+        let mut lane = lane();
+        let frames = timestamps
+            .into_iter()
+            .map(|t| red(lane.frame_at(ms(t)).unwrap()))
+            .collect::<Vec<_>>();
+        assert_eq!(frames, expected);
+    }
+
+    #[rstest]
+    fn frame_at_after_seek_works() {
+        let mut lane = lane();
+        assert_eq!(red(lane.frame_at(ms(350)).unwrap()), Some(2));
+        lane.seek(ms(120)).unwrap();
+        assert_eq!(red(lane.frame_at(ms(120)).unwrap()), Some(1));
+        lane.seek(ms(250)).unwrap();
+        assert_eq!(red(lane.frame_at(ms(250)).unwrap()), None);
+        assert_eq!(red(lane.frame_at(ms(300)).unwrap()), Some(2));
+    }
+
+    #[rstest]
+    fn frame_at_rejects_other_pixel_formats() {
+        let size = Size::new(2, 2);
+        let gray = Image::new_planes(
+            PixelFormat::Gray8,
+            size,
+            vec![ImagePlane::new(vec![0; 4], 2)],
+        )
+        .unwrap();
+        let mut lane = VideoLane::new(Frames {
+            frames: vec![Content::new(gray, ms(0), ms(100))],
+            position: 0,
+        });
+        assert!(lane.frame_at(ms(0)).is_err());
+    }
+
+    #[rstest]
+    #[case([10, 20, 30, 255], [40, 50, 60, 255], [40, 50, 60, 255])]
+    #[case([10, 20, 30, 255], [40, 50, 60, 0], [10, 20, 30, 255])]
+    #[case([0, 0, 0, 255], [255, 255, 255, 51], [51, 51, 51, 255])]
+    #[case([0, 0, 0, 0], [200, 100, 50, 128], [200, 100, 50, 128])]
+    fn composite_draws_later_layers_on_top(
+        #[case] bottom: [u8; 4],
+        #[case] top: [u8; 4],
+        #[case] expected: [u8; 4],
+    ) {
+        let size = Size::new(1, 1);
+        let layers = [solid(size, bottom), solid(size, top)];
+        let image = composite(size, layers.iter()).unwrap();
+        assert_eq!(image.plane(0).unwrap()[..4], expected);
+    }
+
+    #[rstest]
+    fn composite_centers_smaller_layers() {
+        let layers = [solid(Size::new(1, 1), [255, 0, 0, 255])];
+        let image = composite(Size::new(3, 3), layers.iter()).unwrap();
+        let alpha = image
+            .plane(0)
+            .unwrap()
+            .chunks(4)
+            .map(|pixel| pixel[3])
+            .collect::<Vec<_>>();
+        assert_eq!(alpha, vec![0, 0, 0, 0, 255, 0, 0, 0, 0]);
+    }
+}
@@ -0,0 +1,9 @@
+use crate::context::ContextAudioContentStreamService;
+use crate::service::AudioContentStreamServiceTrait;
+use crate::tracks::{ContentRange, Track, TrackContentCursor};
+
+pub type AudioContentRange = ContentRange<ContextAudioContentStreamService>;
+pub type AudioTrack = Track<ContextAudioContentStreamService>;
+pub type AudioTrackContentCursor = TrackContentCursor<ContextAudioContentStreamService>;
+
+impl AudioContentStreamServiceTrait for AudioTrack {}
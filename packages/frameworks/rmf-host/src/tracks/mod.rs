@@ -1,3 +1,7 @@
+mod audio_tracks;
+mod track;
 mod video_tracks;
 
+pub use audio_tracks::*;
+pub use track::*;
 pub use video_tracks::*;
@@ -0,0 +1,664 @@
+use std::collections::VecDeque;
+
+use anyhow::anyhow;
+use rmf_core::*;
+
+use crate::service::{ContentCursorTrait, ContentStreamServiceTrait, ServiceTrait};
+use crate::{Error, Result};
+
+#[derive(Clone)]
+pub struct ContentRange<S> {
+    content: S,
+    offset: Timestamp,
+    in_point: Timestamp,
+    out_point: Timestamp,
+}
+
+impl<S: ContentStreamServiceTrait> ContentRange<S> {
+    pub fn new(content: impl Into<S>, offset: Timestamp) -> Self {
+        let content = content.into();
+        let out_point = content.duration();
+        Self {
+            content,
+            offset,
+            in_point: Timestamp::default(),
+            out_point,
+        }
+    }
+
+    /// Uses only the part of `content` between `in_point` and `out_point`, measured on the source's own timeline.
+    pub fn try_new_trimmed(
+        content: impl Into<S>,
+        offset: Timestamp,
+        in_point: Timestamp,
+        out_point: Timestamp,
+    ) -> Result<Self> {
+        Self::new(content, offset).trimmed(in_point, out_point)
+    }
+
+    pub fn trimmed(mut self, in_point: Timestamp, out_point: Timestamp) -> Result<Self> {
+        if in_point < Timestamp::default()
+            || out_point <= in_point
+            || out_point > self.content.duration()
+        {
+            Err(Error::new_track(anyhow!(
+                "invalid trim from {in_point} to {out_point} on a source of {}",
+                self.content.duration()
+            )))
+        } else {
+            self.in_point = in_point;
+            self.out_point = out_point;
+            Ok(self)
+        }
+    }
+
+    #[inline]
+    pub fn content(&self) -> &S {
+        &self.content
+    }
+    #[inline]
+    pub fn offset(&self) -> Timestamp {
+        self.offset
+    }
+    #[inline]
+    pub fn in_point(&self) -> Timestamp {
+        self.in_point
+    }
+    #[inline]
+    pub fn out_point(&self) -> Timestamp {
+        self.out_point
+    }
+    #[inline]
+    pub fn duration(&self) -> Timestamp {
+        self.out_point - self.in_point
+    }
+    #[inline]
+    pub fn end(&self) -> Timestamp {
+        self.offset() + self.duration()
+    }
+    /// Where the clip sits on the track.
+    #[inline]
+    pub fn range(&self) -> TimeRange {
+        TimeRange::new(self.offset(), self.end())
+    }
+    /// The used part of the source, on the source's own timeline.
+    #[inline]
+    pub fn source_range(&self) -> TimeRange {
+        TimeRange::new(self.in_point, self.out_point)
+    }
+
+    fn source_cursor(&self, position: Timestamp, mode: SeekMode) -> Result<S::ContentCursor> {
+        let mut cursor = self.content.cursor()?;
+        if position > Timestamp::default() {
+            cursor.seek_with_mode(position, mode)?;
+        }
+        Ok(cursor)
+    }
+}
+
+#[derive(Clone)]
+pub struct Track<S> {
+    sequence: VecDeque<ContentRange<S>>,
+}
+
+impl<S> Default for Track<S> {
+    fn default() -> Self {
+        Self {
+            sequence: VecDeque::default(),
+        }
+    }
+}
+
+impl<S: ContentStreamServiceTrait + Clone> Track<S> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.sequence.len()
+    }
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.sequence.is_empty()
+    }
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<&ContentRange<S>> {
+        self.sequence.get(index)
+    }
+    #[inline]
+    pub fn ranges(&self) -> impl Iterator<Item = &ContentRange<S>> {
+        self.sequence.iter()
+    }
+
+    /// Places the content right after the last clip and returns its index.
+    pub fn append(&mut self, content: impl Into<S>) -> usize {
+        let range = ContentRange::new(content, self.duration());
+        self.sequence.push_back(range);
+        self.sequence.len() - 1
+    }
+
+    /// Places the content at `offset` and returns its index.
+    /// Fails without changing the track when the clip would overlap another one.
+    pub fn insert(&mut self, offset: Timestamp, content: impl Into<S>) -> Result<usize> {
+        self.insert_range(ContentRange::new(content, offset))
+    }
+
+    /// Places an already built (e.g. trimmed) clip and returns its index.
+    pub fn insert_range(&mut self, range: ContentRange<S>) -> Result<usize> {
+        let index = self.insertion_index(&range)?;
+        self.sequence.insert(index, range);
+        Ok(index)
+    }
+
+    pub fn remove(&mut self, index: usize) -> Option<ContentRange<S>> {
+        self.sequence.remove(index)
+    }
+
+    /// Moves the clip at `index` to `offset` and returns its new index.
+    pub fn move_to(&mut self, index: usize, offset: Timestamp) -> Result<usize> {
+        let previous = self.remove(index).ok_or_else(|| out_of_range(index))?;
+        let range = ContentRange {
+            offset,
+            ..previous.clone()
+        };
+        self.exchange(index, previous, range)
+            .map(|(new_index, _)| new_index)
+    }
+
+    /// Changes the in and out points of the clip at `index`, keeping its offset.
+    pub fn trim(&mut self, index: usize, in_point: Timestamp, out_point: Timestamp) -> Result<()> {
+        let previous = self.remove(index).ok_or_else(|| out_of_range(index))?;
+        match previous.clone().trimmed(in_point, out_point) {
+            Ok(range) => self.exchange(index, previous, range).map(|_| ()),
+            Err(e) => {
+                self.sequence.insert(index, previous);
+                Err(e)
+            }
+        }
+    }
+
+    /// Swaps the content of the clip at `index`, keeping its offset, and returns the previous clip.
+    /// The new content is used untrimmed.
+    pub fn replace(&mut self, index: usize, content: impl Into<S>) -> Result<ContentRange<S>> {
+        let previous = self.remove(index).ok_or_else(|| out_of_range(index))?;
+        let range = ContentRange::new(content, previous.offset());
+        self.exchange(index, previous, range)
+            .map(|(_, previous)| previous)
+    }
+
+    /// Returns the empty spans between clips, including one before the first clip.
+    pub fn gaps(&self) -> Vec<TimeRange> {
+        let mut gaps = vec![];
+        let mut end = Timestamp::default();
+        for range in self.sequence.iter() {
+            let gap = TimeRange::new(end, range.offset());
+            if !gap.is_empty() {
+                gaps.push(gap);
+            }
+            end = range.end();
+        }
+        gaps
+    }
+
+    /// Fails when the track has a gap, for consumers that need gapless playback.
+    pub fn validate_contiguous(&self) -> Result<()> {
+        if let Some(gap) = self.gaps().first() {
+            Err(Error::new_track(anyhow!(
+                "gap between {} and {}",
+                gap.start(),
+                gap.end()
+            )))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Inserts `range` in place of the already removed `previous`, putting `previous` back on failure.
+    fn exchange(
+        &mut self,
+        index: usize,
+        previous: ContentRange<S>,
+        range: ContentRange<S>,
+    ) -> Result<(usize, ContentRange<S>)> {
+        match self.insertion_index(&range) {
+            Ok(new_index) => {
+                self.sequence.insert(new_index, range);
+                Ok((new_index, previous))
+            }
+            Err(e) => {
+                self.sequence.insert(index, previous);
+                Err(e)
+            }
+        }
+    }
+
+    fn insertion_index(&self, range: &ContentRange<S>) -> Result<usize> {
+        if range.offset() < Timestamp::default() {
+            return Err(Error::new_track(anyhow!(
+                "negative offset {}",
+                range.offset()
+            )));
+        }
+        let index = self
+            .sequence
+            .partition_point(|r| r.offset() <= range.offset());
+        let overlapped = index
+            .checked_sub(1)
+            .and_then(|i| self.sequence.get(i))
+            .filter(|previous| clashes(&previous.range(), &range.range()))
+            .or_else(|| {
+                self.sequence
+                    .get(index)
+                    .filter(|next| clashes(&next.range(), &range.range()))
+            });
+        if let Some(overlapped) = overlapped {
+            Err(Error::new_track(anyhow!(
+                "clip at {} overlaps clip from {} to {}",
+                range.offset(),
+                overlapped.offset(),
+                overlapped.end()
+            )))
+        } else {
+            Ok(index)
+        }
+    }
+}
+
+/// Whether two clips can't share a track: they overlap, or one is empty and sits inside the other.
+#[inline]
+fn clashes(a: &TimeRange, b: &TimeRange) -> bool {
+    a.overlaps(b)
+        || (a.is_empty() && b.contains(a.start()))
+        || (b.is_empty() && a.contains(b.start()))
+}
+
+#[inline]
+fn out_of_range(index: usize) -> Error {
+    Error::new_track(anyhow!("no clip at index {index}"))
+}
+
+pub struct TrackContentCursor<S: ContentStreamServiceTrait> {
+    sequence: VecDeque<ContentRange<S>>,
+    position: usize,
+    current: Option<S::ContentCursor>,
+}
+
+impl<S: ContentStreamServiceTrait> TrackContentCursor<S> {
+    fn new(sequence: VecDeque<ContentRange<S>>) -> Self {
+        Self {
+            sequence,
+            position: 0,
+            current: None,
+        }
+    }
+
+    #[inline]
+    fn advance(&mut self) {
+        self.position += 1;
+        self.current = None;
+    }
+}
+
+impl<S: ContentStreamServiceTrait> ContentCursorTrait for TrackContentCursor<S> {
+    type Item = S::Item;
+    fn read(&mut self) -> Result<Option<Content<Self::Item>>> {
+        while let Some(range) = self.sequence.get(self.position) {
+            let cursor = match &mut self.current {
+                Some(cursor) => cursor,
+                current => {
+                    current.insert(range.source_cursor(range.in_point(), SeekMode::Keyframe)?)
+                }
+            };
+            match cursor.read()? {
+                // Seeking lands on the keyframe before the in point.
+                Some(content) if content.offset() + content.duration() <= range.in_point() => {}
+                Some(content) if content.offset() < range.out_point() => {
+                    let used = content.cut(range.source_range())?;
+                    let offset = range.offset() + used.offset() - range.in_point();
+                    let duration = used.duration();
+                    return Ok(Some(Content::new(used.into_item(), offset, duration)));
+                }
+                _ => self.advance(),
+            }
+        }
+        Ok(None)
+    }
+    fn seek(&mut self, timestamp: Timestamp) -> Result<()> {
+        self.seek_with_mode(timestamp, SeekMode::Keyframe)
+    }
+    fn seek_with_mode(&mut self, timestamp: Timestamp, mode: SeekMode) -> Result<()> {
+        self.current = None;
+        self.position = self
+            .sequence
+            .iter()
+            .position(|range| timestamp < range.end())
+            .unwrap_or(self.sequence.len());
+        if let Some(range) = self.sequence.get(self.position)
+            && timestamp > range.offset()
+        {
+            self.current =
+                Some(range.source_cursor(range.in_point() + timestamp - range.offset(), mode)?);
+        }
+        Ok(())
+    }
+}
+
+impl<S> ServiceTrait for Track<S> {}
+
+impl<S: ContentStreamServiceTrait + Clone> ContentStreamServiceTrait for Track<S> {
+    type Item = S::Item;
+    type ContentCursor = TrackContentCursor<S>;
+    fn duration(&self) -> Timestamp {
+        if let Some(c) = self.sequence.iter().last() {
+            c.end()
+        } else {
+            Timestamp::default()
+        }
+    }
+    fn cursor(&self) -> Result<Self::ContentCursor> {
+        Ok(TrackContentCursor::new(self.sequence.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Blocks;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    /// Mono audio at 100 Hz in blocks of 10 samples, where every sample holds its own index.
+    fn clip(len: usize) -> Blocks {
+        Blocks::ramp(len, 10, 0.0)
+    }
+
+    fn ms(milliseconds: i64) -> Timestamp {
+        Timestamp::from_milliseconds(milliseconds)
+    }
+
+    /// Every block read as `(offset, duration, samples)`, in milliseconds.
+    fn read_all(track: &Track<Blocks>) -> Vec<(i64, i64, Vec<f32>)> {
+        let mut cursor = track.cursor().unwrap();
+        let mut blocks = vec![];
+        while let Some(content) = cursor.read().unwrap() {
+            blocks.push((
+                content.offset().as_milliseconds(),
+                content.duration().as_milliseconds(),
+                content.item().to_f32_lines().remove(0),
+            ));
+        }
+        blocks
+    }
+
+    fn samples(range: std::ops::Range<usize>) -> Vec<f32> {
+        range.map(|i| i as f32).collect()
+    }
+
+    #[rstest]
+    #[case(0, 300, vec![
+        (1000, 100, samples(0..10)),
+        (1100, 100, samples(10..20)),
+        (1200, 100, samples(20..30)),
+    ])]
+    #[case(120, 170, vec![(1000, 50, samples(12..17))])]
+    #[case(150, 420, vec![
+        (1000, 50, samples(15..20)),
+        (1050, 100, samples(20..30)),
+        (1150, 100, samples(30..40)),
+        (1250, 20, samples(40..42)),
+    ])]
+    #[case(900, 1000, vec![(1000, 100, samples(90..100))])]
+    fn read_cuts_audio_to_trim(
+        #[case] in_point: i64,
+        #[case] out_point: i64,
+        #[case] expected: Vec<(i64, i64, Vec<f32>)>,
+    ) {
+        let mut track = Track::new();
+        track
+            .insert_range(
+                ContentRange::try_new_trimmed(clip(100), ms(1000), ms(in_point), ms(out_point))
+                    .unwrap(),
+            )
+            .unwrap();
+        assert_eq!(read_all(&track), expected);
+    }
+
+    #[rstest]
+    fn read_cuts_audio_of_adjacent_clips() {
+        let mut track = Track::new();
+        for (offset, in_point, out_point) in [(0, 50, 250), (200, 970, 1000)] {
+            track
+                .insert_range(
+                    ContentRange::try_new_trimmed(
+                        clip(100),
+                        ms(offset),
+                        ms(in_point),
+                        ms(out_point),
+                    )
+                    .unwrap(),
+                )
+                .unwrap();
+        }
+        assert_eq!(
+            read_all(&track),
+            vec![
+                (0, 50, samples(5..10)),
+                (50, 100, samples(10..20)),
+                (150, 50, samples(20..25)),
+                (200, 30, samples(97..100)),
+            ]
+        );
+    }
+
+    /// Clips as `(offset, end)` in milliseconds.
+    fn spans(track: &Track<Blocks>) -> Vec<(i64, i64)> {
+        track
+            .ranges()
+            .map(|range| {
+                (
+                    range.offset().as_milliseconds(),
+                    range.end().as_milliseconds(),
+                )
+            })
+            .collect()
+    }
+
+    /// Clips of 100 ms at 0 ms and 200 ms.
+    fn two_clips() -> Track<Blocks> {
+        let mut track = Track::new();
+        track.insert(ms(0), clip(10)).unwrap();
+        track.insert(ms(200), clip(10)).unwrap();
+        track
+    }
+
+    #[rstest]
+    #[case(100, 10, Some(1))]
+    #[case(300, 5, Some(2))]
+    #[case(0, 1, None)]
+    #[case(50, 10, None)]
+    #[case(150, 10, None)]
+    #[case(100, 11, None)]
+    #[case(-10, 1, None)]
+    #[case(100, 0, Some(1))]
+    #[case(300, 0, Some(2))]
+    #[case(0, 0, None)]
+    #[case(50, 0, None)]
+    fn insert_rejects_overlaps(
+        #[case] offset: i64,
+        #[case] len: usize,
+        #[case] expected: Option<usize>,
+    ) {
+        let mut track = two_clips();
+        let inserted = track.insert(ms(offset), clip(len)).ok();
+        assert_eq!(inserted, expected);
+        if inserted.is_none() {
+            assert_eq!(spans(&track), vec![(0, 100), (200, 300)]);
+        }
+    }
+
+    #[rstest]
+    #[case(0, None)]
+    #[case(50, None)]
+    #[case(40, None)]
+    #[case(60, Some(1))]
+    #[case(-50, None)]
+    fn insert_rejects_clips_around_empty_ones(
+        #[case] offset: i64,
+        #[case] expected: Option<usize>,
+    ) {
+        let mut track = Track::<Blocks>::new();
+        track.insert(ms(50), clip(0)).unwrap();
+        assert_eq!(track.insert(ms(offset), clip(10)).ok(), expected);
+    }
+
+    #[rstest]
+    fn append_places_after_last_clip() {
+        let mut track = two_clips();
+        assert_eq!(track.append(clip(5)), 2);
+        assert_eq!(spans(&track), vec![(0, 100), (200, 300), (300, 350)]);
+        assert_eq!(track.duration(), ms(350));
+    }
+
+    #[rstest]
+    #[case(0, 400, Some(1), vec![(200, 300), (400, 500)])]
+    #[case(1, 100, Some(1), vec![(0, 100), (100, 200)])]
+    #[case(1, 0, None, vec![(0, 100), (200, 300)])]
+    #[case(0, 150, None, vec![(0, 100), (200, 300)])]
+    #[case(0, -1, None, vec![(0, 100), (200, 300)])]
+    #[case(2, 400, None, vec![(0, 100), (200, 300)])]
+    fn move_to_rolls_back_on_failure(
+        #[case] index: usize,
+        #[case] offset: i64,
+        #[case] expected: Option<usize>,
+        #[case] expected_spans: Vec<(i64, i64)>,
+    ) {
+        let mut track = two_clips();
+        assert_eq!(track.move_to(index, ms(offset)).ok(), expected);
+        assert_eq!(spans(&track), expected_spans);
+    }
+
+    #[rstest]
+    #[case(0, 20, true, vec![(0, 200), (200, 300)])]
+    #[case(0, 21, false, vec![(0, 100), (200, 300)])]
+    #[case(0, 5, true, vec![(0, 50), (200, 300)])]
+    #[case(1, 30, true, vec![(0, 100), (200, 500)])]
+    #[case(2, 5, false, vec![(0, 100), (200, 300)])]
+    fn replace_keeps_offset_and_rolls_back_on_failure(
+        #[case] index: usize,
+        #[case] len: usize,
+        #[case] replaced: bool,
+        #[case] expected_spans: Vec<(i64, i64)>,
+    ) {
+        let mut track = two_clips();
+        assert_eq!(track.replace(index, clip(len)).is_ok(), replaced);
+        assert_eq!(spans(&track), expected_spans);
+    }
+
+    #[rstest]
+    fn remove_works() {
+        let mut track = two_clips();
+        assert_eq!(track.remove(0).map(|range| range.offset()), Some(ms(0)));
+        assert_eq!(spans(&track), vec![(200, 300)]);
+        assert!(track.remove(1).is_none());
+    }
+
+    #[rstest]
+    fn gaps_include_the_start() {
+        let mut track = two_clips();
+        assert_eq!(track.gaps(), vec![TimeRange::new(ms(100), ms(200))]);
+        assert!(track.validate_contiguous().is_err());
+        track.move_to(1, ms(100)).unwrap();
+        assert!(track.gaps().is_empty());
+        assert!(track.validate_contiguous().is_ok());
+        track.move_to(0, ms(300)).unwrap();
+        assert_eq!(
+            track.gaps(),
+            vec![
+                TimeRange::new(ms(0), ms(100)),
+                TimeRange::new(ms(200), ms(300))
+            ]
+        );
+    }
+
+    #[rstest]
+    #[case(0, 100, Some((0, 100)))]
+    #[case(20, 70, Some((20, 70)))]
+    #[case(99, 100, Some((99, 100)))]
+    #[case(-1, 50, None)]
+    #[case(50, 50, None)]
+    #[case(60, 40, None)]
+    #[case(0, 101, None)]
+    fn trimmed_works(
+        #[case] in_point: i64,
+        #[case] out_point: i64,
+        #[case] expected: Option<(i64, i64)>,
+    ) {
+        let range =
+            ContentRange::<Blocks>::try_new_trimmed(clip(10), ms(300), ms(in_point), ms(out_point));
+        assert_eq!(
+            range.ok().map(|range| (
+                range.in_point().as_milliseconds(),
+                range.out_point().as_milliseconds()
+            )),
+            expected
+        );
+    }
+
+    #[rstest]
+    fn trimmed_range_is_placed_at_offset() {
+        let range =
+            ContentRange::<Blocks>::try_new_trimmed(clip(10), ms(300), ms(20), ms(70)).unwrap();
+        assert_eq!(range.duration(), ms(50));
+        assert_eq!(range.end(), ms(350));
+        assert_eq!(range.range(), TimeRange::new(ms(300), ms(350)));
+        assert_eq!(range.source_range(), TimeRange::new(ms(20), ms(70)));
+    }
+
+    #[rstest]
+    #[case(0, 0, 50, true, vec![(0, 50), (200, 300)])]
+    #[case(0, 30, 100, true, vec![(0, 70), (200, 300)])]
+    #[case(1, 0, 30, true, vec![(0, 100), (200, 230)])]
+    #[case(0, 50, 40, false, vec![(0, 100), (200, 300)])]
+    #[case(0, 0, 110, false, vec![(0, 100), (200, 300)])]
+    #[case(2, 0, 50, false, vec![(0, 100), (200, 300)])]
+    fn trim_keeps_offset_and_rolls_back_on_failure(
+        #[case] index: usize,
+        #[case] in_point: i64,
+        #[case] out_point: i64,
+        #[case] trimmed: bool,
+        #[case] expected_spans: Vec<(i64, i64)>,
+    ) {
+        let mut track = two_clips();
+        assert_eq!(
+            track.trim(index, ms(in_point), ms(out_point)).is_ok(),
+            trimmed
+        );
+        assert_eq!(spans(&track), expected_spans);
+    }
+
+    #[rstest]
+    fn trim_can_be_widened_again() {
+        let mut track = two_clips();
+        track.trim(0, ms(30), ms(60)).unwrap();
+        track.trim(0, ms(0), ms(100)).unwrap();
+        assert_eq!(spans(&track), vec![(0, 100), (200, 300)]);
+        assert_eq!(
+            track.get(0).unwrap().source_range(),
+            TimeRange::new(ms(0), ms(100))
+        );
+    }
+
+    #[rstest]
+    fn trim_rolls_back_when_widening_overlaps() {
+        let mut track = two_clips();
+        track.trim(0, ms(0), ms(50)).unwrap();
+        track.insert(ms(60), clip(3)).unwrap();
+        assert!(track.trim(0, ms(0), ms(100)).is_err());
+        assert_eq!(spans(&track), vec![(0, 50), (60, 90), (200, 300)]);
+        assert_eq!(
+            track.get(0).unwrap().source_range(),
+            TimeRange::new(ms(0), ms(50))
+        );
+    }
+}
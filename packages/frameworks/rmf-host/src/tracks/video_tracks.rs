@@ -1,67 +1,18 @@
-use std::collections::VecDeque;
-
-use rmf_core::*;
-
 use crate::context::ContextVideoContentStreamService;
-use crate::image::Image;
-
-use crate::service::{
-    ContentCursorTrait, ContentStreamServiceTrait, ServiceTrait, VideoContentStreamServiceTrait,
-};
-
-#[derive(Clone)]
-struct ContentRange {
-    content: ContextVideoContentStreamService,
-    offset: Timestamp,
-}
-
-impl ContentRange {
-    #[inline]
-    fn offset(&self) -> Timestamp {
-        self.offset
-    }
-    #[inline]
-    fn duration(&self) -> Timestamp {
-        self.content.duration()
-    }
-}
-
-#[derive(Clone)]
-pub struct VideoTrack {
-    sequence: VecDeque<ContentRange>,
-}
-
-pub struct VideoTrackContentCursor {}
-
-impl ContentCursorTrait for VideoTrackContentCursor {
-    type Item = Image;
-    fn read(&mut self) -> crate::Result<Option<Content<Self::Item>>> {
-        unimplemented!()
-    }
-    fn seek(&mut self, _timestamp: Timestamp) -> crate::Result<()> {
-        unimplemented!()
-    }
-}
-
-impl ServiceTrait for VideoTrack {}
+use crate::service::VideoContentStreamServiceTrait;
+use crate::tracks::{ContentRange, Track, TrackContentCursor};
 
-impl ContentStreamServiceTrait for VideoTrack {
-    type Item = Image;
-    type ContentCursor = VideoTrackContentCursor;
-    fn duration(&self) -> Timestamp {
-        if let Some(c) = self.sequence.iter().last() {
-            c.offset() + c.duration()
-        } else {
-            Timestamp::default()
-        }
-    }
-    fn cursor(&self) -> crate::Result<Self::ContentCursor> {
-        unimplemented!()
-    }
-}
+pub type VideoContentRange = ContentRange<ContextVideoContentStreamService>;
+pub type VideoTrack = Track<ContextVideoContentStreamService>;
+pub type VideoTrackContentCursor = TrackContentCursor<ContextVideoContentStreamService>;
 
 impl VideoContentStreamServiceTrait for VideoTrack {
-    fn fps(&self) -> f64 {
-        unimplemented!()
+    /// The highest frame rate among the clips, or `None` while no clip has one.
+    /// Clips keep their own frame timing, so stepping a mixed-rate track at this rate never skips a frame;
+    /// frames of slower clips are returned once and should be held until the next one.
+    fn fps(&self) -> Option<f64> {
+        self.ranges()
+            .filter_map(|range| range.content().fps())
+            .reduce(f64::max)
     }
 }
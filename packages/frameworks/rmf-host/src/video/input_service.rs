@@ -1,7 +1,8 @@
 use crate::image::Image;
-use rmf_core::InputSource;
 use rmf_core::video::VideoContentCursor as _;
+use rmf_core::video::VideoCursorOptions;
 use rmf_core::video::VideoInput as _;
+use rmf_core::{InputSource, StreamInfo};
 use rmf_static::video::DefaultVideoContentCursor;
 use rmf_static::video::DefaultVideoInput;
 use rmf_static::video::DefaultVideoInputProvider;
@@ -33,6 +34,19 @@ impl rmf_core::video::VideoContentCursor for ContextVideoContentCursor {
         }
     }
 
+    #[inline]
+    fn time_base(&self) -> rmf_core::Rational {
+        match self {
+            Self::Default(d) => d.time_base(),
+        }
+    }
+    #[inline]
+    fn native_offset(&self) -> rmf_core::RationalTimestamp {
+        match self {
+            Self::Default(d) => d.native_offset(),
+        }
+    }
+
     #[inline]
     fn fps(&self) -> f64 {
         match self {
@@ -51,6 +65,16 @@ impl rmf_core::video::VideoContentCursor for ContextVideoContentCursor {
             Self::Default(d) => d.seek(timestamp),
         }
     }
+    #[inline]
+    fn seek_with_mode(
+        &mut self,
+        timestamp: rmf_core::Timestamp,
+        mode: rmf_core::SeekMode,
+    ) -> rmf_core::Result<()> {
+        match self {
+            Self::Default(d) => d.seek_with_mode(timestamp, mode),
+        }
+    }
 }
 
 impl rmf_core::video::VideoInput for ContextVideoInput {
@@ -69,16 +93,38 @@ impl rmf_core::video::VideoInput for ContextVideoInput {
         }
     }
     #[inline]
+    fn streams(&self) -> &[rmf_core::StreamInfo] {
+        match self {
+            Self::Default(d) => d.streams(),
+        }
+    }
+    #[inline]
+    fn stream_index(&self) -> usize {
+        match self {
+            Self::Default(d) => d.stream_index(),
+        }
+    }
+    #[inline]
     fn cursor(&self) -> rmf_core::Result<Self::ContentCursor> {
         Ok(match self {
             Self::Default(d) => ContextVideoContentCursor::Default(d.cursor()?),
         })
     }
+    #[inline]
+    fn cursor_with_options(
+        &self,
+        options: VideoCursorOptions,
+    ) -> rmf_core::Result<Self::ContentCursor> {
+        Ok(match self {
+            Self::Default(d) => ContextVideoContentCursor::Default(d.cursor_with_options(options)?),
+        })
+    }
 }
 
 #[derive(Clone)]
 pub struct VideoInputService {
     inner: ContextVideoInput,
+    cursor_options: VideoCursorOptions,
 }
 
 pub struct VideoInputContentCursor {
@@ -93,6 +139,13 @@ impl ContentCursorTrait for VideoInputContentCursor {
     fn seek(&mut self, timestamp: rmf_core::Timestamp) -> Result<()> {
         Ok(self.inner.seek(timestamp)?)
     }
+    fn seek_with_mode(
+        &mut self,
+        timestamp: rmf_core::Timestamp,
+        mode: rmf_core::SeekMode,
+    ) -> Result<()> {
+        Ok(self.inner.seek_with_mode(timestamp, mode)?)
+    }
 }
 
 impl ServiceTrait for VideoInputService {}
@@ -107,14 +160,16 @@ impl ContentStreamServiceTrait for VideoInputService {
     #[inline]
     fn cursor(&self) -> Result<Self::ContentCursor> {
         Ok(VideoInputContentCursor {
-            inner: self.inner.cursor()?,
+            inner: self
+                .inner
+                .cursor_with_options(self.cursor_options.clone())?,
         })
     }
 }
 
 impl VideoContentStreamServiceTrait for VideoInputService {
-    fn fps(&self) -> f64 {
-        self.inner.fps()
+    fn fps(&self) -> Option<f64> {
+        Some(self.inner.fps()).filter(|fps| *fps > 0.0)
     }
 }
 
@@ -124,6 +179,7 @@ impl From<DefaultVideoInput> for VideoInputService {
     fn from(value: DefaultVideoInput) -> Self {
         VideoInputService {
             inner: ContextVideoInput::Default(value),
+            cursor_options: VideoCursorOptions::default(),
         }
     }
 }
@@ -132,4 +188,28 @@ impl VideoInputService {
     pub fn try_new(source: InputSource) -> Result<Self> {
         Ok(Self::from(DefaultVideoInputProvider::provide(source)?))
     }
+    pub fn try_new_with_stream(source: InputSource, stream_index: usize) -> Result<Self> {
+        Ok(Self::from(DefaultVideoInputProvider::provide_stream(
+            source,
+            stream_index,
+        )?))
+    }
+    /// Every video stream of the input; pass an index to `try_new_with_stream` to read another one.
+    #[inline]
+    pub fn streams(&self) -> &[StreamInfo] {
+        self.inner.streams()
+    }
+    #[inline]
+    pub fn stream_index(&self) -> usize {
+        self.inner.stream_index()
+    }
+    /// Sets how cursors deliver frames, e.g. in the decoder's native pixel format. Timelines need RGBA.
+    pub fn with_cursor_options(mut self, options: VideoCursorOptions) -> Self {
+        self.cursor_options = options;
+        self
+    }
+    #[inline]
+    pub fn cursor_options(&self) -> &VideoCursorOptions {
+        &self.cursor_options
+    }
 }
@@ -1,3 +1,5 @@
 mod input_service;
 
 pub use input_service::*;
+
+pub use rmf_core::video::{AspectRatioPolicy, ScalingAlgorithm, VideoCursorOptions};
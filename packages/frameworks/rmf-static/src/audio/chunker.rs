@@ -0,0 +1,416 @@
+use anyhow::anyhow;
+use rmf_core::{
+    Content, Error, Result, SeekMode, Timestamp,
+    audio::{
+        Audio as _, AudioContentCursor, AudioData as _, AudioFormat, ChannelLayout, SampleLayout,
+    },
+};
+use rmf_macros::delegate_implements;
+
+use crate::{
+    Audio, AudioData, AudioDataContext, calculate_samples_to_position, sample_position,
+    sample_timestamp,
+};
+
+/// How [`AudioChunker`] sizes the blocks it returns.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AudioChunking {
+    /// Blocks of exactly this many samples per channel.
+    Samples(usize),
+    /// One block per video frame at this fps, split on [`calculate_samples_to_position`].
+    VideoFrames(f64),
+}
+
+type Lines = rmf_core::audio::AudioDataContext<
+    Vec<Vec<u8>>,
+    Vec<Vec<i16>>,
+    Vec<Vec<i32>>,
+    Vec<Vec<i64>>,
+    Vec<Vec<f32>>,
+    Vec<Vec<f64>>,
+>;
+
+#[derive(Clone, PartialEq)]
+struct BlockFormat {
+    format: AudioFormat,
+    sample_rate: u32,
+    channel_layout: ChannelLayout,
+    layout: SampleLayout,
+}
+
+/// Re-chunks decoded audio into fixed blocks that keep the sample format, rate and layout of the source.
+/// The last block is padded with silence, so every block has the full length.
+pub struct AudioChunker {
+    chunking: AudioChunking,
+    format: Option<BlockFormat>,
+    lines: Lines,
+    /// Source samples still to drop so the first block starts on a video frame.
+    skip: usize,
+    /// Index of the first buffered sample since the start of the stream.
+    position: i64,
+    frame: isize,
+    /// Whether [`AudioChunker::read_from`]'s source has ended.
+    source_ended: bool,
+    offset: Timestamp,
+}
+
+impl AudioChunker {
+    pub fn try_new(chunking: AudioChunking) -> Result<Self> {
+        match chunking {
+            AudioChunking::Samples(0) => {
+                Err(Error::new_audio(anyhow!("blocks need at least one sample")))
+            }
+            AudioChunking::VideoFrames(fps) if !(fps.is_finite() && fps > 0.0) => {
+                Err(Error::new_audio(anyhow!("{fps} is not a valid fps")))
+            }
+            _ => Ok(Self {
+                chunking,
+                format: None,
+                lines: Lines::None,
+                skip: 0,
+                position: 0,
+                frame: 0,
+                source_ended: false,
+                offset: Timestamp::default(),
+            }),
+        }
+    }
+
+    #[inline]
+    pub fn chunking(&self) -> AudioChunking {
+        self.chunking
+    }
+
+    /// The offset of the last block returned by [`AudioChunker::read_from`].
+    #[inline]
+    pub fn offset(&self) -> Timestamp {
+        self.offset
+    }
+
+    /// Returns the next block, pulling content from `read` until one is complete or `read` runs out.
+    /// This is the read loop of every chunking cursor, whatever its error type.
+    pub fn read_from<E: From<Error>>(
+        &mut self,
+        mut read: impl FnMut() -> std::result::Result<Option<Content<Audio>>, E>,
+    ) -> std::result::Result<Option<Content<Audio>>, E> {
+        loop {
+            if let Some(content) = self.pop(self.source_ended)? {
+                self.offset = content.offset();
+                return Ok(Some(content));
+            }
+            if self.source_ended {
+                return Ok(None);
+            }
+            match read()? {
+                Some(content) => self.push(&content)?,
+                None => self.source_ended = true,
+            }
+        }
+    }
+
+    /// Buffers `content`, which must have the format, rate and layout of the content pushed first.
+    pub fn push(&mut self, content: &Content<Audio>) -> Result<()> {
+        let audio = content.item();
+        let format = BlockFormat {
+            format: audio.format(),
+            sample_rate: audio.sample_rate(),
+            channel_layout: audio.channel_layout().clone(),
+            layout: audio.layout(),
+        };
+        match &self.format {
+            None => self.start(content.offset(), format),
+            Some(current) if *current == format => {}
+            Some(_) => {
+                return Err(Error::new_audio(anyhow!(
+                    "audio parameters changed mid-stream, normalize them with AudioCursorOptions"
+                )));
+            }
+        }
+        let skip = self.skip.min(audio.samples_len());
+        self.skip -= skip;
+        match (&mut self.lines, audio.data()) {
+            (Lines::None, AudioDataContext::None) => {}
+            (Lines::U8(lines), AudioDataContext::U8(data)) => extend(lines, data, skip),
+            (Lines::I16(lines), AudioDataContext::I16(data)) => extend(lines, data, skip),
+            (Lines::I32(lines), AudioDataContext::I32(data)) => extend(lines, data, skip),
+            (Lines::I64(lines), AudioDataContext::I64(data)) => extend(lines, data, skip),
+            (Lines::F32(lines), AudioDataContext::F32(data)) => extend(lines, data, skip),
+            (Lines::F64(lines), AudioDataContext::F64(data)) => extend(lines, data, skip),
+            _ => {
+                return Err(Error::new_audio(anyhow!(
+                    "sample format changed mid-stream"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the next complete block, or once `ended` the padded remainder.
+    pub fn pop(&mut self, ended: bool) -> Result<Option<Content<Audio>>> {
+        let Some(format) = self.format.clone() else {
+            return Ok(None);
+        };
+        let len = match self.chunking {
+            AudioChunking::Samples(len) => len,
+            AudioChunking::VideoFrames(fps) => {
+                (calculate_samples_to_position(fps, format.sample_rate, self.frame + 1) as i64
+                    - self.position)
+                    .max(0) as usize
+            }
+        };
+        let buffered = self.buffered();
+        if buffered < len && !(ended && buffered > 0) {
+            return Ok(None);
+        }
+        pad(&mut self.lines, len.saturating_sub(buffered));
+        let data = match &mut self.lines {
+            Lines::None => AudioDataContext::None,
+            Lines::U8(lines) => AudioDataContext::U8(split(lines, len, format.layout)?),
+            Lines::I16(lines) => AudioDataContext::I16(split(lines, len, format.layout)?),
+            Lines::I32(lines) => AudioDataContext::I32(split(lines, len, format.layout)?),
+            Lines::I64(lines) => AudioDataContext::I64(split(lines, len, format.layout)?),
+            Lines::F32(lines) => AudioDataContext::F32(split(lines, len, format.layout)?),
+            Lines::F64(lines) => AudioDataContext::F64(split(lines, len, format.layout)?),
+        };
+        let audio = Audio::tyr_new(data, format.sample_rate, format.channel_layout)?;
+        let (offset, end) = match self.chunking {
+            AudioChunking::Samples(_) => (
+                sample_timestamp(self.position, format.sample_rate),
+                sample_timestamp(self.position + len as i64, format.sample_rate),
+            ),
+            AudioChunking::VideoFrames(fps) => (
+                frame_timestamp(self.frame, fps),
+                frame_timestamp(self.frame + 1, fps),
+            ),
+        };
+        self.position += len as i64;
+        self.frame += 1;
+        Ok(Some(Content::new(audio, offset, end - offset)))
+    }
+
+    /// Drops every buffered sample; the next pushed content starts a new alignment.
+    pub fn reset(&mut self) {
+        self.format = None;
+        self.lines = Lines::None;
+        self.skip = 0;
+        self.source_ended = false;
+    }
+
+    fn start(&mut self, offset: Timestamp, format: BlockFormat) {
+        let first = sample_position(offset, format.sample_rate);
+        self.position = first;
+        self.lines = match format.format {
+            AudioFormat::None => Lines::None,
+            AudioFormat::U8 => Lines::U8(vec![]),
+            AudioFormat::I16 => Lines::I16(vec![]),
+            AudioFormat::I32 => Lines::I32(vec![]),
+            AudioFormat::I64 => Lines::I64(vec![]),
+            AudioFormat::F32 => Lines::F32(vec![]),
+            AudioFormat::F64 => Lines::F64(vec![]),
+        };
+        set_channels(&mut self.lines, format.channel_layout.channels());
+        if let AudioChunking::VideoFrames(fps) = self.chunking {
+            self.frame = (offset.as_seconds_float64() * fps).round() as isize;
+            let boundary =
+                calculate_samples_to_position(fps, format.sample_rate, self.frame) as i64;
+            // Pads up to or drops down to the frame boundary.
+            if first > boundary {
+                pad(&mut self.lines, (first - boundary) as usize);
+            } else {
+                self.skip = (boundary - first) as usize;
+            }
+            self.position = boundary;
+        }
+        self.format = Some(format);
+    }
+
+    fn buffered(&self) -> usize {
+        match &self.lines {
+            Lines::None => 0,
+            Lines::U8(lines) => lines_len(lines),
+            Lines::I16(lines) => lines_len(lines),
+            Lines::I32(lines) => lines_len(lines),
+            Lines::I64(lines) => lines_len(lines),
+            Lines::F32(lines) => lines_len(lines),
+            Lines::F64(lines) => lines_len(lines),
+        }
+    }
+}
+
+/// Wraps a cursor so it returns the blocks of an [`AudioChunker`].
+pub struct ChunkedAudioContentCursor<C> {
+    inner: C,
+    chunker: AudioChunker,
+}
+
+impl<C: AudioContentCursor<Item = Audio>> ChunkedAudioContentCursor<C> {
+    pub fn try_new(inner: C, chunking: AudioChunking) -> Result<Self> {
+        Ok(Self {
+            inner,
+            chunker: AudioChunker::try_new(chunking)?,
+        })
+    }
+}
+
+#[delegate_implements]
+impl<C: AudioContentCursor<Item = Audio>> AudioContentCursor for ChunkedAudioContentCursor<C> {
+    type Item = Audio;
+    #[inline]
+    fn offset(&self) -> Timestamp {
+        self.chunker.offset()
+    }
+    fn read(&mut self) -> Result<Option<Content<Audio>>> {
+        self.chunker.read_from(|| self.inner.read())
+    }
+    #[inline]
+    fn seek(&mut self, timestamp: Timestamp) -> Result<()> {
+        self.seek_with_mode(timestamp, SeekMode::Keyframe)
+    }
+    fn seek_with_mode(&mut self, timestamp: Timestamp, mode: SeekMode) -> Result<()> {
+        self.inner.seek_with_mode(timestamp, mode)?;
+        self.chunker.reset();
+        Ok(())
+    }
+}
+
+#[inline]
+fn frame_timestamp(frame: isize, fps: f64) -> Timestamp {
+    Timestamp::from_microseconds((frame as f64 * 1_000_000.0 / fps).round() as i64)
+}
+
+#[inline]
+fn lines_len<T>(lines: &[Vec<T>]) -> usize {
+    lines.first().map_or(0, Vec::len)
+}
+
+fn set_channels(lines: &mut Lines, channels: usize) {
+    match lines {
+        Lines::None => {}
+        Lines::U8(lines) => lines.resize_with(channels, Vec::new),
+        Lines::I16(lines) => lines.resize_with(channels, Vec::new),
+        Lines::I32(lines) => lines.resize_with(channels, Vec::new),
+        Lines::I64(lines) => lines.resize_with(channels, Vec::new),
+        Lines::F32(lines) => lines.resize_with(channels, Vec::new),
+        Lines::F64(lines) => lines.resize_with(channels, Vec::new),
+    }
+}
+
+/// Appends `len` samples of silence; unsigned 8-bit silence sits at the midpoint.
+fn pad(lines: &mut Lines, len: usize) {
+    #[inline]
+    fn fill<T: Clone>(lines: &mut [Vec<T>], len: usize, silence: T) {
+        lines
+            .iter_mut()
+            .for_each(|line| line.resize(line.len() + len, silence.clone()));
+    }
+    match lines {
+        Lines::None => {}
+        Lines::U8(lines) => fill(lines, len, 128),
+        Lines::I16(lines) => fill(lines, len, 0),
+        Lines::I32(lines) => fill(lines, len, 0),
+        Lines::I64(lines) => fill(lines, len, 0),
+        Lines::F32(lines) => fill(lines, len, 0.0),
+        Lines::F64(lines) => fill(lines, len, 0.0),
+    }
+}
+
+fn extend<T: Clone>(lines: &mut [Vec<T>], data: &AudioData<T>, skip: usize) {
+    for (channel, line) in lines.iter_mut().enumerate() {
+        line.extend(data.channel_samples(channel).skip(skip).cloned());
+    }
+}
+
+fn split<T: Clone>(lines: &mut [Vec<T>], len: usize, layout: SampleLayout) -> Result<AudioData<T>> {
+    let block = lines
+        .iter_mut()
+        .map(|line| line.drain(..len).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    match layout {
+        SampleLayout::Planar => AudioData::from_channels(block),
+        SampleLayout::Packed => AudioData::from_packed(
+            block.len(),
+            (0..len)
+                .flat_map(|index| block.iter().map(move |line| line[index].clone()))
+                .collect(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    fn content(offset: Timestamp, samples: Vec<f32>) -> Content<Audio> {
+        let duration = sample_timestamp(samples.len() as i64, 100);
+        let audio = Audio::tyr_new(
+            AudioDataContext::F32(AudioData::from_channels(vec![samples]).unwrap()),
+            100,
+            ChannelLayout::mono(),
+        )
+        .unwrap();
+        Content::new(audio, offset, duration)
+    }
+
+    fn blocks(chunker: &mut AudioChunker) -> Vec<(Timestamp, Timestamp, Vec<f32>)> {
+        let mut blocks = vec![];
+        while let Some(content) = chunker.pop(true).unwrap() {
+            blocks.push((
+                content.offset(),
+                content.duration(),
+                content.item().to_f32_lines().remove(0),
+            ));
+        }
+        blocks
+    }
+
+    #[rstest]
+    #[case(4, vec![vec![1.0, 2.0, 3.0, 4.0], vec![5.0, 0.0, 0.0, 0.0]])]
+    #[case(5, vec![vec![1.0, 2.0, 3.0, 4.0, 5.0]])]
+    fn samples_chunking_works(#[case] len: usize, #[case] expected: Vec<Vec<f32>>) {
+        let mut chunker = AudioChunker::try_new(AudioChunking::Samples(len)).unwrap();
+        chunker
+            .push(&content(Timestamp::default(), vec![1.0, 2.0, 3.0]))
+            .unwrap();
+        chunker
+            .push(&content(Timestamp::from_milliseconds(30), vec![4.0, 5.0]))
+            .unwrap();
+        let lines = blocks(&mut chunker)
+            .into_iter()
+            .map(|(_, duration, line)| {
+                assert_eq!(duration, sample_timestamp(len as i64, 100));
+                line
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(lines, expected);
+    }
+
+    #[rstest]
+    #[case(Timestamp::from_milliseconds(20), 0, vec![0.0, 0.0, 1.0, 2.0, 3.0])]
+    #[case(Timestamp::from_milliseconds(70), 50, vec![0.0, 0.0, 1.0, 2.0, 3.0])]
+    #[case(Timestamp::from_milliseconds(80), 100, vec![3.0, 0.0, 0.0, 0.0, 0.0])]
+    fn video_frames_chunking_aligns_to_frames(
+        #[case] offset: Timestamp,
+        #[case] expected_offset: i64,
+        #[case] expected: Vec<f32>,
+    ) {
+        // 20 fps at 100 Hz makes frames of 5 samples.
+        let mut chunker = AudioChunker::try_new(AudioChunking::VideoFrames(20.0)).unwrap();
+        chunker.push(&content(offset, vec![1.0, 2.0, 3.0])).unwrap();
+        let blocks = blocks(&mut chunker);
+        assert_eq!(blocks.len(), 1);
+        let (block_offset, duration, line) = blocks[0].clone();
+        assert_eq!(block_offset, Timestamp::from_milliseconds(expected_offset));
+        assert_eq!(duration, Timestamp::from_milliseconds(50));
+        assert_eq!(line, expected);
+    }
+
+    #[rstest]
+    #[case(AudioChunking::Samples(0))]
+    #[case(AudioChunking::VideoFrames(0.0))]
+    #[case(AudioChunking::VideoFrames(f64::NAN))]
+    fn invalid_chunking_fails(#[case] chunking: AudioChunking) {
+        assert!(AudioChunker::try_new(chunking).is_err());
+    }
+}
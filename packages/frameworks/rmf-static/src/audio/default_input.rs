@@ -16,6 +16,14 @@ impl rmf_core::audio::AudioContentCursor for DefaultAudioContentCursor {
         self.0.offset()
     }
     #[inline]
+    fn time_base(&self) -> rmf_core::Rational {
+        self.0.time_base()
+    }
+    #[inline]
+    fn native_offset(&self) -> rmf_core::RationalTimestamp {
+        self.0.native_offset()
+    }
+    #[inline]
     fn read(&mut self) -> rmf_core::Result<Option<rmf_core::Content<Audio>>> {
         self.0.read()
     }
@@ -23,6 +31,14 @@ impl rmf_core::audio::AudioContentCursor for DefaultAudioContentCursor {
     fn seek(&mut self, timestamp: rmf_core::Timestamp) -> rmf_core::Result<()> {
         self.0.seek(timestamp)
     }
+    #[inline]
+    fn seek_with_mode(
+        &mut self,
+        timestamp: rmf_core::Timestamp,
+        mode: rmf_core::SeekMode,
+    ) -> rmf_core::Result<()> {
+        self.0.seek_with_mode(timestamp, mode)
+    }
 }
 
 #[derive(Clone)]
@@ -40,11 +56,28 @@ impl rmf_core::audio::AudioInput for DefaultAudioInput {
     fn sample_rate(&self) -> u32 {
         self.0.sample_rate()
     }
+    #[inline]
+    fn streams(&self) -> &[rmf_core::StreamInfo] {
+        self.0.streams()
+    }
+    #[inline]
+    fn stream_index(&self) -> usize {
+        self.0.stream_index()
+    }
 
     #[inline]
     fn cursor(&self) -> rmf_core::Result<DefaultAudioContentCursor> {
         Ok(DefaultAudioContentCursor(self.0.cursor()?))
     }
+    #[inline]
+    fn cursor_with_options(
+        &self,
+        options: rmf_core::audio::AudioCursorOptions,
+    ) -> rmf_core::Result<DefaultAudioContentCursor> {
+        Ok(DefaultAudioContentCursor(
+            self.0.cursor_with_options(options)?,
+        ))
+    }
 }
 
 pub struct DefaultAudioInputProvider;
@@ -53,4 +86,13 @@ impl DefaultAudioInputProvider {
     pub fn provide(source: rmf_core::InputSource) -> crate::core::Result<DefaultAudioInput> {
         Ok(DefaultAudioInput(AVFormatAudioInput::try_new(source)?))
     }
+    pub fn provide_stream(
+        source: rmf_core::InputSource,
+        stream_index: usize,
+    ) -> crate::core::Result<DefaultAudioInput> {
+        Ok(DefaultAudioInput(AVFormatAudioInput::try_new_with_stream(
+            source,
+            stream_index,
+        )?))
+    }
 }
@@ -0,0 +1,293 @@
+use rmf_core::{
+    Content, Error, Rational, RationalTimestamp, Result, SeekMode, Timestamp,
+    audio::{Audio as _, AudioContentCursor, AudioData as _, SampleLayout},
+    audio_effect::AudioEffect,
+};
+use rmf_macros::delegate_implements;
+
+use crate::{Audio, AudioData, AudioDataContext};
+
+/// Applies `effects` to audio that starts at `offset`, multiplying their gains sample by sample.
+/// Effect timestamps are on the same clock as `offset`.
+pub fn apply_audio_effects(
+    audio: &Audio,
+    offset: Timestamp,
+    effects: &[AudioEffect],
+) -> Result<Audio> {
+    if effects.is_empty() {
+        return Ok(audio.clone());
+    }
+    let start = offset.as_seconds_float64();
+    let sample_rate = audio.sample_rate().max(1) as f64;
+    let gains = (0..audio.samples_len())
+        .map(|i| {
+            let seconds = start + i as f64 / sample_rate;
+            effects
+                .iter()
+                .map(|effect| effect.gain_at_seconds(seconds))
+                .product::<f32>()
+        })
+        .collect::<Vec<_>>();
+    let data = match audio.data() {
+        AudioDataContext::None => AudioDataContext::None,
+        AudioDataContext::U8(d) => AudioDataContext::U8(scale_data(d, &gains)?),
+        AudioDataContext::I16(d) => AudioDataContext::I16(scale_data(d, &gains)?),
+        AudioDataContext::I32(d) => AudioDataContext::I32(scale_data(d, &gains)?),
+        AudioDataContext::I64(d) => AudioDataContext::I64(scale_data(d, &gains)?),
+        AudioDataContext::F32(d) => AudioDataContext::F32(scale_data(d, &gains)?),
+        AudioDataContext::F64(d) => AudioDataContext::F64(scale_data(d, &gains)?),
+    };
+    Audio::tyr_new(data, audio.sample_rate(), audio.channel_layout().clone())
+}
+
+/// Effects applied, in order, to every content an effect cursor reads.
+#[derive(Clone, Default)]
+pub struct AudioEffectChain {
+    effects: Vec<AudioEffect>,
+}
+
+impl AudioEffectChain {
+    pub fn new(effects: Vec<AudioEffect>) -> Self {
+        Self { effects }
+    }
+    #[inline]
+    pub fn effects(&self) -> &[AudioEffect] {
+        &self.effects
+    }
+    #[inline]
+    pub fn effects_mut(&mut self) -> &mut Vec<AudioEffect> {
+        &mut self.effects
+    }
+
+    /// Returns the next content from `read` with the effects applied.
+    /// This is the read of every effect cursor, whatever its error type.
+    pub fn read_from<E: From<Error>>(
+        &self,
+        read: impl FnOnce() -> std::result::Result<Option<Content<Audio>>, E>,
+    ) -> std::result::Result<Option<Content<Audio>>, E> {
+        read()?
+            .map(|content| {
+                Ok(content.with_item(apply_audio_effects(
+                    content.item(),
+                    content.offset(),
+                    &self.effects,
+                )?))
+            })
+            .transpose()
+    }
+}
+
+/// Wraps an audio cursor and applies gain, fade and envelope effects to everything it reads.
+pub struct AudioEffectContentCursor<C> {
+    inner: C,
+    chain: AudioEffectChain,
+}
+
+impl<C: AudioContentCursor<Item = Audio>> AudioEffectContentCursor<C> {
+    pub fn new(inner: C, effects: Vec<AudioEffect>) -> Self {
+        Self {
+            inner,
+            chain: AudioEffectChain::new(effects),
+        }
+    }
+    pub fn with_effect(mut self, effect: AudioEffect) -> Self {
+        self.chain.effects_mut().push(effect);
+        self
+    }
+    #[inline]
+    pub fn effects(&self) -> &[AudioEffect] {
+        self.chain.effects()
+    }
+    #[inline]
+    pub fn effects_mut(&mut self) -> &mut Vec<AudioEffect> {
+        self.chain.effects_mut()
+    }
+}
+
+#[delegate_implements]
+impl<C: AudioContentCursor<Item = Audio>> AudioContentCursor for AudioEffectContentCursor<C> {
+    type Item = Audio;
+    #[inline]
+    fn offset(&self) -> Timestamp {
+        self.inner.offset()
+    }
+    #[inline]
+    fn time_base(&self) -> Rational {
+        self.inner.time_base()
+    }
+    #[inline]
+    fn native_offset(&self) -> RationalTimestamp {
+        self.inner.native_offset()
+    }
+    fn read(&mut self) -> Result<Option<Content<Audio>>> {
+        self.chain.read_from(|| self.inner.read())
+    }
+    #[inline]
+    fn seek(&mut self, timestamp: Timestamp) -> Result<()> {
+        self.inner.seek(timestamp)
+    }
+    #[inline]
+    fn seek_with_mode(&mut self, timestamp: Timestamp, mode: SeekMode) -> Result<()> {
+        self.inner.seek_with_mode(timestamp, mode)
+    }
+}
+
+trait Sample: Copy {
+    fn scale(self, gain: f32) -> Self;
+}
+
+macro_rules! impl_integer_sample {
+    ($($t:ty),*) => {
+        $(impl Sample for $t {
+            #[inline]
+            fn scale(self, gain: f32) -> Self {
+                (self as f64 * gain as f64).round() as Self
+            }
+        })*
+    };
+}
+
+impl_integer_sample!(i16, i32, i64);
+
+/// Unsigned samples are centered on 128.
+impl Sample for u8 {
+    #[inline]
+    fn scale(self, gain: f32) -> Self {
+        ((self as f32 - 128.0) * gain + 128.0).round() as Self
+    }
+}
+
+impl Sample for f32 {
+    #[inline]
+    fn scale(self, gain: f32) -> Self {
+        self * gain
+    }
+}
+
+impl Sample for f64 {
+    #[inline]
+    fn scale(self, gain: f32) -> Self {
+        self * gain as f64
+    }
+}
+
+fn scale_data<T: Sample>(data: &AudioData<T>, gains: &[f32]) -> Result<AudioData<T>> {
+    match data.layout() {
+        SampleLayout::Planar => AudioData::from_channels(
+            data.iter()
+                .map(|line| {
+                    line.iter()
+                        .zip(gains)
+                        .map(|(sample, gain)| sample.scale(*gain))
+                        .collect()
+                })
+                .collect(),
+        ),
+        SampleLayout::Packed => {
+            let channels = data.channels_len();
+            AudioData::from_packed(
+                channels,
+                data.plane(0)
+                    .unwrap_or_default()
+                    .iter()
+                    .enumerate()
+                    .map(|(i, sample)| sample.scale(gains[i / channels.max(1)]))
+                    .collect(),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rmf_core::{
+        audio::ChannelLayout,
+        audio_effect::{FadeCurve, VolumeEnvelope},
+    };
+    use rstest::rstest;
+
+    fn audio(data: AudioDataContext, channel_layout: ChannelLayout) -> Audio {
+        Audio::tyr_new(data, 4, channel_layout).unwrap()
+    }
+
+    #[rstest]
+    #[case(AudioEffect::Gain { db: 0.0 }, vec![1.0, 1.0, 1.0, 1.0])]
+    #[case(
+        AudioEffect::FadeIn {
+            start: Timestamp::default(),
+            duration: Timestamp::from_seconds(1),
+            curve: FadeCurve::Linear,
+        },
+        vec![0.0, 0.25, 0.5, 0.75]
+    )]
+    #[case(
+        AudioEffect::FadeOut {
+            end: Timestamp::from_seconds(1),
+            duration: Timestamp::from_seconds(1),
+            curve: FadeCurve::Linear,
+        },
+        vec![1.0, 0.75, 0.5, 0.25]
+    )]
+    #[case(AudioEffect::Envelope(VolumeEnvelope::default()), vec![1.0, 1.0, 1.0, 1.0])]
+    fn apply_audio_effects_works(#[case] effect: AudioEffect, #[case] expected: Vec<f32>) {
+        let audio = audio(
+            AudioDataContext::F32(AudioData::from_channels(vec![vec![1.0; 4]]).unwrap()),
+            ChannelLayout::mono(),
+        );
+        let applied = apply_audio_effects(&audio, Timestamp::default(), &[effect]).unwrap();
+        assert_eq!(applied.to_f32_lines(), vec![expected]);
+    }
+
+    #[rstest]
+    fn apply_audio_effects_keeps_packed_layout() {
+        let audio = audio(
+            AudioDataContext::I16(AudioData::from_packed(2, vec![100, -100, 100, -100]).unwrap()),
+            ChannelLayout::stereo(),
+        );
+        let effect = AudioEffect::FadeIn {
+            start: Timestamp::default(),
+            duration: Timestamp::from_milliseconds(250),
+            curve: FadeCurve::Linear,
+        };
+        let applied = apply_audio_effects(&audio, Timestamp::default(), &[effect]).unwrap();
+        let AudioDataContext::I16(data) = applied.data() else {
+            panic!("sample format changed");
+        };
+        assert_eq!(data.layout(), SampleLayout::Packed);
+        assert_eq!(data.plane(0).unwrap(), &[0, 0, 100, -100]);
+    }
+
+    #[rstest]
+    fn read_from_applies_effects_at_content_offset() {
+        let chain = AudioEffectChain::new(vec![AudioEffect::FadeIn {
+            start: Timestamp::from_seconds(1),
+            duration: Timestamp::from_seconds(1),
+            curve: FadeCurve::Linear,
+        }]);
+        let content = Content::new(
+            audio(
+                AudioDataContext::F32(AudioData::from_channels(vec![vec![1.0; 4]]).unwrap()),
+                ChannelLayout::mono(),
+            ),
+            Timestamp::from_seconds(1),
+            Timestamp::from_seconds(1),
+        );
+        let read = chain
+            .read_from(|| Ok::<_, Error>(Some(content)))
+            .unwrap()
+            .unwrap();
+        assert_eq!(read.offset(), Timestamp::from_seconds(1));
+        assert_eq!(read.item().to_f32_lines(), vec![vec![0.0, 0.25, 0.5, 0.75]]);
+        assert!(chain.read_from(|| Ok::<_, Error>(None)).unwrap().is_none());
+    }
+
+    #[rstest]
+    #[case(255, 0.5, 192)]
+    #[case(0, 0.5, 64)]
+    #[case(128, 2.0, 128)]
+    fn u8_scale_stays_centered(#[case] sample: u8, #[case] gain: f32, #[case] expected: u8) {
+        assert_eq!(sample.scale(gain), expected);
+    }
+}
@@ -0,0 +1,62 @@
+use anyhow::anyhow;
+use rmf_core::{
+    Content, Error, Result,
+    audio::AudioContentCursor,
+    loudness::{LoudnessMeter, LoudnessReport},
+};
+
+use crate::Audio;
+
+/// Feeds decoded audio into a [`LoudnessMeter`] set up from the first block it sees.
+#[derive(Default)]
+pub struct LoudnessAnalyzer {
+    meter: Option<LoudnessMeter>,
+}
+
+impl LoudnessAnalyzer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, content: &Content<Audio>) -> Result<()> {
+        let audio = content.item();
+        let meter = match &mut self.meter {
+            Some(meter) => meter,
+            meter => meter.insert(
+                LoudnessMeter::try_new(audio.sample_rate(), audio.channel_layout())?
+                    .with_start(content.offset()),
+            ),
+        };
+        if audio.sample_rate() != meter.sample_rate() {
+            return Err(Error::new_audio(anyhow!(
+                "sample rate changed from {} to {} Hz while measuring loudness",
+                meter.sample_rate(),
+                audio.sample_rate()
+            )));
+        }
+        meter.push(&audio.to_f32_lines())
+    }
+
+    #[inline]
+    pub fn meter(&self) -> Option<&LoudnessMeter> {
+        self.meter.as_ref()
+    }
+
+    pub fn report(&self) -> Result<LoudnessReport> {
+        self.meter
+            .as_ref()
+            .map(LoudnessMeter::report)
+            .ok_or_else(|| Error::new_audio(anyhow!("no audio to measure loudness of")))
+    }
+}
+
+/// Reads `cursor` to the end and measures the loudness of everything it returns.
+pub fn analyze_loudness<C: AudioContentCursor<Item = Audio>>(
+    cursor: &mut C,
+) -> Result<LoudnessReport> {
+    let mut analyzer = LoudnessAnalyzer::new();
+    while let Some(content) = cursor.read()? {
+        analyzer.push(&content)?;
+    }
+    analyzer.report()
+}
@@ -1,7 +1,15 @@
+mod chunker;
 mod default_input;
+mod effects;
+mod loudness;
 mod utils;
 
+#[cfg(feature = "ffmpeg_audio_as_default")]
+pub use crate::ffmpeg::AudioConverter;
 #[cfg(feature = "ffmpeg_audio_as_default")]
 pub use crate::ffmpeg::audio::*;
+pub use chunker::*;
 pub use default_input::*;
+pub use effects::*;
+pub use loudness::*;
 pub use utils::*;
@@ -1,3 +1,5 @@
+use rmf_core::Timestamp;
+
 #[inline]
 pub fn calculate_frame_samples(fps: f64, sample_rate: u32, position: isize) -> isize {
     calculate_samples_to_position(fps, sample_rate, position + 1)
@@ -13,6 +15,19 @@ pub fn calculate_samples_to_position(fps: f64, sample_rate: u32, position: isize
     }
 }
 
+/// When the sample at `position` starts, truncated to microseconds.
+#[inline]
+pub fn sample_timestamp(position: i64, sample_rate: u32) -> Timestamp {
+    Timestamp::from_microseconds((position as i128 * 1_000_000 / sample_rate.max(1) as i128) as i64)
+}
+
+/// The sample nearest to `timestamp`, rounding halves up.
+#[inline]
+pub fn sample_position(timestamp: Timestamp, sample_rate: u32) -> i64 {
+    (timestamp.as_microseconds() as i128 * sample_rate as i128 + 500_000).div_euclid(1_000_000)
+        as i64
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -52,4 +67,51 @@ mod tests {
             calculate_samples_to_position(fps, sample_rate, position)
         )
     }
+
+    #[rstest]
+    #[case(0, 48000, 0)]
+    #[case(48000, 48000, 1_000_000)]
+    #[case(1, 44100, 22)]
+    #[case(-1, 44100, -22)]
+    #[case(1, 0, 1_000_000)]
+    fn sample_timestamp_works(
+        #[case] position: i64,
+        #[case] sample_rate: u32,
+        #[case] expected: i64,
+    ) {
+        assert_eq!(
+            sample_timestamp(position, sample_rate),
+            Timestamp::from_microseconds(expected)
+        )
+    }
+
+    #[rstest]
+    #[case(0, 48000, 0)]
+    #[case(1_000_000, 48000, 48000)]
+    #[case(22, 44100, 1)]
+    #[case(-22, 44100, -1)]
+    #[case(5_000, 100, 1)]
+    #[case(4_999, 100, 0)]
+    fn sample_position_works(
+        #[case] microseconds: i64,
+        #[case] sample_rate: u32,
+        #[case] expected: i64,
+    ) {
+        assert_eq!(
+            sample_position(Timestamp::from_microseconds(microseconds), sample_rate),
+            expected
+        )
+    }
+
+    #[rstest]
+    #[case(44100)]
+    #[case(48000)]
+    fn sample_position_round_trips(#[case] sample_rate: u32) {
+        for position in [-1000, -1, 0, 1, 999, 123_456] {
+            assert_eq!(
+                sample_position(sample_timestamp(position, sample_rate), sample_rate),
+                position
+            );
+        }
+    }
 }
@@ -1,26 +1,45 @@
-use std::collections::VecDeque;
-use std::marker::PhantomData;
-use std::slice::Iter;
+use std::ops::Range;
+use std::slice::{self, Iter};
+use std::sync::Arc;
 
 use anyhow::anyhow;
-use rmf_core::{Error, Result};
+use rmf_core::audio::{
+    Audio as _, AudioData as _, AudioFormat, ChannelLayout, ChannelPosition, SampleLayout,
+};
+use rmf_core::{Error, Result, TimeRange, Timestamp};
 use rmf_macros::delegate_implements;
 use rsmpeg::ffi::{
     AV_SAMPLE_FMT_DBL, AV_SAMPLE_FMT_DBLP, AV_SAMPLE_FMT_FLT, AV_SAMPLE_FMT_FLTP,
     AV_SAMPLE_FMT_S16, AV_SAMPLE_FMT_S16P, AV_SAMPLE_FMT_S32, AV_SAMPLE_FMT_S32P,
-    AV_SAMPLE_FMT_S64, AV_SAMPLE_FMT_S64P, AV_SAMPLE_FMT_U8P,
+    AV_SAMPLE_FMT_S64, AV_SAMPLE_FMT_S64P, AV_SAMPLE_FMT_U8P, AVSampleFormat,
 };
 use rsmpeg::{
-    avutil::AVFrame,
-    ffi::{AV_SAMPLE_FMT_NONE, AV_SAMPLE_FMT_U8},
+    avutil::{AVChannelLayout, AVFrame},
+    ffi::{self, AV_SAMPLE_FMT_NONE, AV_SAMPLE_FMT_U8},
 };
 
+use crate::sample_position;
+
 #[derive(Clone)]
 pub struct Audio {
     data_context: AudioDataContext,
+    sample_rate: u32,
+    channel_layout: ChannelLayout,
 }
 
-impl rmf_core::InnerContent for Audio {}
+impl rmf_core::InnerContent for Audio {
+    /// Keeps the samples whose start lies inside `used`.
+    fn cut(self, range: TimeRange, used: TimeRange) -> Result<Self> {
+        let len = self.samples_len();
+        let position = |timestamp: Timestamp| {
+            sample_position(timestamp - range.start(), self.sample_rate).clamp(0, len as i64)
+                as usize
+        };
+        let start = position(used.start());
+        let end = position(used.end()).max(start);
+        self.slice(start..end)
+    }
+}
 
 #[delegate_implements]
 impl rmf_core::audio::Audio for Audio {
@@ -31,6 +50,14 @@ impl rmf_core::audio::Audio for Audio {
     type F32Data = AudioData<f32>;
     type F64Data = AudioData<f64>;
 
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+    #[inline]
+    fn channel_layout(&self) -> &ChannelLayout {
+        &self.channel_layout
+    }
     #[inline]
     fn data(&self) -> &AudioDataContext {
         &self.data_context
@@ -46,9 +73,219 @@ impl rmf_core::audio::AudioConstructor for Audio {
     type F32Data = AudioData<f32>;
     type F64Data = AudioData<f64>;
     type Audio = Self;
-    fn tyr_new(data_context: AudioDataContext) -> Result<Self> {
-        Ok(Self { data_context })
+    fn tyr_new(
+        data_context: AudioDataContext,
+        sample_rate: u32,
+        channel_layout: ChannelLayout,
+    ) -> Result<Self> {
+        let channels = match &data_context {
+            AudioDataContext::None => channel_layout.channels(),
+            AudioDataContext::U8(d) => d.channels_len(),
+            AudioDataContext::I16(d) => d.channels_len(),
+            AudioDataContext::I32(d) => d.channels_len(),
+            AudioDataContext::I64(d) => d.channels_len(),
+            AudioDataContext::F32(d) => d.channels_len(),
+            AudioDataContext::F64(d) => d.channels_len(),
+        };
+        if channels != channel_layout.channels() {
+            return Err(Error::new_audio(anyhow!(
+                "{channels} channels don't match a layout of {}",
+                channel_layout.channels()
+            )));
+        }
+        Ok(Self {
+            data_context,
+            sample_rate,
+            channel_layout,
+        })
+    }
+}
+
+impl Audio {
+    /// Builds audio from a decoded frame, taking its sample rate and channel layout.
+    pub(crate) fn try_from_frame(frame: &AVFrame) -> Result<Self> {
+        Self::tyr_new(
+            AudioDataContextBuilder::try_new(frame)?,
+            frame.sample_rate as _,
+            to_channel_layout(&frame.ch_layout()),
+        )
+    }
+
+    /// How the samples are laid out; silence without data counts as the default layout.
+    pub fn layout(&self) -> SampleLayout {
+        match &self.data_context {
+            AudioDataContext::None => SampleLayout::default(),
+            AudioDataContext::U8(d) => d.layout(),
+            AudioDataContext::I16(d) => d.layout(),
+            AudioDataContext::I32(d) => d.layout(),
+            AudioDataContext::I64(d) => d.layout(),
+            AudioDataContext::F32(d) => d.layout(),
+            AudioDataContext::F64(d) => d.layout(),
+        }
+    }
+
+    /// Copies the samples in `samples`, which is clamped to the samples held.
+    pub fn slice(&self, samples: Range<usize>) -> Result<Self> {
+        let end = samples.end.min(self.samples_len());
+        let samples = samples.start.min(end)..end;
+        let data = match &self.data_context {
+            AudioDataContext::None => AudioDataContext::None,
+            AudioDataContext::U8(d) => AudioDataContext::U8(d.slice(samples)?),
+            AudioDataContext::I16(d) => AudioDataContext::I16(d.slice(samples)?),
+            AudioDataContext::I32(d) => AudioDataContext::I32(d.slice(samples)?),
+            AudioDataContext::I64(d) => AudioDataContext::I64(d.slice(samples)?),
+            AudioDataContext::F32(d) => AudioDataContext::F32(d.slice(samples)?),
+            AudioDataContext::F64(d) => AudioDataContext::F64(d.slice(samples)?),
+        };
+        Self::tyr_new(data, self.sample_rate, self.channel_layout.clone())
+    }
+
+    /// Converts every sample to `f32` in `-1.0..=1.0`, one `Vec` per channel.
+    pub fn to_f32_lines(&self) -> Vec<Vec<f32>> {
+        #[inline]
+        fn convert<T: Clone>(data: &AudioData<T>, f: impl Fn(&T) -> f32) -> Vec<Vec<f32>> {
+            (0..data.channels_len())
+                .map(|channel| data.channel_samples(channel).map(&f).collect())
+                .collect()
+        }
+        match &self.data_context {
+            AudioDataContext::None => vec![],
+            AudioDataContext::U8(d) => convert(d, |s| (*s as f32 - 128.0) / 128.0),
+            AudioDataContext::I16(d) => convert(d, |s| *s as f32 / i16::MAX as f32),
+            AudioDataContext::I32(d) => convert(d, |s| *s as f32 / i32::MAX as f32),
+            AudioDataContext::I64(d) => convert(d, |s| *s as f32 / i64::MAX as f32),
+            AudioDataContext::F32(d) => convert(d, |s| *s),
+            AudioDataContext::F64(d) => convert(d, |s| *s as f32),
+        }
+    }
+}
+
+/// Reads the position of every channel; unordered layouts give unknown positions.
+pub fn to_channel_layout(layout: &AVChannelLayout) -> ChannelLayout {
+    ChannelLayout::new(
+        (0..layout.nb_channels)
+            .map(|index| {
+                to_channel_position(unsafe {
+                    ffi::av_channel_layout_channel_from_index(layout.as_ptr(), index as _)
+                })
+            })
+            .collect(),
+    )
+}
+
+/// Native layouts can't reorder channels, so other orders and unknown positions fall back to the default layout.
+pub fn to_av_channel_layout(layout: &ChannelLayout) -> AVChannelLayout {
+    let mut mask = 0u64;
+    let mut last = None;
+    for position in layout.positions() {
+        match CHANNEL_POSITIONS.iter().find(|(_, p)| p == position) {
+            Some((channel, _)) if last < Some(*channel) => {
+                mask |= 1 << channel;
+                last = Some(*channel);
+            }
+            _ => return AVChannelLayout::from_nb_channels(layout.channels() as _),
+        }
     }
+    AVChannelLayout::from_mask(mask)
+        .unwrap_or_else(|| AVChannelLayout::from_nb_channels(layout.channels() as _))
+}
+
+fn to_channel_position(channel: ffi::AVChannel) -> ChannelPosition {
+    CHANNEL_POSITIONS
+        .iter()
+        .find(|(c, _)| *c == channel)
+        .map_or(ChannelPosition::Unknown, |(_, position)| *position)
+}
+
+const CHANNEL_POSITIONS: [(ffi::AVChannel, ChannelPosition); 30] = [
+    (ffi::AV_CHAN_FRONT_LEFT, ChannelPosition::FrontLeft),
+    (ffi::AV_CHAN_FRONT_RIGHT, ChannelPosition::FrontRight),
+    (ffi::AV_CHAN_FRONT_CENTER, ChannelPosition::FrontCenter),
+    (ffi::AV_CHAN_LOW_FREQUENCY, ChannelPosition::LowFrequency),
+    (ffi::AV_CHAN_BACK_LEFT, ChannelPosition::BackLeft),
+    (ffi::AV_CHAN_BACK_RIGHT, ChannelPosition::BackRight),
+    (
+        ffi::AV_CHAN_FRONT_LEFT_OF_CENTER,
+        ChannelPosition::FrontLeftOfCenter,
+    ),
+    (
+        ffi::AV_CHAN_FRONT_RIGHT_OF_CENTER,
+        ChannelPosition::FrontRightOfCenter,
+    ),
+    (ffi::AV_CHAN_BACK_CENTER, ChannelPosition::BackCenter),
+    (ffi::AV_CHAN_SIDE_LEFT, ChannelPosition::SideLeft),
+    (ffi::AV_CHAN_SIDE_RIGHT, ChannelPosition::SideRight),
+    (ffi::AV_CHAN_TOP_CENTER, ChannelPosition::TopCenter),
+    (ffi::AV_CHAN_TOP_FRONT_LEFT, ChannelPosition::TopFrontLeft),
+    (
+        ffi::AV_CHAN_TOP_FRONT_CENTER,
+        ChannelPosition::TopFrontCenter,
+    ),
+    (ffi::AV_CHAN_TOP_FRONT_RIGHT, ChannelPosition::TopFrontRight),
+    (ffi::AV_CHAN_TOP_BACK_LEFT, ChannelPosition::TopBackLeft),
+    (ffi::AV_CHAN_TOP_BACK_CENTER, ChannelPosition::TopBackCenter),
+    (ffi::AV_CHAN_TOP_BACK_RIGHT, ChannelPosition::TopBackRight),
+    (ffi::AV_CHAN_STEREO_LEFT, ChannelPosition::StereoLeft),
+    (ffi::AV_CHAN_STEREO_RIGHT, ChannelPosition::StereoRight),
+    (ffi::AV_CHAN_WIDE_LEFT, ChannelPosition::WideLeft),
+    (ffi::AV_CHAN_WIDE_RIGHT, ChannelPosition::WideRight),
+    (
+        ffi::AV_CHAN_SURROUND_DIRECT_LEFT,
+        ChannelPosition::SurroundDirectLeft,
+    ),
+    (
+        ffi::AV_CHAN_SURROUND_DIRECT_RIGHT,
+        ChannelPosition::SurroundDirectRight,
+    ),
+    (ffi::AV_CHAN_LOW_FREQUENCY_2, ChannelPosition::LowFrequency2),
+    (ffi::AV_CHAN_TOP_SIDE_LEFT, ChannelPosition::TopSideLeft),
+    (ffi::AV_CHAN_TOP_SIDE_RIGHT, ChannelPosition::TopSideRight),
+    (
+        ffi::AV_CHAN_BOTTOM_FRONT_CENTER,
+        ChannelPosition::BottomFrontCenter,
+    ),
+    (
+        ffi::AV_CHAN_BOTTOM_FRONT_LEFT,
+        ChannelPosition::BottomFrontLeft,
+    ),
+    (
+        ffi::AV_CHAN_BOTTOM_FRONT_RIGHT,
+        ChannelPosition::BottomFrontRight,
+    ),
+];
+
+pub fn to_av_sample_format(format: AudioFormat, layout: SampleLayout) -> Option<AVSampleFormat> {
+    let (packed, planar) = match format {
+        AudioFormat::None => return None,
+        AudioFormat::U8 => (AV_SAMPLE_FMT_U8, AV_SAMPLE_FMT_U8P),
+        AudioFormat::I16 => (AV_SAMPLE_FMT_S16, AV_SAMPLE_FMT_S16P),
+        AudioFormat::I32 => (AV_SAMPLE_FMT_S32, AV_SAMPLE_FMT_S32P),
+        AudioFormat::I64 => (AV_SAMPLE_FMT_S64, AV_SAMPLE_FMT_S64P),
+        AudioFormat::F32 => (AV_SAMPLE_FMT_FLT, AV_SAMPLE_FMT_FLTP),
+        AudioFormat::F64 => (AV_SAMPLE_FMT_DBL, AV_SAMPLE_FMT_DBLP),
+    };
+    Some(match layout {
+        SampleLayout::Packed => packed,
+        SampleLayout::Planar => planar,
+    })
+}
+
+pub fn from_av_sample_format(format: AVSampleFormat) -> Option<(AudioFormat, SampleLayout)> {
+    Some(match format {
+        AV_SAMPLE_FMT_U8 => (AudioFormat::U8, SampleLayout::Packed),
+        AV_SAMPLE_FMT_U8P => (AudioFormat::U8, SampleLayout::Planar),
+        AV_SAMPLE_FMT_S16 => (AudioFormat::I16, SampleLayout::Packed),
+        AV_SAMPLE_FMT_S16P => (AudioFormat::I16, SampleLayout::Planar),
+        AV_SAMPLE_FMT_S32 => (AudioFormat::I32, SampleLayout::Packed),
+        AV_SAMPLE_FMT_S32P => (AudioFormat::I32, SampleLayout::Planar),
+        AV_SAMPLE_FMT_S64 => (AudioFormat::I64, SampleLayout::Packed),
+        AV_SAMPLE_FMT_S64P => (AudioFormat::I64, SampleLayout::Planar),
+        AV_SAMPLE_FMT_FLT => (AudioFormat::F32, SampleLayout::Packed),
+        AV_SAMPLE_FMT_FLTP => (AudioFormat::F32, SampleLayout::Planar),
+        AV_SAMPLE_FMT_DBL => (AudioFormat::F64, SampleLayout::Packed),
+        AV_SAMPLE_FMT_DBLP => (AudioFormat::F64, SampleLayout::Planar),
+        _ => return None,
+    })
 }
 
 pub type AudioDataContext = rmf_core::audio::AudioDataContext<
@@ -63,70 +300,156 @@ pub type AudioDataContext = rmf_core::audio::AudioDataContext<
 pub struct AudioDataContextBuilder;
 
 impl AudioDataContextBuilder {
-    pub fn try_new(av_frame: AVFrame) -> Result<AudioDataContext> {
+    pub fn try_new(av_frame: &AVFrame) -> Result<AudioDataContext> {
         match av_frame.format {
             AV_SAMPLE_FMT_NONE => Ok(AudioDataContext::None),
             AV_SAMPLE_FMT_U8 | AV_SAMPLE_FMT_U8P => {
-                Ok(AudioDataContext::U8(AudioData::<u8>::new(av_frame)))
+                Ok(AudioDataContext::U8(AudioData::<u8>::try_new(av_frame)?))
             }
             AV_SAMPLE_FMT_S16 | AV_SAMPLE_FMT_S16P => {
-                Ok(AudioDataContext::I16(AudioData::<i16>::new(av_frame)))
+                Ok(AudioDataContext::I16(AudioData::<i16>::try_new(av_frame)?))
             }
             AV_SAMPLE_FMT_S32 | AV_SAMPLE_FMT_S32P => {
-                Ok(AudioDataContext::I32(AudioData::<i32>::new(av_frame)))
+                Ok(AudioDataContext::I32(AudioData::<i32>::try_new(av_frame)?))
             }
             AV_SAMPLE_FMT_S64 | AV_SAMPLE_FMT_S64P => {
-                Ok(AudioDataContext::I64(AudioData::<i64>::new(av_frame)))
+                Ok(AudioDataContext::I64(AudioData::<i64>::try_new(av_frame)?))
             }
             AV_SAMPLE_FMT_FLT | AV_SAMPLE_FMT_FLTP => {
-                Ok(AudioDataContext::F32(AudioData::<f32>::new(av_frame)))
+                Ok(AudioDataContext::F32(AudioData::<f32>::try_new(av_frame)?))
             }
             AV_SAMPLE_FMT_DBL | AV_SAMPLE_FMT_DBLP => {
-                Ok(AudioDataContext::F64(AudioData::<f64>::new(av_frame)))
+                Ok(AudioDataContext::F64(AudioData::<f64>::try_new(av_frame)?))
             }
             _ => Err(Error::new_audio(anyhow!("Can't convert av frame"))),
         }
     }
 }
 
+/// Samples copied out of a decoded frame, so they outlive it. Clones share the memory.
 #[derive(Clone)]
 pub struct AudioData<T: Clone> {
-    data: Vec<Vec<T>>,
-    _phantom: PhantomData<T>,
+    layout: SampleLayout,
+    channels: usize,
+    samples: usize,
+    planes: Arc<[Vec<T>]>,
 }
 
 impl<T: Clone> AudioData<T> {
-    pub(crate) fn new(audio_av_frame: AVFrame) -> Self {
-        let mut data = Vec::with_capacity(audio_av_frame.ch_layout().nb_channels as _);
-        for i in 0..audio_av_frame.ch_layout().nb_channels as _ {
-            let len = audio_av_frame.linesize[0] as usize;
-            let ch_data = unsafe {
-                Vec::from_raw_parts(*audio_av_frame.extended_data.add(i) as *mut T, len, len)
+    pub(crate) fn try_new(audio_av_frame: &AVFrame) -> Result<Self> {
+        let format = audio_av_frame.format;
+        if unsafe { ffi::av_get_bytes_per_sample(format) } as usize != size_of::<T>() {
+            return Err(Error::new_audio(anyhow!(
+                "sample format {format} doesn't hold {}-byte samples",
+                size_of::<T>()
+            )));
+        }
+        let channels = audio_av_frame.ch_layout().nb_channels as usize;
+        let samples = audio_av_frame.nb_samples as usize;
+        let (layout, plane_count, plane_len) =
+            if unsafe { ffi::av_sample_fmt_is_planar(format) } != 0 {
+                (SampleLayout::Planar, channels, samples)
+            } else {
+                (SampleLayout::Packed, 1, samples * channels)
             };
-            data.push(ch_data);
+        let planes = (0..plane_count)
+            .map(|i| {
+                let plane = unsafe { *audio_av_frame.extended_data.add(i) };
+                if plane.is_null() {
+                    return Err(Error::new_audio(anyhow!("frame has no plane {i}")));
+                }
+                Ok(unsafe { slice::from_raw_parts(plane as *const T, plane_len) }.to_vec())
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            layout,
+            channels,
+            samples,
+            planes: planes.into(),
+        })
+    }
+
+    /// Copies the samples in `samples`, which must lie within the data.
+    fn slice(&self, samples: Range<usize>) -> Result<Self> {
+        match self.layout {
+            SampleLayout::Planar => Self::from_channels(
+                self.planes
+                    .iter()
+                    .map(|plane| plane[samples.clone()].to_vec())
+                    .collect(),
+            ),
+            SampleLayout::Packed => Self::from_packed(
+                self.channels,
+                self.planes[0][samples.start * self.channels..samples.end * self.channels].to_vec(),
+            ),
         }
-        Self {
-            data,
-            _phantom: PhantomData::<T>,
+    }
+
+    /// Planar data from one line per channel; fails unless every line holds the same number of samples.
+    pub fn from_channels(data: Vec<Vec<T>>) -> Result<Self> {
+        let samples = data.first().map_or(0, Vec::len);
+        if data.iter().any(|line| line.len() != samples) {
+            return Err(Error::new_audio(anyhow!(
+                "channel lines hold different numbers of samples"
+            )));
         }
+        Ok(Self {
+            layout: SampleLayout::Planar,
+            channels: data.len(),
+            samples,
+            planes: data.into(),
+        })
+    }
+
+    /// Packed data from interleaved samples of `channels` channels;
+    /// fails unless the length is a multiple of `channels`.
+    pub fn from_packed(channels: usize, data: Vec<T>) -> Result<Self> {
+        let samples = match data.len().checked_div(channels) {
+            Some(samples) if samples * channels == data.len() => samples,
+            None if data.is_empty() => 0,
+            _ => {
+                return Err(Error::new_audio(anyhow!(
+                    "{} samples don't interleave into {channels} channels",
+                    data.len()
+                )));
+            }
+        };
+        Ok(Self {
+            layout: SampleLayout::Packed,
+            channels,
+            samples,
+            planes: Arc::new([data]),
+        })
     }
 }
 
 impl<T: Clone> rmf_core::audio::AudioData for AudioData<T> {
     type Item = T;
+    #[inline]
+    fn layout(&self) -> SampleLayout {
+        self.layout
+    }
+    #[inline]
     fn channels_len(&self) -> usize {
-        self.data.len()
+        self.channels
+    }
+    #[inline]
+    fn samples_len(&self) -> usize {
+        self.samples
     }
     fn get_channel_line(&self, index: usize) -> Option<&[T]> {
-        if index < self.channels_len() {
-            Some(self.data[index].as_slice())
+        if self.layout == SampleLayout::Planar {
+            self.plane(index)
         } else {
             None
         }
     }
+    fn plane(&self, index: usize) -> Option<&[T]> {
+        self.planes.get(index).map(|plane| plane.as_slice())
+    }
     fn iter(&self) -> impl Iterator<Item = &'_ [Self::Item]> {
         AudioIterator::<'_, T> {
-            iter: self.data.iter(),
+            iter: self.planes.iter(),
         }
     }
 }
@@ -141,3 +464,43 @@ impl<'a, T: Clone> Iterator for AudioIterator<'a, T> {
         self.iter.next().map(|v| v.as_slice())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(vec![vec![0.0; 4], vec![0.0; 4]], Some(4))]
+    #[case(vec![], Some(0))]
+    #[case(vec![vec![0.0; 4], vec![0.0; 3]], None)]
+    fn from_channels_rejects_ragged_lines(
+        #[case] data: Vec<Vec<f32>>,
+        #[case] expected: Option<usize>,
+    ) {
+        assert_eq!(
+            AudioData::from_channels(data).ok().map(|d| d.samples_len()),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case(2, 6, Some(3))]
+    #[case(2, 0, Some(0))]
+    #[case(0, 0, Some(0))]
+    #[case(2, 5, None)]
+    #[case(0, 2, None)]
+    fn from_packed_rejects_partial_samples(
+        #[case] channels: usize,
+        #[case] len: usize,
+        #[case] expected: Option<usize>,
+    ) {
+        assert_eq!(
+            AudioData::from_packed(channels, vec![0i16; len])
+                .ok()
+                .map(|d| d.samples_len()),
+            expected
+        );
+    }
+}
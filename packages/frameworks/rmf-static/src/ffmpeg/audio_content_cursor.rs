@@ -1,38 +1,121 @@
 use std::collections::VecDeque;
 
 use anyhow::anyhow;
-use rmf_core::{Content, Error, Result, Timestamp};
-use rmf_macros::delegate_implements;
-use rsmpeg::{
-    avformat::AVFormatContextInput, avutil::AVFrame, error::RsmpegError, ffi::AVMEDIA_TYPE_AUDIO,
+use rmf_core::{
+    Content, Error, Rational, RationalTimestamp, Result, SeekMode, Timestamp,
+    audio::{Audio as _, AudioCursorOptions},
 };
+use rmf_macros::delegate_implements;
+use rsmpeg::{avformat::AVFormatContextInput, error::RsmpegError, ffi::AVMEDIA_TYPE_AUDIO};
 
 use crate::{
-    Audio, AudioDataContextBuilder,
-    ffmpeg::utils::{AVFormatContentContexts, input_contexts, seek_input, to_timestamp},
+    Audio,
+    ffmpeg::{
+        AudioConverter,
+        utils::{AVFormatContentContexts, input_contexts, seek_input, to_rational},
+    },
+    sample_position,
 };
 
 pub struct AVFormatAudioContentCursor {
     input: AVFormatContextInput,
     offset: Timestamp,
+    native_offset: RationalTimestamp,
+    time_base: Rational,
     audio_context: AVFormatContentContexts,
     audio_cache: VecDeque<Content<Audio>>,
+    converter: Option<Box<AudioConverter>>,
+    /// Where the next converted audio starts. The resampler holds samples back, so converted audio is
+    /// timed by counting samples from the first frame it was given rather than by frame pts.
+    converted_end: Option<RationalTimestamp>,
+    drained: bool,
 }
 
 impl AVFormatAudioContentCursor {
-    pub fn try_new(input: AVFormatContextInput) -> Result<Self> {
-        let audio_context = input_contexts(&input, AVMEDIA_TYPE_AUDIO)?
+    pub fn try_new(
+        input: AVFormatContextInput,
+        stream_index: usize,
+        options: AudioCursorOptions,
+    ) -> Result<Self> {
+        let audio_context = input_contexts(&input, AVMEDIA_TYPE_AUDIO, Some(stream_index))?
             .ok_or_else(|| Error::new_input(anyhow!("Can not make input context")))?;
+        let converter = (!options.is_native()).then(|| Box::new(AudioConverter::new(options)));
+        let time_base = to_rational(audio_context.time_base);
         Ok(Self {
             input,
             offset: Timestamp::default(),
+            native_offset: RationalTimestamp::new(0, time_base),
+            time_base,
             audio_context,
             audio_cache: VecDeque::default(),
+            converter,
+            converted_end: None,
+            drained: false,
         })
     }
-    fn avframe_to_audio(frame: AVFrame) -> Result<Audio> {
-        let data_context = AudioDataContextBuilder::try_new(frame)?;
-        Audio::tyr_new(data_context)
+    /// Reads and discards content until the block covering `timestamp`, which is put back for the next read
+    /// without the samples before `timestamp`.
+    fn skip_to(&mut self, timestamp: Timestamp) -> Result<()> {
+        while let Some(content) = self.read()? {
+            let covers = if content.duration() == Timestamp::default() {
+                content.offset() >= timestamp
+            } else {
+                content.offset() + content.duration() > timestamp
+            };
+            if covers {
+                let content = trim_start(content, timestamp)?;
+                self.offset = content.offset();
+                self.native_offset = content.native_offset();
+                self.audio_cache.push_front(content);
+                break;
+            }
+        }
+        Ok(())
+    }
+    /// Appends the samples the resampler still holds right after the last content.
+    fn flush_resampler(&mut self) -> Result<()> {
+        let Some(audio) = self
+            .converter
+            .as_mut()
+            .map(|converter| converter.flush())
+            .transpose()?
+            .flatten()
+        else {
+            return Ok(());
+        };
+        let offset = self.converted_end.unwrap_or(self.native_offset);
+        let duration = audio_duration(&audio);
+        self.audio_cache
+            .push_back(Content::from_native(audio, offset, duration));
+        Ok(())
+    }
+    fn receive_frames(&mut self) -> Result<()> {
+        loop {
+            match self.audio_context.avcodec_context.receive_frame() {
+                Ok(frame) => {
+                    let presentation_timestamp = RationalTimestamp::new(frame.pts, self.time_base);
+                    let (audio, offset) = match &mut self.converter {
+                        Some(converter) => (
+                            converter.convert_frame(&frame)?,
+                            *self.converted_end.get_or_insert(presentation_timestamp),
+                        ),
+                        None => (Some(Audio::try_from_frame(&frame)?), presentation_timestamp),
+                    };
+                    if let Some(audio) = audio {
+                        let duration = audio_duration(&audio);
+                        if self.converter.is_some() {
+                            self.converted_end = Some(offset + duration);
+                        }
+                        self.audio_cache
+                            .push_back(Content::from_native(audio, offset, duration));
+                    }
+                }
+                Err(RsmpegError::DecoderFlushedError | RsmpegError::DecoderDrainError) => {
+                    return Ok(());
+                }
+                Err(err) => return Err(Error::new_audio(err.into())),
+            }
+        }
     }
 }
 
@@ -43,59 +126,145 @@ impl rmf_core::audio::AudioContentCursor for AVFormatAudioContentCursor {
     fn offset(&self) -> Timestamp {
         self.offset
     }
+    #[inline]
+    fn time_base(&self) -> Rational {
+        self.time_base
+    }
+    #[inline]
+    fn native_offset(&self) -> RationalTimestamp {
+        self.native_offset
+    }
     fn read(&mut self) -> Result<Option<Content<Audio>>> {
-        if let Some(content) = self.audio_cache.pop_front() {
-            Ok(Some(content))
-        } else {
-            while let Some(packet) = self
+        while self.audio_cache.is_empty() && !self.drained {
+            match self
                 .input
                 .read_packet()
                 .map_err(|e| Error::new_audio(e.into()))?
             {
-                if packet.stream_index == self.audio_context.index as _ {
+                Some(packet) if packet.stream_index == self.audio_context.index as _ => {
                     self.audio_context
                         .avcodec_context
                         .send_packet(Some(&packet))
                         .map_err(|e| Error::new_audio(e.into()))?;
-                    loop {
-                        match self.audio_context.avcodec_context.receive_frame() {
-                            Ok(frame) => {
-                                let presentation_timestamp =
-                                    to_timestamp(frame.pts, frame.time_base);
-                                let duration_timestamp =
-                                    to_timestamp(frame.duration, frame.time_base);
-                                let audio = Self::avframe_to_audio(frame)?;
-                                self.audio_cache.push_back(Content::new(
-                                    audio,
-                                    presentation_timestamp,
-                                    duration_timestamp,
-                                ));
-                            }
-                            Err(err) => {
-                                if err == RsmpegError::DecoderFlushedError
-                                    || err == RsmpegError::DecoderDrainError
-                                {
-                                    break;
-                                } else {
-                                    Err(Error::new_input(err.into()))?
-                                }
-                            }
-                        }
-                    }
-                    break;
+                    self.receive_frames()?;
+                }
+                Some(_) => {}
+                None => {
+                    // Lets the decoder release the samples it still holds because of codec delay.
+                    self.audio_context
+                        .avcodec_context
+                        .send_packet(None)
+                        .map_err(|e| Error::new_audio(e.into()))?;
+                    self.receive_frames()?;
+                    self.flush_resampler()?;
+                    self.drained = true;
                 }
-            }
-
-            if let Some(audio) = self.audio_cache.pop_front() {
-                self.offset = audio.offset();
-                Ok(Some(audio))
-            } else {
-                Ok(None)
             }
         }
+        if let Some(audio) = self.audio_cache.pop_front() {
+            self.offset = audio.offset();
+            self.native_offset = audio.native_offset();
+            Ok(Some(audio))
+        } else {
+            Ok(None)
+        }
     }
     #[inline]
     fn seek(&mut self, timestamp: Timestamp) -> Result<()> {
-        seek_input(&mut self.input, timestamp)
+        self.seek_with_mode(timestamp, SeekMode::Keyframe)
+    }
+    fn seek_with_mode(&mut self, timestamp: Timestamp, mode: SeekMode) -> Result<()> {
+        seek_input(&mut self.input, &mut self.audio_context, timestamp)?;
+        self.audio_cache.clear();
+        if let Some(converter) = &mut self.converter {
+            converter.reset();
+        }
+        self.converted_end = None;
+        self.drained = false;
+        if mode == SeekMode::Exact {
+            self.skip_to(timestamp)?;
+        }
+        Ok(())
+    }
+}
+
+/// Drops the samples before `timestamp`, so the content starts at the sample nearest to it.
+fn trim_start(content: Content<Audio>, timestamp: Timestamp) -> Result<Content<Audio>> {
+    let audio = content.item();
+    let len = audio.samples_len();
+    let skip =
+        sample_position(timestamp - content.offset(), audio.sample_rate()).clamp(0, len as i64);
+    if skip == 0 {
+        return Ok(content);
+    }
+    let trimmed = audio.slice(skip as usize..len)?;
+    let offset = content.native_offset()
+        + RationalTimestamp::new(skip, Rational::new(1, audio.sample_rate() as i64));
+    let duration = audio_duration(&trimmed);
+    Ok(Content::from_native(trimmed, offset, duration))
+}
+
+/// Exact, counted in samples.
+#[inline]
+fn audio_duration(audio: &Audio) -> RationalTimestamp {
+    match audio.sample_rate() {
+        0 => RationalTimestamp::new(0, Rational::MICROSECOND),
+        sample_rate => RationalTimestamp::new(
+            audio.samples_len() as i64,
+            Rational::new(1, sample_rate as i64),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AudioData, AudioDataContext};
+    use pretty_assertions::assert_eq;
+    use rmf_core::audio::ChannelLayout;
+    use rstest::rstest;
+
+    /// Ten mono samples at 100 Hz starting at one second, each holding its own index.
+    fn content() -> Content<Audio> {
+        let audio = Audio::tyr_new(
+            AudioDataContext::F32(
+                AudioData::from_channels(vec![(0..10).map(|i| i as f32).collect()]).unwrap(),
+            ),
+            100,
+            ChannelLayout::mono(),
+        )
+        .unwrap();
+        Content::from_native(
+            audio,
+            RationalTimestamp::new(100, Rational::new(1, 100)),
+            RationalTimestamp::new(10, Rational::new(1, 100)),
+        )
+    }
+
+    #[rstest]
+    #[case(900, 1000, 0)]
+    #[case(1000, 1000, 0)]
+    #[case(1030, 1030, 3)]
+    #[case(1034, 1030, 3)]
+    #[case(1035, 1040, 4)]
+    #[case(1090, 1090, 9)]
+    fn trim_start_drops_samples_before_target(
+        #[case] target: i64,
+        #[case] expected_offset: i64,
+        #[case] expected_first: usize,
+    ) {
+        let trimmed = trim_start(content(), Timestamp::from_milliseconds(target)).unwrap();
+        assert_eq!(
+            trimmed.offset(),
+            Timestamp::from_milliseconds(expected_offset)
+        );
+        assert_eq!(
+            trimmed.offset() + trimmed.duration(),
+            Timestamp::from_milliseconds(1100)
+        );
+        assert_eq!(
+            trimmed.item().to_f32_lines(),
+            vec![(expected_first..10).map(|i| i as f32).collect::<Vec<_>>()]
+        );
     }
 }
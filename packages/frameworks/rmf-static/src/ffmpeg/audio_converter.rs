@@ -0,0 +1,187 @@
+use std::ptr;
+
+use anyhow::anyhow;
+use rmf_core::{
+    Error, Result,
+    audio::{Audio as _, AudioCursorOptions, AudioData as _, ChannelLayout, SampleLayout},
+};
+use rsmpeg::{avutil::AVFrame, ffi::AVSampleFormat, swresample::SwrContext};
+
+use crate::{
+    Audio, AudioData, AudioDataContext, AudioDataContextBuilder,
+    ffmpeg::audio::{
+        from_av_sample_format, to_av_channel_layout, to_av_sample_format, to_channel_layout,
+    },
+};
+
+/// Converts audio to the parameters of [`AudioCursorOptions`] with swresample.
+/// The resampler is rebuilt whenever the input parameters change.
+pub struct AudioConverter {
+    options: AudioCursorOptions,
+    converter: Option<Converter>,
+}
+
+struct Converter {
+    input: (AVSampleFormat, i32, ChannelLayout),
+    swr_context: SwrContext,
+    format: AVSampleFormat,
+    sample_rate: i32,
+    channel_layout: ChannelLayout,
+}
+
+impl Converter {
+    fn try_new(options: &AudioCursorOptions, frame: &AVFrame) -> Result<Self> {
+        let input_layout = to_channel_layout(&frame.ch_layout());
+        let (format, layout) = from_av_sample_format(frame.format)
+            .ok_or_else(|| Error::new_audio(anyhow!("unsupported sample format")))?;
+        let format = to_av_sample_format(
+            options.format.unwrap_or(format),
+            options.layout.unwrap_or(layout),
+        )
+        .ok_or_else(|| Error::new_audio(anyhow!("can't convert audio to no format")))?;
+        let sample_rate = options.sample_rate.map_or(frame.sample_rate, |r| r as _);
+        let channel_layout = options
+            .channel_layout
+            .clone()
+            .unwrap_or_else(|| input_layout.clone());
+        let mut swr_context = SwrContext::new(
+            &to_av_channel_layout(&channel_layout),
+            format,
+            sample_rate,
+            &frame.ch_layout(),
+            frame.format,
+            frame.sample_rate,
+        )
+        .map_err(|e| Error::new_audio(e.into()))?;
+        swr_context.init().map_err(|e| Error::new_audio(e.into()))?;
+        Ok(Self {
+            input: (frame.format, frame.sample_rate, input_layout),
+            swr_context,
+            format,
+            sample_rate,
+            channel_layout,
+        })
+    }
+
+    /// Converts `frame`, or flushes the samples held back for resampling when it is `None`.
+    fn convert(&self, frame: Option<&AVFrame>) -> Result<Option<Audio>> {
+        let mut output = AVFrame::new();
+        output.set_format(self.format);
+        output.set_ch_layout(to_av_channel_layout(&self.channel_layout).into_inner());
+        output.set_sample_rate(self.sample_rate);
+        self.swr_context
+            .convert_frame(frame, &mut output)
+            .map_err(|e| Error::new_audio(e.into()))?;
+        if output.nb_samples == 0 {
+            return Ok(None);
+        }
+        Audio::tyr_new(
+            AudioDataContextBuilder::try_new(&output)?,
+            self.sample_rate as _,
+            self.channel_layout.clone(),
+        )
+        .map(Some)
+    }
+}
+
+impl AudioConverter {
+    pub fn new(options: AudioCursorOptions) -> Self {
+        Self {
+            options,
+            converter: None,
+        }
+    }
+
+    #[inline]
+    pub fn options(&self) -> &AudioCursorOptions {
+        &self.options
+    }
+
+    /// Returns `Ok(None)` while the resampler holds back every sample it was given.
+    pub fn convert(&mut self, audio: &Audio) -> Result<Option<Audio>> {
+        self.convert_frame(&audio_to_avframe(audio)?)
+    }
+
+    pub(crate) fn convert_frame(&mut self, frame: &AVFrame) -> Result<Option<Audio>> {
+        let input = (
+            frame.format,
+            frame.sample_rate,
+            to_channel_layout(&frame.ch_layout()),
+        );
+        let converter = match &mut self.converter {
+            Some(converter) if converter.input == input => converter,
+            converter => converter.insert(Converter::try_new(&self.options, frame)?),
+        };
+        converter.convert(Some(frame))
+    }
+
+    /// Returns the samples held back for resampling once the input has ended.
+    pub fn flush(&mut self) -> Result<Option<Audio>> {
+        match &self.converter {
+            Some(converter) => converter.convert(None),
+            None => Ok(None),
+        }
+    }
+
+    /// Drops the held back samples, e.g. after seeking.
+    #[inline]
+    pub fn reset(&mut self) {
+        self.converter = None;
+    }
+}
+
+/// Allocates a frame with the audio's parameters and copies every plane.
+pub(crate) fn audio_to_avframe(audio: &Audio) -> Result<AVFrame> {
+    /// Copies at most the frame's samples per plane, so inconsistent data can't overrun its buffers.
+    fn copy<T: Clone>(frame: &mut AVFrame, data: &AudioData<T>) -> Result<()> {
+        let channels = frame.ch_layout.nb_channels as usize;
+        let samples = frame.nb_samples as usize;
+        let (plane_count, plane_len) = match data.layout() {
+            SampleLayout::Planar => (channels, samples),
+            SampleLayout::Packed => (1, samples * channels),
+        };
+        if data.channels_len() != channels || data.iter().count() != plane_count {
+            return Err(Error::new_audio(anyhow!(
+                "audio has {} channels in {} planes but its layout has {channels}",
+                data.channels_len(),
+                data.iter().count()
+            )));
+        }
+        for (index, plane) in data.iter().enumerate() {
+            if plane.len() < plane_len {
+                return Err(Error::new_audio(anyhow!(
+                    "plane {index} holds {} of {plane_len} samples",
+                    plane.len()
+                )));
+            }
+            unsafe {
+                ptr::copy_nonoverlapping(
+                    plane.as_ptr(),
+                    *frame.extended_data.add(index) as *mut T,
+                    plane_len,
+                );
+            }
+        }
+        Ok(())
+    }
+    let format = to_av_sample_format(audio.format(), audio.layout())
+        .ok_or_else(|| Error::new_audio(anyhow!("audio has no sample format")))?;
+    let mut frame = AVFrame::new();
+    frame.set_format(format);
+    frame.set_ch_layout(to_av_channel_layout(audio.channel_layout()).into_inner());
+    frame.set_sample_rate(audio.sample_rate() as _);
+    frame.set_nb_samples(audio.samples_len() as _);
+    frame
+        .alloc_buffer()
+        .map_err(|e| Error::new_audio(e.into()))?;
+    match audio.data() {
+        AudioDataContext::None => {}
+        AudioDataContext::U8(d) => copy(&mut frame, d)?,
+        AudioDataContext::I16(d) => copy(&mut frame, d)?,
+        AudioDataContext::I32(d) => copy(&mut frame, d)?,
+        AudioDataContext::I64(d) => copy(&mut frame, d)?,
+        AudioDataContext::F32(d) => copy(&mut frame, d)?,
+        AudioDataContext::F64(d) => copy(&mut frame, d)?,
+    }
+    Ok(frame)
+}
@@ -1,9 +1,12 @@
 use crate::{
     core::{Error, Timestamp},
-    ffmpeg::utils::input_contexts,
+    ffmpeg::utils::{input_contexts, stream_infos},
 };
 use anyhow::anyhow;
-use rmf_core::{InputSource, Result, audio::AudioInput};
+use rmf_core::{
+    InputSource, Result, StreamInfo,
+    audio::{AudioCursorOptions, AudioInput},
+};
 use rmf_macros::delegate_implements;
 use rsmpeg::ffi::AVMEDIA_TYPE_AUDIO;
 
@@ -17,6 +20,8 @@ pub struct AVFormatAudioInput {
     source: InputSource,
     duration: Timestamp,
     sample_rate: u32,
+    streams: Vec<StreamInfo>,
+    stream_index: usize,
 }
 
 #[delegate_implements]
@@ -31,17 +36,40 @@ impl AudioInput for AVFormatAudioInput {
     fn sample_rate(&self) -> u32 {
         self.sample_rate
     }
+    #[inline]
+    fn streams(&self) -> &[StreamInfo] {
+        &self.streams
+    }
+    #[inline]
+    fn stream_index(&self) -> usize {
+        self.stream_index
+    }
 
     #[inline]
     fn cursor(&self) -> Result<AVFormatAudioContentCursor> {
-        AVFormatAudioContentCursor::try_new(make_input(&self.source)?)
+        self.cursor_with_options(AudioCursorOptions::default())
+    }
+    fn cursor_with_options(
+        &self,
+        options: AudioCursorOptions,
+    ) -> Result<AVFormatAudioContentCursor> {
+        AVFormatAudioContentCursor::try_new(make_input(&self.source)?, self.stream_index, options)
     }
 }
 
 impl AVFormatAudioInput {
     pub fn try_new(source: InputSource) -> Result<Self> {
+        Self::try_new_stream(source, None)
+    }
+
+    /// Reads the audio stream at container index `stream_index` instead of the best one.
+    pub fn try_new_with_stream(source: InputSource, stream_index: usize) -> Result<Self> {
+        Self::try_new_stream(source, Some(stream_index))
+    }
+
+    fn try_new_stream(source: InputSource, stream_index: Option<usize>) -> Result<Self> {
         let input = make_input(&source)?;
-        let context = input_contexts(&input, AVMEDIA_TYPE_AUDIO)?
+        let context = input_contexts(&input, AVMEDIA_TYPE_AUDIO, stream_index)?
             .ok_or_else(|| Error::new_input(anyhow!("not found audio stream.")))?;
         let audio_stream = &input.streams()[context.index];
 
@@ -49,6 +77,8 @@ impl AVFormatAudioInput {
             source,
             sample_rate: audio_stream.codecpar().sample_rate as _,
             duration: Timestamp::from_microseconds(input.duration),
+            streams: stream_infos(&input, AVMEDIA_TYPE_AUDIO),
+            stream_index: context.index,
         })
     }
 }
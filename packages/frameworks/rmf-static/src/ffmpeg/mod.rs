@@ -1,11 +1,18 @@
 pub mod audio;
 mod audio_content_cursor;
+mod audio_converter;
 mod audio_input;
+mod output;
+mod pixel;
+mod probe;
 mod utils;
 mod video_content_cursor;
 mod video_input;
 
 pub use audio_content_cursor::*;
+pub use audio_converter::*;
 pub use audio_input::*;
+pub use output::*;
+pub use probe::*;
 pub use video_content_cursor::*;
 pub use video_input::*;
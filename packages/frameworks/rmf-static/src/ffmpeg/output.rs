@@ -0,0 +1,522 @@
+use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+use anyhow::anyhow;
+use rmf_core::{
+    Content, Error, OutputDestination, OutputService, Result, Size,
+    audio::{Audio as _, AudioFormat, ChannelLayout, SampleLayout},
+    image::PixelFormat,
+    output::{
+        AudioCodec, AudioEncoderOptions, ContainerFormat, OutputOptions, VideoCodec,
+        VideoEncoderOptions,
+    },
+};
+use rmf_macros::delegate_implements;
+use rsmpeg::{
+    avcodec::{AVCodec, AVCodecContext, AVCodecRef},
+    avformat::AVFormatContextOutput,
+    avutil::{AVAudioFifo, AVChannelLayout, AVFrame, av_d2q, av_rescale_q, get_planar_sample_fmt},
+    error::RsmpegError,
+    ffi::{self, AV_TIME_BASE_Q, AVRational},
+    swresample::SwrContext,
+    swscale::SwsContext,
+};
+
+use crate::{
+    Audio, Image,
+    ffmpeg::{
+        audio::{to_av_sample_format, to_channel_layout},
+        audio_converter::audio_to_avframe,
+        pixel::{image_to_avframe, to_av_pixel_format},
+    },
+};
+
+/// Frame size used for encoders that accept any number of samples per frame.
+const VARIABLE_FRAME_SIZE: i32 = 1024;
+
+pub struct AVFormatOutput {
+    output: AVFormatContextOutput,
+    video: Option<VideoEncoder>,
+    audio: Option<AudioEncoder>,
+    finished: bool,
+}
+
+impl AVFormatOutput {
+    pub fn try_new(destination: OutputDestination, options: OutputOptions) -> Result<Self> {
+        let filename = match &destination {
+            OutputDestination::Path(path) => to_cstring(path.as_os_str().as_bytes())?,
+        };
+        let format_name = options
+            .format
+            .as_ref()
+            .map(|format| to_cstring(container_format_name(format).as_bytes()))
+            .transpose()?;
+        let mut output = AVFormatContextOutput::builder()
+            .filename(&filename)
+            .maybe_format_name(format_name.as_deref())
+            .build()
+            .map_err(|e| Error::new_output(e.into()))?;
+        let global_header = output.oformat().flags & ffi::AVFMT_GLOBALHEADER as i32 != 0;
+
+        let video = options
+            .video
+            .as_ref()
+            .map(|options| VideoEncoder::try_new(&mut output, options, global_header))
+            .transpose()?;
+        let audio = options
+            .audio
+            .as_ref()
+            .map(|options| AudioEncoder::try_new(&mut output, options, global_header))
+            .transpose()?;
+        if video.is_none() && audio.is_none() {
+            return Err(Error::new_output(anyhow!(
+                "no video or audio stream to write"
+            )));
+        }
+        output
+            .write_header(&mut None)
+            .map_err(|e| Error::new_output(e.into()))?;
+
+        Ok(Self {
+            output,
+            video,
+            audio,
+            finished: false,
+        })
+    }
+
+    #[inline]
+    fn ensure_writable(&self) -> Result<()> {
+        if self.finished {
+            Err(Error::new_output(anyhow!("output is already finished")))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[delegate_implements]
+impl OutputService for AVFormatOutput {
+    type Image = Image;
+    type Audio = Audio;
+    fn has_video(&self) -> bool {
+        self.video.is_some()
+    }
+    fn has_audio(&self) -> bool {
+        self.audio.is_some()
+    }
+    fn write_video(&mut self, content: &Content<Image>) -> Result<()> {
+        self.ensure_writable()?;
+        self.video
+            .as_mut()
+            .ok_or_else(|| Error::new_output(anyhow!("no video stream configured")))?
+            .write(&mut self.output, content)
+    }
+    fn write_audio(&mut self, content: &Content<Audio>) -> Result<()> {
+        self.ensure_writable()?;
+        self.audio
+            .as_mut()
+            .ok_or_else(|| Error::new_output(anyhow!("no audio stream configured")))?
+            .write(&mut self.output, content)
+    }
+    fn finish(&mut self) -> Result<()> {
+        self.ensure_writable()?;
+        self.finished = true;
+        if let Some(video) = &mut self.video {
+            video.flush(&mut self.output)?;
+        }
+        if let Some(audio) = &mut self.audio {
+            audio.flush(&mut self.output)?;
+        }
+        self.output
+            .write_trailer()
+            .map_err(|e| Error::new_output(e.into()))
+    }
+}
+
+impl Drop for AVFormatOutput {
+    fn drop(&mut self) {
+        if !self.finished {
+            let _ = self.finish();
+        }
+    }
+}
+
+struct VideoEncoder {
+    avcodec_context: AVCodecContext,
+    stream_index: usize,
+    scale_context: Option<(Size, PixelFormat, SwsContext)>,
+    last_pts: Option<i64>,
+}
+
+impl VideoEncoder {
+    fn try_new(
+        output: &mut AVFormatContextOutput,
+        options: &VideoEncoderOptions,
+        global_header: bool,
+    ) -> Result<Self> {
+        let codec = match &options.codec {
+            VideoCodec::Named(name) => AVCodec::find_encoder_by_name(&to_cstring(name.as_bytes())?),
+            codec => AVCodec::find_encoder(video_codec_id(codec)),
+        }
+        .ok_or_else(|| Error::new_output(anyhow!("{:?} encoder not found", options.codec)))?;
+        let mut avcodec_context = AVCodecContext::new(&codec);
+        let frame_rate = av_d2q(options.fps, 100_000);
+        avcodec_context.set_width(options.size.width as _);
+        avcodec_context.set_height(options.size.height as _);
+        avcodec_context.set_framerate(frame_rate);
+        avcodec_context.set_time_base(AVRational {
+            num: frame_rate.den,
+            den: frame_rate.num,
+        });
+        let pix_fmt = match options.pixel_format {
+            Some(pixel_format) => to_av_pixel_format(pixel_format),
+            None => avcodec_context
+                .get_supported_pix_fmts(Some(&codec))
+                .ok()
+                .and_then(|formats| formats.first().copied())
+                .unwrap_or(ffi::AV_PIX_FMT_YUV420P),
+        };
+        avcodec_context.set_pix_fmt(pix_fmt);
+        if let Some(bitrate) = options.bitrate {
+            avcodec_context.set_bit_rate(bitrate as _);
+        }
+        open_encoder(&mut avcodec_context, global_header)?;
+        let stream_index = add_stream(output, &avcodec_context);
+
+        Ok(Self {
+            avcodec_context,
+            stream_index,
+            scale_context: None,
+            last_pts: None,
+        })
+    }
+
+    fn write(
+        &mut self,
+        output: &mut AVFormatContextOutput,
+        content: &Content<Image>,
+    ) -> Result<()> {
+        let image = content.item();
+        let size = image.size();
+        let pixel_format = image.pixel_format();
+        let source = image_to_avframe(image).map_err(|e| Error::new_output(e.into()))?;
+        let sws_context = match &mut self.scale_context {
+            Some((scale_size, scale_format, sws_context))
+                if *scale_size == size && *scale_format == pixel_format =>
+            {
+                sws_context
+            }
+            scale_context => {
+                let sws_context = SwsContext::get_context(
+                    size.width as _,
+                    size.height as _,
+                    to_av_pixel_format(pixel_format),
+                    self.avcodec_context.width,
+                    self.avcodec_context.height,
+                    self.avcodec_context.pix_fmt,
+                    ffi::SWS_BICUBIC,
+                    None,
+                    None,
+                    None,
+                )
+                .ok_or_else(|| Error::new_output(anyhow!("Can't get sws context")))?;
+                &mut scale_context.insert((size, pixel_format, sws_context)).2
+            }
+        };
+
+        let mut frame = AVFrame::new();
+        frame.set_format(self.avcodec_context.pix_fmt);
+        frame.set_width(self.avcodec_context.width);
+        frame.set_height(self.avcodec_context.height);
+        frame
+            .alloc_buffer()
+            .map_err(|e| Error::new_output(e.into()))?;
+        sws_context
+            .scale_frame(&source, 0, size.height as _, &mut frame)
+            .map_err(|e| Error::new_output(e.into()))?;
+
+        let mut pts = av_rescale_q(
+            content.offset().as_microseconds(),
+            AV_TIME_BASE_Q,
+            self.avcodec_context.time_base,
+        );
+        // Encoders reject non increasing timestamps, which rounding can produce for close frames.
+        if let Some(last_pts) = self.last_pts
+            && pts <= last_pts
+        {
+            pts = last_pts + 1;
+        }
+        self.last_pts = Some(pts);
+        frame.set_pts(pts);
+        encode(
+            &mut self.avcodec_context,
+            self.stream_index,
+            output,
+            Some(&frame),
+        )
+    }
+
+    fn flush(&mut self, output: &mut AVFormatContextOutput) -> Result<()> {
+        encode(&mut self.avcodec_context, self.stream_index, output, None)
+    }
+}
+
+struct AudioEncoder {
+    avcodec_context: AVCodecContext,
+    stream_index: usize,
+    channels: i32,
+    /// The resampler and the input format, rate and layout it was built for.
+    resample_context: Option<((ffi::AVSampleFormat, i32, ChannelLayout), SwrContext)>,
+    fifo: AVAudioFifo,
+    next_pts: Option<i64>,
+}
+
+impl AudioEncoder {
+    fn try_new(
+        output: &mut AVFormatContextOutput,
+        options: &AudioEncoderOptions,
+        global_header: bool,
+    ) -> Result<Self> {
+        let codec = match &options.codec {
+            AudioCodec::Named(name) => AVCodec::find_encoder_by_name(&to_cstring(name.as_bytes())?),
+            codec => AVCodec::find_encoder(audio_codec_id(codec)),
+        }
+        .ok_or_else(|| Error::new_output(anyhow!("{:?} encoder not found", options.codec)))?;
+        let mut avcodec_context = AVCodecContext::new(&codec);
+        let channels = options.channels as i32;
+        let sample_fmt = choose_sample_format(&avcodec_context, &codec, options.sample_format);
+        avcodec_context.set_sample_fmt(sample_fmt);
+        avcodec_context.set_sample_rate(options.sample_rate as _);
+        avcodec_context.set_ch_layout(AVChannelLayout::from_nb_channels(channels).into_inner());
+        avcodec_context.set_time_base(AVRational {
+            num: 1,
+            den: options.sample_rate as _,
+        });
+        if let Some(bitrate) = options.bitrate {
+            avcodec_context.set_bit_rate(bitrate as _);
+        }
+        open_encoder(&mut avcodec_context, global_header)?;
+        let stream_index = add_stream(output, &avcodec_context);
+
+        Ok(Self {
+            fifo: AVAudioFifo::new(sample_fmt, channels, 1),
+            avcodec_context,
+            stream_index,
+            channels,
+            resample_context: None,
+            next_pts: None,
+        })
+    }
+
+    /// Resamples to the encoder's sample format, rate and layout, then encodes every complete encoder frame.
+    fn write(
+        &mut self,
+        output: &mut AVFormatContextOutput,
+        content: &Content<Audio>,
+    ) -> Result<()> {
+        if content.item().samples_len() == 0 {
+            return Ok(());
+        }
+        let input = audio_to_avframe(content.item())?;
+        let parameters = (
+            input.format,
+            input.sample_rate,
+            to_channel_layout(&input.ch_layout()),
+        );
+        if !matches!(&self.resample_context, Some((current, _)) if *current == parameters) {
+            // Samples the previous resampler holds back come before this content.
+            self.resample(None)?;
+            let mut swr_context = SwrContext::new(
+                &self.avcodec_context.ch_layout,
+                self.avcodec_context.sample_fmt,
+                self.avcodec_context.sample_rate,
+                &input.ch_layout,
+                input.format,
+                input.sample_rate,
+            )
+            .map_err(|e| Error::new_output(e.into()))?;
+            swr_context
+                .init()
+                .map_err(|e| Error::new_output(e.into()))?;
+            self.resample_context = Some((parameters, swr_context));
+        }
+
+        if self.next_pts.is_none() {
+            self.next_pts = Some(av_rescale_q(
+                content.offset().as_microseconds(),
+                AV_TIME_BASE_Q,
+                self.avcodec_context.time_base,
+            ));
+        }
+        self.resample(Some(&input))?;
+        self.encode_buffered(output, false)
+    }
+
+    /// Buffers `input` converted for the encoder, or the samples the resampler holds back when it is `None`.
+    fn resample(&mut self, input: Option<&AVFrame>) -> Result<()> {
+        let Some((_, swr_context)) = &self.resample_context else {
+            return Ok(());
+        };
+        let mut converted = AVFrame::new();
+        converted.set_format(self.avcodec_context.sample_fmt);
+        converted.set_ch_layout(AVChannelLayout::from_nb_channels(self.channels).into_inner());
+        converted.set_sample_rate(self.avcodec_context.sample_rate);
+        swr_context
+            .convert_frame(input, &mut converted)
+            .map_err(|e| Error::new_output(e.into()))?;
+        if converted.nb_samples == 0 {
+            return Ok(());
+        }
+        self.fifo.realloc(self.fifo.size() + converted.nb_samples);
+        unsafe {
+            self.fifo
+                .write(converted.extended_data as _, converted.nb_samples)
+        }
+        .map_err(|e| Error::new_output(e.into()))?;
+        Ok(())
+    }
+
+    fn encode_buffered(&mut self, output: &mut AVFormatContextOutput, flush: bool) -> Result<()> {
+        let frame_size = match self.avcodec_context.frame_size {
+            0 => VARIABLE_FRAME_SIZE,
+            frame_size => frame_size,
+        };
+        while self.fifo.size() >= frame_size || (flush && self.fifo.size() > 0) {
+            let nb_samples = frame_size.min(self.fifo.size());
+            let mut frame = AVFrame::new();
+            frame.set_format(self.avcodec_context.sample_fmt);
+            frame.set_ch_layout(AVChannelLayout::from_nb_channels(self.channels).into_inner());
+            frame.set_sample_rate(self.avcodec_context.sample_rate);
+            frame.set_nb_samples(nb_samples);
+            frame
+                .alloc_buffer()
+                .map_err(|e| Error::new_output(e.into()))?;
+            unsafe { self.fifo.read(frame.extended_data as _, nb_samples) }
+                .map_err(|e| Error::new_output(e.into()))?;
+            let pts = self.next_pts.unwrap_or_default();
+            frame.set_pts(pts);
+            self.next_pts = Some(pts + nb_samples as i64);
+            encode(
+                &mut self.avcodec_context,
+                self.stream_index,
+                output,
+                Some(&frame),
+            )?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self, output: &mut AVFormatContextOutput) -> Result<()> {
+        self.resample(None)?;
+        self.encode_buffered(output, true)?;
+        encode(&mut self.avcodec_context, self.stream_index, output, None)
+    }
+}
+
+#[inline]
+fn to_cstring(bytes: &[u8]) -> Result<CString> {
+    CString::new(bytes.to_vec()).map_err(|e| Error::new_output(e.into()))
+}
+
+fn open_encoder(avcodec_context: &mut AVCodecContext, global_header: bool) -> Result<()> {
+    if global_header {
+        avcodec_context.set_flags(avcodec_context.flags | ffi::AV_CODEC_FLAG_GLOBAL_HEADER as i32);
+    }
+    avcodec_context
+        .open(None)
+        .map_err(|e| Error::new_output(e.into()))?;
+    Ok(())
+}
+
+fn add_stream(output: &mut AVFormatContextOutput, avcodec_context: &AVCodecContext) -> usize {
+    let mut stream = output.new_stream();
+    stream.set_codecpar(avcodec_context.extract_codecpar());
+    stream.set_time_base(avcodec_context.time_base);
+    stream.index as _
+}
+
+/// Sends `frame` (or a flush request for `None`) and muxes every packet the encoder returns.
+fn encode(
+    avcodec_context: &mut AVCodecContext,
+    stream_index: usize,
+    output: &mut AVFormatContextOutput,
+    frame: Option<&AVFrame>,
+) -> Result<()> {
+    avcodec_context
+        .send_frame(frame)
+        .map_err(|e| Error::new_output(e.into()))?;
+    loop {
+        match avcodec_context.receive_packet() {
+            Ok(mut packet) => {
+                packet.set_stream_index(stream_index as _);
+                packet.rescale_ts(
+                    avcodec_context.time_base,
+                    output.streams()[stream_index].time_base,
+                );
+                output
+                    .interleaved_write_frame(&mut packet)
+                    .map_err(|e| Error::new_output(e.into()))?;
+            }
+            Err(RsmpegError::EncoderDrainError | RsmpegError::EncoderFlushedError) => {
+                return Ok(());
+            }
+            Err(err) => return Err(Error::new_output(err.into())),
+        }
+    }
+}
+
+/// Prefers the requested format in the layout (packed or planar) the encoder supports.
+fn choose_sample_format(
+    avcodec_context: &AVCodecContext,
+    codec: &AVCodecRef,
+    requested: Option<AudioFormat>,
+) -> ffi::AVSampleFormat {
+    let supported = avcodec_context
+        .get_supported_sample_fmts(Some(codec))
+        .unwrap_or(&[]);
+    match requested.and_then(|format| to_av_sample_format(format, SampleLayout::Packed)) {
+        Some(packed) => [Some(packed), get_planar_sample_fmt(packed)]
+            .into_iter()
+            .flatten()
+            .find(|format| supported.is_empty() || supported.contains(format))
+            .unwrap_or(packed),
+        None => supported
+            .first()
+            .copied()
+            .unwrap_or(ffi::AV_SAMPLE_FMT_FLTP),
+    }
+}
+
+fn container_format_name(format: &ContainerFormat) -> &str {
+    match format {
+        ContainerFormat::Mp4 => "mp4",
+        ContainerFormat::Mkv => "matroska",
+        ContainerFormat::WebM => "webm",
+        ContainerFormat::Mov => "mov",
+        ContainerFormat::Named(name) => name,
+    }
+}
+
+fn video_codec_id(codec: &VideoCodec) -> ffi::AVCodecID {
+    match codec {
+        VideoCodec::H264 => ffi::AV_CODEC_ID_H264,
+        VideoCodec::Hevc => ffi::AV_CODEC_ID_HEVC,
+        VideoCodec::Vp8 => ffi::AV_CODEC_ID_VP8,
+        VideoCodec::Vp9 => ffi::AV_CODEC_ID_VP9,
+        VideoCodec::Av1 => ffi::AV_CODEC_ID_AV1,
+        VideoCodec::ProRes => ffi::AV_CODEC_ID_PRORES,
+        VideoCodec::Named(_) => ffi::AV_CODEC_ID_NONE,
+    }
+}
+
+fn audio_codec_id(codec: &AudioCodec) -> ffi::AVCodecID {
+    match codec {
+        AudioCodec::Aac => ffi::AV_CODEC_ID_AAC,
+        AudioCodec::Opus => ffi::AV_CODEC_ID_OPUS,
+        AudioCodec::Vorbis => ffi::AV_CODEC_ID_VORBIS,
+        AudioCodec::Mp3 => ffi::AV_CODEC_ID_MP3,
+        AudioCodec::Flac => ffi::AV_CODEC_ID_FLAC,
+        AudioCodec::PcmS16Le => ffi::AV_CODEC_ID_PCM_S16LE,
+        AudioCodec::Named(_) => ffi::AV_CODEC_ID_NONE,
+    }
+}
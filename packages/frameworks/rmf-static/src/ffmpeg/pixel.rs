@@ -0,0 +1,125 @@
+use std::{ptr, slice, sync::Arc};
+
+use anyhow::anyhow;
+use rmf_core::{Error, Result, Size, image::PixelFormat, video::ScalingAlgorithm};
+use rsmpeg::{avutil::AVFrame, ffi};
+
+use crate::{Image, SharedPlanes};
+
+pub fn to_av_pixel_format(pixel_format: PixelFormat) -> ffi::AVPixelFormat {
+    match pixel_format {
+        PixelFormat::Rgba => ffi::AV_PIX_FMT_RGBA,
+        PixelFormat::Bgra => ffi::AV_PIX_FMT_BGRA,
+        PixelFormat::Rgb24 => ffi::AV_PIX_FMT_RGB24,
+        PixelFormat::Gray8 => ffi::AV_PIX_FMT_GRAY8,
+        PixelFormat::Yuv420p => ffi::AV_PIX_FMT_YUV420P,
+        PixelFormat::Yuv422p => ffi::AV_PIX_FMT_YUV422P,
+        PixelFormat::Yuv444p => ffi::AV_PIX_FMT_YUV444P,
+        PixelFormat::Nv12 => ffi::AV_PIX_FMT_NV12,
+        PixelFormat::Gray16 => ffi::AV_PIX_FMT_GRAY16LE,
+        PixelFormat::Rgba64 => ffi::AV_PIX_FMT_RGBA64LE,
+        PixelFormat::Yuv420p10 => ffi::AV_PIX_FMT_YUV420P10LE,
+        PixelFormat::Yuv422p10 => ffi::AV_PIX_FMT_YUV422P10LE,
+        PixelFormat::Yuv444p10 => ffi::AV_PIX_FMT_YUV444P10LE,
+        PixelFormat::P010 => ffi::AV_PIX_FMT_P010LE,
+    }
+}
+
+pub fn from_av_pixel_format(pixel_format: ffi::AVPixelFormat) -> Option<PixelFormat> {
+    Some(match pixel_format {
+        ffi::AV_PIX_FMT_RGBA => PixelFormat::Rgba,
+        ffi::AV_PIX_FMT_BGRA => PixelFormat::Bgra,
+        ffi::AV_PIX_FMT_RGB24 => PixelFormat::Rgb24,
+        ffi::AV_PIX_FMT_GRAY8 => PixelFormat::Gray8,
+        ffi::AV_PIX_FMT_YUV420P => PixelFormat::Yuv420p,
+        ffi::AV_PIX_FMT_YUV422P => PixelFormat::Yuv422p,
+        ffi::AV_PIX_FMT_YUV444P => PixelFormat::Yuv444p,
+        ffi::AV_PIX_FMT_NV12 => PixelFormat::Nv12,
+        ffi::AV_PIX_FMT_GRAY16LE => PixelFormat::Gray16,
+        ffi::AV_PIX_FMT_RGBA64LE => PixelFormat::Rgba64,
+        ffi::AV_PIX_FMT_YUV420P10LE => PixelFormat::Yuv420p10,
+        ffi::AV_PIX_FMT_YUV422P10LE => PixelFormat::Yuv422p10,
+        ffi::AV_PIX_FMT_YUV444P10LE => PixelFormat::Yuv444p10,
+        ffi::AV_PIX_FMT_P010LE => PixelFormat::P010,
+        _ => return None,
+    })
+}
+
+pub fn to_sws_flags(scaling: ScalingAlgorithm) -> u32 {
+    match scaling {
+        ScalingAlgorithm::FastBilinear => ffi::SWS_FAST_BILINEAR,
+        ScalingAlgorithm::Bilinear => ffi::SWS_BILINEAR,
+        ScalingAlgorithm::Bicubic => ffi::SWS_BICUBIC,
+        ScalingAlgorithm::Nearest => ffi::SWS_POINT,
+        ScalingAlgorithm::Area => ffi::SWS_AREA,
+        ScalingAlgorithm::Lanczos => ffi::SWS_LANCZOS,
+        ScalingAlgorithm::Spline => ffi::SWS_SPLINE,
+    }
+}
+
+/// Keeps a decoded frame alive so images can borrow its planes.
+struct FramePlanes {
+    frame: AVFrame,
+    lengths: Vec<usize>,
+}
+
+// The frame is never written to once wrapped, so sharing references across threads is fine.
+unsafe impl Sync for FramePlanes {}
+
+impl SharedPlanes for FramePlanes {
+    fn plane(&self, index: usize) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.frame.data[index], self.lengths[index]) }
+    }
+    fn stride(&self, index: usize) -> usize {
+        self.frame.linesize[index] as usize
+    }
+}
+
+/// Wraps every plane of `frame`, which must hold pixels of `format`, without copying them.
+pub fn avframe_to_image(frame: AVFrame, format: PixelFormat) -> Result<Image> {
+    let size = Size::new(frame.width as _, frame.height as _);
+    let lengths = (0..format.plane_count())
+        .map(|index| {
+            let (row_bytes, rows) = format.plane_layout(index, size).unwrap_or_default();
+            if frame.data[index].is_null() {
+                return Err(Error::new_video(anyhow!("frame has no plane {index}")));
+            }
+            if frame.linesize[index] < 0 {
+                return Err(Error::new_video(anyhow!(
+                    "bottom-up frames are not supported"
+                )));
+            }
+            Ok(frame.linesize[index] as usize * rows.saturating_sub(1) + row_bytes)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Image::from_shared_planes(format, size, Arc::new(FramePlanes { frame, lengths }))
+}
+
+/// Allocates a frame in the image's format and copies every plane row by row.
+pub fn image_to_avframe(image: &Image) -> Result<AVFrame> {
+    let format = image.pixel_format();
+    let size = image.size();
+    let mut frame = AVFrame::new();
+    frame.set_format(to_av_pixel_format(format));
+    frame.set_width(size.width as _);
+    frame.set_height(size.height as _);
+    frame
+        .alloc_buffer()
+        .map_err(|e| Error::new_video(e.into()))?;
+    for index in 0..format.plane_count() {
+        let (row_bytes, rows) = format.plane_layout(index, size).unwrap_or_default();
+        let (Some(data), Some(stride)) = (image.plane(index), image.stride(index)) else {
+            return Err(Error::new_video(anyhow!("image has no plane {index}")));
+        };
+        for row in 0..rows {
+            unsafe {
+                ptr::copy_nonoverlapping(
+                    data[row * stride..].as_ptr(),
+                    frame.data[index].add(row * frame.linesize[index] as usize),
+                    row_bytes,
+                );
+            }
+        }
+    }
+    Ok(frame)
+}
@@ -0,0 +1,103 @@
+use std::{
+    collections::BTreeMap,
+    ffi::{CStr, c_char},
+};
+
+use rmf_core::{
+    InputSource, Rational, Result, Size, Timestamp,
+    probe::{AudioStreamReport, MediaType, ProbeReport, StreamReport, VideoStreamReport},
+};
+use rsmpeg::{
+    avformat::AVStream,
+    avutil::{AVDictionary, get_pix_fmt_name, get_sample_fmt_name},
+    ffi::{self, AV_NOPTS_VALUE, av_q2d},
+};
+
+use crate::ffmpeg::utils::{make_input, stream_info, to_timestamp};
+
+/// Reads the container header and stream parameters of `source` without decoding.
+pub fn probe_input(source: &InputSource) -> Result<ProbeReport> {
+    let input = make_input(source)?;
+    let format = unsafe { CStr::from_ptr(input.iformat().name) };
+    Ok(ProbeReport {
+        format: format.to_string_lossy().into_owned(),
+        duration: (input.duration != AV_NOPTS_VALUE)
+            .then(|| Timestamp::from_microseconds(input.duration)),
+        start_time: (input.start_time != AV_NOPTS_VALUE)
+            .then(|| Timestamp::from_microseconds(input.start_time)),
+        bitrate: positive(input.bit_rate),
+        tags: input.metadata().map(|m| tags(&m)).unwrap_or_default(),
+        streams: input.streams().iter().map(|s| stream_report(s)).collect(),
+    })
+}
+
+fn stream_report(stream: &AVStream) -> StreamReport {
+    let codecpar = stream.codecpar();
+    let media_type = match codecpar.codec_type {
+        ffi::AVMEDIA_TYPE_VIDEO => MediaType::Video,
+        ffi::AVMEDIA_TYPE_AUDIO => MediaType::Audio,
+        ffi::AVMEDIA_TYPE_SUBTITLE => MediaType::Subtitle,
+        ffi::AVMEDIA_TYPE_DATA => MediaType::Data,
+        ffi::AVMEDIA_TYPE_ATTACHMENT => MediaType::Attachment,
+        _ => MediaType::Unknown,
+    };
+    let video = (media_type == MediaType::Video).then(|| VideoStreamReport {
+        size: Size::new(codecpar.width as _, codecpar.height as _),
+        pixel_format: get_pix_fmt_name(codecpar.format).map(to_string),
+        fps: (stream.avg_frame_rate.den != 0 && stream.avg_frame_rate.num != 0)
+            .then(|| av_q2d(stream.avg_frame_rate)),
+        frame_rate: Rational::checked_new(
+            stream.avg_frame_rate.num as _,
+            stream.avg_frame_rate.den as _,
+        )
+        .filter(|rate| *rate > Rational::ZERO),
+    });
+    let audio = (media_type == MediaType::Audio).then(|| AudioStreamReport {
+        sample_rate: codecpar.sample_rate as _,
+        channels: codecpar.ch_layout.nb_channels as _,
+        channel_layout: codecpar
+            .ch_layout()
+            .describe()
+            .ok()
+            .map(|layout| to_string(&layout)),
+        sample_format: get_sample_fmt_name(codecpar.format).map(to_string),
+    });
+    let profile = unsafe { ffi::avcodec_profile_name(codecpar.codec_id, codecpar.profile) };
+
+    StreamReport {
+        info: stream_info(stream),
+        media_type,
+        profile: to_optional_string(profile),
+        bitrate: positive(codecpar.bit_rate),
+        frame_count: positive(stream.nb_frames),
+        start_time: (stream.start_time != AV_NOPTS_VALUE)
+            .then(|| to_timestamp(stream.start_time, stream.time_base)),
+        duration: (stream.duration != AV_NOPTS_VALUE)
+            .then(|| to_timestamp(stream.duration, stream.time_base)),
+        tags: stream.metadata().map(|m| tags(&m)).unwrap_or_default(),
+        video,
+        audio,
+    }
+}
+
+fn tags(metadata: &AVDictionary) -> BTreeMap<String, String> {
+    metadata
+        .iter()
+        .map(|entry| (to_string(entry.key()), to_string(entry.value())))
+        .collect()
+}
+
+#[inline]
+fn positive(value: i64) -> Option<u64> {
+    (value > 0).then_some(value as _)
+}
+
+#[inline]
+fn to_string(value: &CStr) -> String {
+    value.to_string_lossy().into_owned()
+}
+
+#[inline]
+fn to_optional_string(value: *const c_char) -> Option<String> {
+    (!value.is_null()).then(|| to_string(unsafe { CStr::from_ptr(value) }))
+}
@@ -1,31 +1,153 @@
-use std::{ffi::CString, os::unix::ffi::OsStrExt, path::Path};
+use std::{
+    ffi::{CStr, CString},
+    io::{self, SeekFrom},
+    os::unix::ffi::OsStrExt,
+    path::Path,
+    sync::{Arc, Mutex},
+};
 
-use rmf_core::{Error, InputSource, Result, Timestamp};
+use anyhow::anyhow;
+use rmf_core::{Error, InputReader, InputSource, Rational, Result, StreamInfo, Timestamp};
 use rsmpeg::{
     avcodec::{AVCodec, AVCodecContext},
-    avformat::AVFormatContextInput,
-    avutil::av_rescale_q,
+    avformat::{AVFormatContextInput, AVIOContextContainer, AVIOContextCustom, AVStream},
+    avutil::{AVMem, av_rescale_q},
     ffi::{self, AV_TIME_BASE_Q, AVRational, AVSEEK_FLAG_BACKWARD},
 };
 
+const AVIO_BUFFER_SIZE: usize = 32 * 1024;
+
 #[inline]
 fn try_from_path_input(path: impl AsRef<Path>) -> Result<AVFormatContextInput> {
     let path = path.as_ref();
     AVFormatContextInput::open(&CString::new(path.as_os_str().as_bytes().to_vec()).unwrap())
         .map_err(|e| Error::new_input(e.into()))
 }
+
+enum SharedSource {
+    Bytes(Arc<[u8]>),
+    Reader(Arc<Mutex<dyn InputReader>>),
+}
+
+impl SharedSource {
+    fn read_at(&self, position: u64, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Self::Bytes(bytes) => {
+                let start = (position as usize).min(bytes.len());
+                let len = buf.len().min(bytes.len() - start);
+                buf[..len].copy_from_slice(&bytes[start..start + len]);
+                Ok(len)
+            }
+            Self::Reader(reader) => {
+                let mut reader = reader
+                    .lock()
+                    .map_err(|_| io::Error::other("input reader is poisoned"))?;
+                reader.seek(SeekFrom::Start(position))?;
+                reader.read(buf)
+            }
+        }
+    }
+    fn size(&self) -> io::Result<u64> {
+        match self {
+            Self::Bytes(bytes) => Ok(bytes.len() as _),
+            Self::Reader(reader) => reader
+                .lock()
+                .map_err(|_| io::Error::other("input reader is poisoned"))?
+                .seek(SeekFrom::End(0)),
+        }
+    }
+}
+
+/// A read position over a [`SharedSource`], owned by one AVIO context.
+struct SharedCursor {
+    source: SharedSource,
+    position: u64,
+}
+
+impl SharedCursor {
+    fn read(&mut self, buf: &mut [u8]) -> i32 {
+        match self.source.read_at(self.position, buf) {
+            Ok(0) => ffi::AVERROR_EOF,
+            Ok(len) => {
+                self.position += len as u64;
+                len as _
+            }
+            Err(_) => ffi::AVERROR(ffi::EIO),
+        }
+    }
+    fn seek(&mut self, offset: i64, whence: i32) -> i64 {
+        if whence & ffi::AVSEEK_SIZE as i32 != 0 {
+            return self
+                .source
+                .size()
+                .map_or(ffi::AVERROR(ffi::EIO) as _, |size| size as _);
+        }
+        let base = match (whence & !(ffi::AVSEEK_FORCE as i32)) as u32 {
+            ffi::SEEK_SET => 0,
+            ffi::SEEK_CUR => self.position as i64,
+            ffi::SEEK_END => match self.source.size() {
+                Ok(size) => size as i64,
+                Err(_) => return ffi::AVERROR(ffi::EIO) as _,
+            },
+            _ => return ffi::AVERROR(ffi::EINVAL) as _,
+        };
+        match base.checked_add(offset) {
+            Some(position) if position >= 0 => {
+                self.position = position as _;
+                position
+            }
+            _ => ffi::AVERROR(ffi::EINVAL) as _,
+        }
+    }
+}
+
+fn try_from_shared_input(source: SharedSource) -> Result<AVFormatContextInput> {
+    let cursor = Arc::new(Mutex::new(SharedCursor {
+        source,
+        position: 0,
+    }));
+    let read_cursor = cursor.clone();
+    let io_context = AVIOContextCustom::alloc_context(
+        AVMem::new(AVIO_BUFFER_SIZE),
+        false,
+        vec![],
+        Some(Box::new(move |_, buf| {
+            read_cursor
+                .lock()
+                .map_or(ffi::AVERROR(ffi::EIO), |mut c| c.read(buf))
+        })),
+        None,
+        Some(Box::new(move |_, offset, whence| {
+            cursor
+                .lock()
+                .map_or(ffi::AVERROR(ffi::EIO) as _, |mut c| c.seek(offset, whence))
+        })),
+    );
+    AVFormatContextInput::from_io_context(AVIOContextContainer::Custom(io_context))
+        .map_err(|e| Error::new_input(e.into()))
+}
+
 #[inline]
 pub fn make_input(source: &InputSource) -> Result<AVFormatContextInput> {
     match source {
         InputSource::Path(path) => try_from_path_input(path),
+        InputSource::Bytes(bytes) => try_from_shared_input(SharedSource::Bytes(bytes.clone())),
+        InputSource::Reader(reader) => try_from_shared_input(SharedSource::Reader(reader.clone())),
     }
 }
 
+/// Seeks to the keyframe at or before `timestamp` and drops every frame still buffered in the decoder.
 #[inline]
-pub fn seek_input(input: &mut AVFormatContextInput, timestamp: Timestamp) -> Result<()> {
+pub fn seek_input(
+    input: &mut AVFormatContextInput,
+    context: &mut AVFormatContentContexts,
+    timestamp: Timestamp,
+) -> Result<()> {
     input
         .seek(-1, timestamp.as_microseconds(), AVSEEK_FLAG_BACKWARD as _)
-        .map_err(|e| Error::new_input(e.into()))
+        .map_err(|e| Error::new_input(e.into()))?;
+    context.avcodec_context.flush_buffers();
+    Ok(())
 }
 
 #[inline]
@@ -33,15 +155,65 @@ pub fn to_timestamp(ts: i64, time_base: AVRational) -> Timestamp {
     Timestamp::from_microseconds(av_rescale_q(ts, time_base, AV_TIME_BASE_Q))
 }
 
+/// Time bases FFmpeg leaves unset (`0/0` or `0/1`) fall back to microseconds.
+#[inline]
+pub fn to_rational(time_base: AVRational) -> Rational {
+    Rational::checked_new(time_base.num as _, time_base.den as _)
+        .filter(|rational| rational.numerator() != 0)
+        .unwrap_or(Rational::MICROSECOND)
+}
+
+/// Lists the streams of `media_type` in container order.
+pub fn stream_infos(input: &AVFormatContextInput, media_type: ffi::AVMediaType) -> Vec<StreamInfo> {
+    input
+        .streams()
+        .iter()
+        .filter(|stream| stream.codecpar().codec_type == media_type)
+        .map(|stream| stream_info(stream))
+        .collect()
+}
+
+pub fn stream_info(stream: &AVStream) -> StreamInfo {
+    let metadata = |key: &CStr| {
+        stream.metadata().and_then(|metadata| {
+            metadata
+                .get(key, None, 0)
+                .map(|entry| entry.value().to_string_lossy().into_owned())
+        })
+    };
+    let codec = unsafe { CStr::from_ptr(ffi::avcodec_get_name(stream.codecpar().codec_id)) };
+    StreamInfo {
+        index: stream.index as _,
+        codec: codec.to_string_lossy().into_owned(),
+        language: metadata(c"language"),
+        title: metadata(c"title"),
+        is_default: stream.disposition & ffi::AV_DISPOSITION_DEFAULT as i32 != 0,
+        time_base: to_rational(stream.time_base),
+    }
+}
+
+/// Opens the decoder of `stream_index`, or of the best stream of `media_type` when omitted.
 #[inline]
 pub fn input_contexts(
     input: &AVFormatContextInput,
     media_type: ffi::AVMediaType,
+    stream_index: Option<usize>,
 ) -> Result<Option<AVFormatContentContexts>> {
-    if let Some((index, _)) = input
-        .find_best_stream(media_type)
-        .map_err(|e| Error::new_input(e.into()))?
-    {
+    let index = match stream_index {
+        Some(index) => match input.streams().get(index) {
+            Some(stream) if stream.codecpar().codec_type == media_type => Some(index),
+            _ => {
+                return Err(Error::new_input(anyhow!(
+                    "stream {index} is not a stream of the requested media type"
+                )));
+            }
+        },
+        None => input
+            .find_best_stream(media_type)
+            .map_err(|e| Error::new_input(e.into()))?
+            .map(|(index, _)| index),
+    };
+    if let Some(index) = index {
         let stream = &input.streams()[index];
         let mut avcodec_context =
             AVCodecContext::new(&AVCodec::find_decoder(stream.codecpar().codec_id).unwrap());
@@ -1,83 +1,187 @@
-use std::{collections::VecDeque, slice};
+use std::collections::VecDeque;
 
 use anyhow::anyhow;
-use rmf_core::{Content, Error, Result, Size, Timestamp, video::VideoContentCursor};
+use rmf_core::{
+    Content, Error, Rational, RationalTimestamp, Result, SeekMode, Size, Timestamp,
+    image::PixelFormat,
+    video::{VideoContentCursor, VideoCursorOptions},
+};
 use rmf_macros::delegate_implements;
 use rsmpeg::{
     avformat::AVFormatContextInput,
     avutil::AVFrame,
     error::RsmpegError,
-    ffi::{AV_PIX_FMT_RGBA, AVMEDIA_TYPE_VIDEO, SWS_BICUBIC},
+    ffi::{self, AVMEDIA_TYPE_VIDEO},
     swscale::SwsContext,
 };
 
 use crate::{
     Image,
-    ffmpeg::utils::{AVFormatContentContexts, input_contexts, seek_input, to_timestamp},
+    ffmpeg::{
+        pixel::{avframe_to_image, from_av_pixel_format, to_av_pixel_format, to_sws_flags},
+        utils::{AVFormatContentContexts, input_contexts, seek_input, to_rational},
+    },
 };
 
 pub struct AVFormatVideoContentCursor {
     input: AVFormatContextInput,
     offset: Timestamp,
+    native_offset: RationalTimestamp,
+    time_base: Rational,
     video_context: AVFormatContentContexts,
+    pixel_format: PixelFormat,
+    crop: Option<Crop>,
     scale_context: Option<ScaleContext>,
     video_cache: VecDeque<Content<Image>>,
+    drained: bool,
     fps: f64,
 }
 struct ScaleContext {
     sws_context: SwsContext,
-    frame_rgba: AVFrame,
+    size: Size,
+}
+/// Pixels removed from each edge of decoded frames before scaling.
+#[derive(Clone, Copy)]
+struct Crop {
+    left: usize,
+    top: usize,
+    right: usize,
+    bottom: usize,
 }
 
 impl AVFormatVideoContentCursor {
-    pub fn try_new(input: AVFormatContextInput, fps: f64) -> Result<Self> {
-        let video_context = input_contexts(&input, AVMEDIA_TYPE_VIDEO)?
+    pub fn try_new(
+        input: AVFormatContextInput,
+        stream_index: usize,
+        fps: f64,
+        options: VideoCursorOptions,
+    ) -> Result<Self> {
+        let video_context = input_contexts(&input, AVMEDIA_TYPE_VIDEO, Some(stream_index))?
             .ok_or_else(|| Error::new_input(anyhow!("Can not make input context")))?;
+        let decoder = &video_context.avcodec_context;
+        // Native formats the image can't hold fall back to RGBA.
+        let pixel_format = options
+            .pixel_format
+            .or_else(|| from_av_pixel_format(decoder.pix_fmt))
+            .unwrap_or(PixelFormat::Rgba);
+        let source_size = Size::new(decoder.width as _, decoder.height as _);
+        let (region, output_size) = match options.size {
+            Some(size) => options.aspect_ratio.resolve(source_size, size),
+            None => (source_size, source_size),
+        };
+        let crop = (region != source_size).then(|| {
+            let left = (source_size.width - region.width) / 2;
+            let top = (source_size.height - region.height) / 2;
+            Crop {
+                left: left as _,
+                top: top as _,
+                right: (source_size.width - region.width - left) as _,
+                bottom: (source_size.height - region.height - top) as _,
+            }
+        });
 
-        let scale_context = if video_context.avcodec_context.pix_fmt == AV_PIX_FMT_RGBA {
-            None
-        } else {
-            let sws_context = SwsContext::get_context(
-                video_context.avcodec_context.width,
-                video_context.avcodec_context.height,
-                video_context.avcodec_context.pix_fmt,
-                video_context.avcodec_context.width,
-                video_context.avcodec_context.height,
-                AV_PIX_FMT_RGBA,
-                SWS_BICUBIC,
-                None,
-                None,
-                None,
-            )
-            .ok_or_else(|| Error::new_video(anyhow!("Can't get sws context")))?;
-            let mut frame_rgba = AVFrame::default();
-            frame_rgba.set_format(AV_PIX_FMT_RGBA);
-            frame_rgba.set_width(video_context.avcodec_context.width);
-            frame_rgba.set_height(video_context.avcodec_context.height);
-            frame_rgba.get_buffer(0).unwrap();
+        let scale_context =
+            if decoder.pix_fmt == to_av_pixel_format(pixel_format) && region == output_size {
+                None
+            } else {
+                let sws_context = SwsContext::get_context(
+                    region.width as _,
+                    region.height as _,
+                    decoder.pix_fmt,
+                    output_size.width as _,
+                    output_size.height as _,
+                    to_av_pixel_format(pixel_format),
+                    to_sws_flags(options.scaling),
+                    None,
+                    None,
+                    None,
+                )
+                .ok_or_else(|| Error::new_video(anyhow!("Can't get sws context")))?;
 
-            Some(ScaleContext {
-                sws_context,
-                frame_rgba,
-            })
-        };
+                Some(ScaleContext {
+                    sws_context,
+                    size: output_size,
+                })
+            };
 
+        let time_base = to_rational(video_context.time_base);
         Ok(Self {
             input,
             offset: Timestamp::default(),
+            native_offset: RationalTimestamp::new(0, time_base),
+            time_base,
             video_context,
+            pixel_format,
+            crop,
             scale_context,
             video_cache: VecDeque::default(),
+            drained: false,
             fps,
         })
     }
-    fn avframe_to_image(frame: &AVFrame) -> Result<Image> {
-        let width = frame.width as usize;
-        let height = frame.height as usize;
+    /// Reads and discards content until the one covering `timestamp`, which is put back for the next read.
+    fn skip_to(&mut self, timestamp: Timestamp) -> Result<()> {
+        while let Some(content) = self.read()? {
+            if covers(content.offset(), content.duration(), self.fps, timestamp) {
+                self.offset = content.offset();
+                self.native_offset = content.native_offset();
+                self.video_cache.push_front(content);
+                break;
+            }
+        }
+        Ok(())
+    }
+    fn receive_frames(&mut self) -> Result<()> {
+        loop {
+            match self.video_context.avcodec_context.receive_frame() {
+                Ok(mut frame) => {
+                    if let Some(crop) = self.crop {
+                        let result = unsafe {
+                            let raw = frame.as_mut_ptr();
+                            (*raw).crop_left = crop.left;
+                            (*raw).crop_top = crop.top;
+                            (*raw).crop_right = crop.right;
+                            (*raw).crop_bottom = crop.bottom;
+                            ffi::av_frame_apply_cropping(raw, ffi::AV_FRAME_CROP_UNALIGNED as _)
+                        };
+                        // The scaler was set up for the cropped size, so an uncropped frame can't go on.
+                        if result < 0 {
+                            return Err(Error::new_video(RsmpegError::AVError(result).into()));
+                        }
+                    }
+                    let presentation_timestamp = RationalTimestamp::new(frame.pts, self.time_base);
+                    let duration_timestamp = RationalTimestamp::new(frame.duration, self.time_base);
+                    // Images keep the frame they wrap, so scaled output needs a fresh buffer each time.
+                    let frame = if let Some(scale_context) = &mut self.scale_context {
+                        let mut scaled = AVFrame::default();
+                        scaled.set_format(to_av_pixel_format(self.pixel_format));
+                        scaled.set_width(scale_context.size.width as _);
+                        scaled.set_height(scale_context.size.height as _);
+                        scaled
+                            .get_buffer(0)
+                            .map_err(|e| Error::new_video(e.into()))?;
+                        scale_context
+                            .sws_context
+                            .scale_frame(&frame, 0, frame.height, &mut scaled)
+                            .map_err(|e| Error::new_video(e.into()))?;
+                        scaled
+                    } else {
+                        frame
+                    };
 
-        let expected_total_size = width * height * 4;
-        let data = unsafe { slice::from_raw_parts(frame.data[0], expected_total_size) };
-        Image::new_size(Size::new(frame.width as _, frame.height as _), data)
+                    let image = avframe_to_image(frame, self.pixel_format)?;
+                    self.video_cache.push_back(Content::from_native(
+                        image,
+                        presentation_timestamp,
+                        duration_timestamp,
+                    ));
+                }
+                Err(RsmpegError::DecoderFlushedError | RsmpegError::DecoderDrainError) => {
+                    return Ok(());
+                }
+                Err(err) => return Err(Error::new_video(err.into())),
+            }
+        }
     }
 }
 
@@ -92,73 +196,103 @@ impl VideoContentCursor for AVFormatVideoContentCursor {
     fn fps(&self) -> f64 {
         self.fps
     }
+    #[inline]
+    fn time_base(&self) -> Rational {
+        self.time_base
+    }
+    #[inline]
+    fn native_offset(&self) -> RationalTimestamp {
+        self.native_offset
+    }
     fn read(&mut self) -> Result<Option<rmf_core::Content<Image>>> {
-        if let Some(content) = self.video_cache.pop_front() {
-            Ok(Some(content))
-        } else {
-            while let Some(packet) = self
+        while self.video_cache.is_empty() && !self.drained {
+            match self
                 .input
                 .read_packet()
                 .map_err(|e| Error::new_video(e.into()))?
             {
-                if packet.stream_index == self.video_context.index as _ {
+                Some(packet) if packet.stream_index == self.video_context.index as _ => {
                     self.video_context
                         .avcodec_context
                         .send_packet(Some(&packet))
                         .map_err(|e| Error::new_video(e.into()))?;
-                    loop {
-                        match self.video_context.avcodec_context.receive_frame() {
-                            Ok(frame) => {
-                                let presentation_timestamp =
-                                    to_timestamp(frame.pts, self.video_context.time_base);
-                                let duration_timestamp =
-                                    to_timestamp(frame.duration, self.video_context.time_base);
-                                let frame = if let Some(scale_context) = &mut self.scale_context {
-                                    scale_context
-                                        .sws_context
-                                        .scale_frame(
-                                            &frame,
-                                            0,
-                                            frame.height,
-                                            &mut scale_context.frame_rgba,
-                                        )
-                                        .map_err(|e| Error::new_video(e.into()))?;
-                                    &scale_context.frame_rgba
-                                } else {
-                                    &frame
-                                };
-
-                                let image = Self::avframe_to_image(frame)?;
-                                self.video_cache.push_back(Content::new(
-                                    image,
-                                    presentation_timestamp,
-                                    duration_timestamp,
-                                ));
-                            }
-                            Err(err) => {
-                                if err == RsmpegError::DecoderFlushedError
-                                    || err == RsmpegError::DecoderDrainError
-                                {
-                                    break;
-                                } else {
-                                    Err(Error::new_video(err.into()))?
-                                }
-                            }
-                        }
-                    }
-                    break;
+                    self.receive_frames()?;
+                }
+                Some(_) => {}
+                None => {
+                    // Lets the decoder release the frames it still holds for reordering or delay.
+                    self.video_context
+                        .avcodec_context
+                        .send_packet(None)
+                        .map_err(|e| Error::new_video(e.into()))?;
+                    self.receive_frames()?;
+                    self.drained = true;
                 }
             }
-            if let Some(video) = self.video_cache.pop_front() {
-                self.offset = video.offset();
-                Ok(Some(video))
-            } else {
-                Ok(None)
-            }
+        }
+        if let Some(video) = self.video_cache.pop_front() {
+            self.offset = video.offset();
+            self.native_offset = video.native_offset();
+            Ok(Some(video))
+        } else {
+            Ok(None)
         }
     }
     #[inline]
     fn seek(&mut self, timestamp: Timestamp) -> Result<()> {
-        seek_input(&mut self.input, timestamp)
+        self.seek_with_mode(timestamp, SeekMode::Keyframe)
+    }
+    fn seek_with_mode(&mut self, timestamp: Timestamp, mode: SeekMode) -> Result<()> {
+        seek_input(&mut self.input, &mut self.video_context, timestamp)?;
+        self.video_cache.clear();
+        self.drained = false;
+        if mode == SeekMode::Exact {
+            self.skip_to(timestamp)?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether a frame at `offset` is still on screen at `timestamp`.
+/// Frames without a duration last one frame at `fps`, or, without a rate, cover only timestamps they have reached.
+fn covers(offset: Timestamp, duration: Timestamp, fps: f64, timestamp: Timestamp) -> bool {
+    let duration = if duration != Timestamp::default() {
+        duration
+    } else if fps > 0.0 {
+        Timestamp::from_seconds_float64(1.0 / fps)
+    } else {
+        return offset >= timestamp;
+    };
+    offset.saturating_add(duration) > timestamp
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    fn ms(milliseconds: i64) -> Timestamp {
+        Timestamp::from_milliseconds(milliseconds)
+    }
+
+    #[rstest]
+    #[case(ms(0), ms(40), 25.0, ms(39), true)]
+    #[case(ms(0), ms(40), 25.0, ms(40), false)]
+    #[case(ms(0), ms(0), 25.0, ms(39), true)]
+    #[case(ms(0), ms(0), 25.0, ms(40), false)]
+    #[case(ms(0), ms(0), 0.0, ms(10), false)]
+    #[case(ms(10), ms(0), 0.0, ms(10), true)]
+    #[case(ms(10), ms(0), f64::NAN, ms(10), true)]
+    #[case(ms(0), ms(0), 1e-300, ms(10), true)]
+    #[case(Timestamp::from_microseconds(i64::MAX - 1), ms(40), 25.0, ms(10), true)]
+    fn covers_works(
+        #[case] offset: Timestamp,
+        #[case] duration: Timestamp,
+        #[case] fps: f64,
+        #[case] timestamp: Timestamp,
+        #[case] expected: bool,
+    ) {
+        assert_eq!(covers(offset, duration, fps, timestamp), expected);
     }
 }
@@ -1,7 +1,7 @@
 use anyhow::anyhow;
 use rmf_core::{
-    Error, InputSource, Result, Timestamp,
-    video::{VideoContentCursor, VideoInput},
+    Error, InputSource, Result, StreamInfo, Timestamp,
+    video::{VideoCursorOptions, VideoInput},
 };
 use rmf_macros::delegate_implements;
 use rsmpeg::ffi::{AVMEDIA_TYPE_VIDEO, av_q2d};
@@ -10,7 +10,7 @@ use crate::{
     Image,
     ffmpeg::{
         AVFormatVideoContentCursor,
-        utils::{input_contexts, make_input},
+        utils::{input_contexts, make_input, stream_infos},
     },
 };
 
@@ -19,12 +19,29 @@ pub struct AVFormatVideoInput {
     source: InputSource,
     duration: Timestamp,
     fps: f64,
+    streams: Vec<StreamInfo>,
+    stream_index: usize,
 }
 
 impl AVFormatVideoInput {
     pub fn try_new(source: InputSource) -> Result<AVFormatVideoInput> {
+        Self::try_new_stream(source, None)
+    }
+
+    /// Reads the video stream at container index `stream_index` instead of the best one.
+    pub fn try_new_with_stream(
+        source: InputSource,
+        stream_index: usize,
+    ) -> Result<AVFormatVideoInput> {
+        Self::try_new_stream(source, Some(stream_index))
+    }
+
+    fn try_new_stream(
+        source: InputSource,
+        stream_index: Option<usize>,
+    ) -> Result<AVFormatVideoInput> {
         let input = make_input(&source)?;
-        let context = input_contexts(&input, AVMEDIA_TYPE_VIDEO)?
+        let context = input_contexts(&input, AVMEDIA_TYPE_VIDEO, stream_index)?
             .ok_or_else(|| Error::new_input(anyhow!("not found video stream.")))?;
         let video_stream = &input.streams()[context.index];
         let fps = av_q2d(video_stream.r_frame_rate);
@@ -33,6 +50,8 @@ impl AVFormatVideoInput {
             source,
             fps,
             duration: Timestamp::from_microseconds(input.duration),
+            streams: stream_infos(&input, AVMEDIA_TYPE_VIDEO),
+            stream_index: context.index,
         })
     }
 }
@@ -44,9 +63,21 @@ impl VideoInput for AVFormatVideoInput {
     fn fps(&self) -> f64 {
         self.fps
     }
+    fn streams(&self) -> &[StreamInfo] {
+        &self.streams
+    }
+    fn stream_index(&self) -> usize {
+        self.stream_index
+    }
     fn cursor(&self) -> Result<AVFormatVideoContentCursor> {
+        self.cursor_with_options(VideoCursorOptions::default())
+    }
+    fn cursor_with_options(
+        &self,
+        options: VideoCursorOptions,
+    ) -> Result<AVFormatVideoContentCursor> {
         let input = make_input(&self.source)?;
-        AVFormatVideoContentCursor::try_new(input, self.fps)
+        AVFormatVideoContentCursor::try_new(input, self.stream_index, self.fps, options)
     }
     fn duration(&self) -> Timestamp {
         self.duration
@@ -1,6 +1,8 @@
 pub mod audio;
 pub mod core;
 pub mod image;
+pub mod output;
+pub mod probe;
 pub mod video;
 
 #[cfg(feature = "use_photon")]
@@ -0,0 +1,46 @@
+use rmf_macros::delegate_implements;
+
+use crate::{Audio, Image, ffmpeg::AVFormatOutput};
+
+pub struct DefaultOutput(AVFormatOutput);
+
+#[delegate_implements]
+impl rmf_core::OutputService for DefaultOutput {
+    type Image = Image;
+    type Audio = Audio;
+    #[inline]
+    fn has_video(&self) -> bool {
+        self.0.has_video()
+    }
+    #[inline]
+    fn has_audio(&self) -> bool {
+        self.0.has_audio()
+    }
+    #[inline]
+    fn write_video(&mut self, content: &rmf_core::Content<Image>) -> rmf_core::Result<()> {
+        self.0.write_video(content)
+    }
+    #[inline]
+    fn write_audio(&mut self, content: &rmf_core::Content<Audio>) -> rmf_core::Result<()> {
+        self.0.write_audio(content)
+    }
+    #[inline]
+    fn finish(&mut self) -> rmf_core::Result<()> {
+        self.0.finish()
+    }
+}
+
+pub struct DefaultOutputProvider;
+
+impl DefaultOutputProvider {
+    #[inline]
+    pub fn provide(
+        destination: rmf_core::OutputDestination,
+        options: rmf_core::output::OutputOptions,
+    ) -> rmf_core::Result<DefaultOutput> {
+        Ok(DefaultOutput(AVFormatOutput::try_new(
+            destination,
+            options,
+        )?))
+    }
+}
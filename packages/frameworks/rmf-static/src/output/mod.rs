@@ -0,0 +1,2 @@
+mod default_output;
+pub use default_output::*;
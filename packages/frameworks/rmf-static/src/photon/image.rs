@@ -1,26 +1,161 @@
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use rmf_core::image::{ImagePlane, PixelFormat};
 use rmf_macros::delegate_implements;
 
+use crate::core::{Error, Result, Size};
+
+/// Pixel memory owned elsewhere, such as a decoder frame, that images borrow instead of copying.
+pub trait SharedPlanes: Send + Sync {
+    fn plane(&self, index: usize) -> &[u8];
+    fn stride(&self, index: usize) -> usize;
+}
+
+#[derive(Clone)]
+enum Planes {
+    Owned(Arc<[ImagePlane]>),
+    Shared(Arc<dyn SharedPlanes>),
+}
+
+/// Cloning is cheap: plane memory is reference counted and never copied.
 #[derive(Clone)]
 pub struct Image {
-    inner: photon_rs::PhotonImage,
+    format: PixelFormat,
+    size: Size,
+    planes: Planes,
 }
 
 impl crate::core::InnerContent for Image {}
 
+impl Image {
+    /// Wraps `planes` without copying them.
+    pub fn from_shared_planes(
+        format: PixelFormat,
+        size: Size,
+        planes: Arc<dyn SharedPlanes>,
+    ) -> Result<Self> {
+        for index in 0..format.plane_count() {
+            validate_plane(
+                format,
+                size,
+                index,
+                planes.plane(index),
+                planes.stride(index),
+            )?;
+        }
+        Ok(Self {
+            format,
+            size,
+            planes: Planes::Shared(planes),
+        })
+    }
+
+    /// Converts to a photon image, which only exists for RGBA.
+    pub fn to_photon_image(&self) -> Option<photon_rs::PhotonImage> {
+        (self.format == PixelFormat::Rgba).then(|| {
+            photon_rs::PhotonImage::new(self.data_bytes(), self.size.width, self.size.height)
+        })
+    }
+}
+
+impl From<photon_rs::PhotonImage> for Image {
+    fn from(value: photon_rs::PhotonImage) -> Self {
+        let size = Size::new(value.get_width(), value.get_height());
+        Self {
+            format: PixelFormat::Rgba,
+            size,
+            planes: Planes::Owned(Arc::new([ImagePlane::new(
+                value.get_raw_pixels(),
+                size.width as usize * 4,
+            )])),
+        }
+    }
+}
+
 #[delegate_implements]
 impl rmf_core::image::Image for Image {
     fn data_bytes(&self) -> Vec<u8> {
-        self.inner.get_raw_pixels()
+        let mut data = vec![];
+        for index in 0..self.format.plane_count() {
+            if let (Some((row_bytes, rows)), Some(plane), Some(stride)) = (
+                self.format.plane_layout(index, self.size),
+                self.plane(index),
+                self.stride(index),
+            ) && stride > 0
+            {
+                plane
+                    .chunks(stride)
+                    .take(rows)
+                    .for_each(|row| data.extend_from_slice(&row[..row_bytes]));
+            }
+        }
+        data
+    }
+    fn size(&self) -> Size {
+        self.size
     }
-    fn size(&self) -> crate::core::Size {
-        crate::core::Size {
-            height: self.inner.get_height(),
-            width: self.inner.get_width(),
+    fn pixel_format(&self) -> PixelFormat {
+        self.format
+    }
+    fn plane(&self, index: usize) -> Option<&[u8]> {
+        match &self.planes {
+            Planes::Owned(planes) => planes.get(index).map(|plane| plane.data.as_slice()),
+            Planes::Shared(planes) => {
+                (index < self.format.plane_count()).then(|| planes.plane(index))
+            }
         }
     }
-    fn new_size(size: crate::core::Size, data: &[u8]) -> crate::core::Result<Self> {
+    fn stride(&self, index: usize) -> Option<usize> {
+        match &self.planes {
+            Planes::Owned(planes) => planes.get(index).map(|plane| plane.stride),
+            Planes::Shared(planes) => {
+                (index < self.format.plane_count()).then(|| planes.stride(index))
+            }
+        }
+    }
+    fn new_size(size: Size, data: &[u8]) -> Result<Self> {
+        Self::new_planes(
+            PixelFormat::Rgba,
+            size,
+            vec![ImagePlane::new(data.to_vec(), size.width as usize * 4)],
+        )
+    }
+    fn new_planes(format: PixelFormat, size: Size, planes: Vec<ImagePlane>) -> Result<Self> {
+        if planes.len() != format.plane_count() {
+            return Err(Error::new_video(anyhow!(
+                "{format:?} needs {} planes, got {}",
+                format.plane_count(),
+                planes.len()
+            )));
+        }
+        for (index, plane) in planes.iter().enumerate() {
+            validate_plane(format, size, index, &plane.data, plane.stride)?;
+        }
         Ok(Self {
-            inner: photon_rs::PhotonImage::new(data.to_vec(), size.width, size.height),
+            format,
+            size,
+            planes: Planes::Owned(planes.into()),
         })
     }
 }
+
+fn validate_plane(
+    format: PixelFormat,
+    size: Size,
+    index: usize,
+    data: &[u8],
+    stride: usize,
+) -> Result<()> {
+    let (row_bytes, rows) = format.plane_layout(index, size).unwrap_or_default();
+    let required = stride * rows.saturating_sub(1) + row_bytes;
+    if stride < row_bytes || (rows > 0 && data.len() < required) {
+        Err(Error::new_video(anyhow!(
+            "plane {index} of a {}x{} {format:?} image is too small",
+            size.width,
+            size.height
+        )))
+    } else {
+        Ok(())
+    }
+}
@@ -0,0 +1,12 @@
+use rmf_core::probe::ProbeReport;
+
+use crate::ffmpeg::probe_input;
+
+pub struct DefaultProbeProvider;
+
+impl DefaultProbeProvider {
+    #[inline]
+    pub fn probe(source: &rmf_core::InputSource) -> rmf_core::Result<ProbeReport> {
+        probe_input(source)
+    }
+}
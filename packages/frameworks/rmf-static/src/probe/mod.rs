@@ -0,0 +1,2 @@
+mod default_probe;
+pub use default_probe::*;
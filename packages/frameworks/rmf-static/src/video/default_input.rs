@@ -1,4 +1,4 @@
-use rmf_core::Timestamp;
+use rmf_core::{Rational, RationalTimestamp, Timestamp};
 use rmf_macros::delegate_implements;
 
 use crate::{
@@ -16,6 +16,14 @@ impl rmf_core::video::VideoContentCursor for DefaultVideoContentCursor {
         self.0.offset()
     }
     #[inline]
+    fn time_base(&self) -> Rational {
+        self.0.time_base()
+    }
+    #[inline]
+    fn native_offset(&self) -> RationalTimestamp {
+        self.0.native_offset()
+    }
+    #[inline]
     fn read(&mut self) -> rmf_core::Result<Option<rmf_core::Content<Image>>> {
         self.0.read()
     }
@@ -27,6 +35,14 @@ impl rmf_core::video::VideoContentCursor for DefaultVideoContentCursor {
     fn seek(&mut self, timestamp: rmf_core::Timestamp) -> rmf_core::Result<()> {
         self.0.seek(timestamp)
     }
+    #[inline]
+    fn seek_with_mode(
+        &mut self,
+        timestamp: rmf_core::Timestamp,
+        mode: rmf_core::SeekMode,
+    ) -> rmf_core::Result<()> {
+        self.0.seek_with_mode(timestamp, mode)
+    }
 }
 
 #[derive(Clone)]
@@ -45,9 +61,26 @@ impl rmf_core::video::VideoInput for DefaultVideoInput {
         self.0.duration()
     }
     #[inline]
+    fn streams(&self) -> &[rmf_core::StreamInfo] {
+        self.0.streams()
+    }
+    #[inline]
+    fn stream_index(&self) -> usize {
+        self.0.stream_index()
+    }
+    #[inline]
     fn cursor(&self) -> rmf_core::Result<DefaultVideoContentCursor> {
         Ok(DefaultVideoContentCursor(self.0.cursor()?))
     }
+    #[inline]
+    fn cursor_with_options(
+        &self,
+        options: rmf_core::video::VideoCursorOptions,
+    ) -> rmf_core::Result<DefaultVideoContentCursor> {
+        Ok(DefaultVideoContentCursor(
+            self.0.cursor_with_options(options)?,
+        ))
+    }
 }
 
 pub struct DefaultVideoInputProvider;
@@ -57,4 +90,14 @@ impl DefaultVideoInputProvider {
     pub fn provide(source: rmf_core::InputSource) -> rmf_core::Result<DefaultVideoInput> {
         Ok(DefaultVideoInput(AVFormatVideoInput::try_new(source)?))
     }
+    #[inline]
+    pub fn provide_stream(
+        source: rmf_core::InputSource,
+        stream_index: usize,
+    ) -> rmf_core::Result<DefaultVideoInput> {
+        Ok(DefaultVideoInput(AVFormatVideoInput::try_new_with_stream(
+            source,
+            stream_index,
+        )?))
+    }
 }
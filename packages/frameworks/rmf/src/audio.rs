@@ -1,6 +1,12 @@
 pub use crate::rmf_impl::{
-    Audio, AudioData, AudioDataContext, DefaultAudioContentCursor, DefaultAudioInput,
-    DefaultAudioInputProvider,
+    Audio, AudioChunker, AudioChunking, AudioData, AudioDataContext, AudioEffectChain,
+    AudioEffectContentCursor, ChunkedAudioContentCursor, DefaultAudioContentCursor,
+    DefaultAudioInput, DefaultAudioInputProvider, LoudnessAnalyzer, analyze_loudness,
+    apply_audio_effects,
 };
-pub use rmf_core::audio::AudioInput;
 pub use rmf_core::audio::{Audio as _, AudioData as _};
+pub use rmf_core::audio::{
+    AudioCursorOptions, AudioFormat, AudioInput, ChannelLayout, ChannelPosition, SampleLayout,
+};
+pub use rmf_core::audio_effect::*;
+pub use rmf_core::loudness::{LoudnessMeter, LoudnessPoint, LoudnessReport};
@@ -1,2 +1,3 @@
 pub use crate::rmf_impl::image::Image;
 pub use rmf_core::image::Image as _;
+pub use rmf_core::image::{ImagePlane, PixelFormat};
@@ -1,7 +1,10 @@
 pub mod audio;
 pub mod image;
+pub mod output;
+pub mod probe;
 pub mod video;
 pub use rmf_core::OutputService;
+pub use rmf_core::{Rational, RationalTimestamp, SeekMode, StreamInfo, TimeRange, Timecode};
 
 #[cfg(feature = "static_link")]
 use rmf_static as rmf_impl;
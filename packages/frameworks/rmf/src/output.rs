@@ -0,0 +1,6 @@
+pub use crate::rmf_impl::output::{DefaultOutput, DefaultOutputProvider};
+pub use rmf_core::output::{
+    AudioCodec, AudioEncoderOptions, ContainerFormat, OutputOptions, VideoCodec,
+    VideoEncoderOptions,
+};
+pub use rmf_core::{OutputDestination, OutputService};
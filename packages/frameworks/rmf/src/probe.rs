@@ -0,0 +1,4 @@
+pub use crate::rmf_impl::probe::DefaultProbeProvider;
+pub use rmf_core::probe::{
+    AudioStreamReport, MediaType, ProbeReport, StreamReport, VideoStreamReport,
+};
@@ -1,4 +1,4 @@
 pub use crate::rmf_impl::video::{
     DefaultVideoContentCursor, DefaultVideoInput, DefaultVideoInputProvider,
 };
-pub use rmf_core::video::VideoInput;
+pub use rmf_core::video::{AspectRatioPolicy, ScalingAlgorithm, VideoCursorOptions, VideoInput};